@@ -0,0 +1,67 @@
+//! `#[derive(CasperRecord)]`, re-exported by `casper_client`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Ident, parse_macro_input};
+
+/// Implement `casper_client::CasperRecord` for a struct with named fields.
+///
+/// Mark the vector ID (any type converting into `u32`) with `#[id]` and the
+/// vector (anything `AsRef<[f32]>`) with `#[vector]`:
+///
+/// ```ignore
+/// #[derive(CasperRecord)]
+/// struct Doc {
+///     #[id]
+///     id: u32,
+///     #[vector]
+///     embedding: Vec<f32>,
+///     title: String,
+/// }
+/// ```
+#[proc_macro_derive(CasperRecord, attributes(id, vector))]
+pub fn derive_casper_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(&input.ident, "CasperRecord can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(&input.ident, "CasperRecord requires named fields"));
+    };
+
+    let id = marked_field(fields.named.iter(), "id", &input.ident)?;
+    let vector = marked_field(fields.named.iter(), "vector", &input.ident)?;
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::casper_client::CasperRecord for #name #type_generics #where_clause {
+            fn record_id(&self) -> u32 {
+                ::core::convert::Into::<u32>::into(::core::clone::Clone::clone(&self.#id))
+            }
+
+            fn record_vector(&self) -> &[f32] {
+                ::core::convert::AsRef::<[f32]>::as_ref(&self.#vector)
+            }
+        }
+    })
+}
+
+/// The one field carrying `#[attribute]`
+fn marked_field<'a>(
+    fields: impl Iterator<Item = &'a syn::Field>,
+    attribute: &str,
+    name: &Ident,
+) -> syn::Result<&'a Ident> {
+    let mut marked = fields.filter(|field| field.attrs.iter().any(|attr| attr.path().is_ident(attribute)));
+    let Some(field) = marked.next() else {
+        return Err(Error::new_spanned(name, format!("CasperRecord requires a field marked #[{}]", attribute)));
+    };
+    if let Some(duplicate) = marked.next() {
+        return Err(Error::new_spanned(duplicate, format!("only one field can be marked #[{}]", attribute)));
+    }
+    Ok(field.ident.as_ref().expect("named field"))
+}
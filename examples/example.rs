@@ -34,6 +34,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let create_request = CreateCollectionRequest {
         dim: 128,
         max_size: 10000,
+        ..Default::default()
     };
     client.create_collection("example_collection", create_request).await?;
     println!("Collection 'example_collection' created successfully");
@@ -45,6 +46,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let insert_request = InsertRequest {
             id: i,
             vector,
+            payload: None,
         };
         client.insert_vector("example_collection", insert_request).await?;
         println!("Vector {} inserted", i);
@@ -55,10 +57,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut inserts = Vec::new();
     for i in 6..=10 {
         let vector = generate_random_vector(128, i as f32);
-        inserts.push(BatchInsertOperation { id: i, vector });
+        inserts.push(BatchInsertOperation { id: i, vector, payload: None });
     }
-    let batch_request = BatchUpdateRequest { insert: inserts, delete: vec![] };
-    client.batch_update("example_collection", batch_request).await?;
+    let batch_request = BatchUpdateRequest { insert: inserts, delete: vec![], upsert: false };
+    client.batch_update_results("example_collection", batch_request).await?;
     println!("Batch insert completed");
 
     // 4. Create HNSW index
@@ -74,7 +76,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         normalization: Some(true),
     };
-    client.create_hnsw_index("example_collection", hnsw_request).await?;
+    client.create_hnsw_index("example_collection", hnsw_request).await?.wait().await?;
     println!("HNSW index created");
 
     // 5. Search for similar vectors
@@ -83,8 +85,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let search_request = SearchRequest {
         vector: query_vector,
         limit: Some(5),
+        ..Default::default()
     };
-    let results = client.search("example_collection", 30, search_request).await?;
+    let results = client.query("example_collection", search_request).await?;
 
     println!("Found {} similar vectors:", results.len());
     for (i, result) in results.iter().enumerate() {
@@ -170,12 +173,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let res1 = client
         .upload_matrix(m1_name, dim, m1_vectors.clone(), 6)
         .await?;
-    println!("Uploaded matrix '{}' via gRPC: {}", m1_name, res1.message);
+    println!("Uploaded matrix '{}' via gRPC: {} rows", m1_name, res1.len);
 
     let res2 = client
         .upload_matrix(m2_name, dim, m2_vectors.clone(), 6)
         .await?;
-    println!("Uploaded matrix '{}' via gRPC: {}", m2_name, res2.message);
+    println!("Uploaded matrix '{}' via gRPC: {} rows", m2_name, res2.len);
 
     println!("\nListing matrices...");
     let matrices = client.list_matrices().await?;
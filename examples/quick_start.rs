@@ -19,13 +19,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .create_collection("example_collection", CreateCollectionRequest {
             dim: 128,
             max_size: 10_000,
+            ..Default::default()
         })
         .await?;
 
     // 2 Insert some vectors
     for i in 1..=5 {
         let vector = generate_random_vector(128, i as f32);
-        let insert_request = InsertRequest { id: i, vector };
+        let insert_request = InsertRequest { id: i, vector, payload: None };
         client.insert_vector("example_collection", insert_request).await?;
     }
 
@@ -33,10 +34,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut inserts = Vec::new();
     for i in 6..=10 {
         let vector = generate_random_vector(128, i as f32);
-        inserts.push(BatchInsertOperation { id: i, vector });
+        inserts.push(BatchInsertOperation { id: i, vector, payload: None });
     }
-    let batch_request = BatchUpdateRequest { insert: inserts, delete: vec![] };
-    client.batch_update("example_collection", batch_request).await?;
+    let batch_request = BatchUpdateRequest { insert: inserts, delete: vec![], upsert: false };
+    client.batch_update_results("example_collection", batch_request).await?;
 
     // 4 Create HNSW index
     let hnsw_request = CreateHNSWIndexRequest {
@@ -50,15 +51,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
         normalization: Some(true),
     };
-    client.create_hnsw_index("example_collection", hnsw_request).await?;
+    client.create_hnsw_index("example_collection", hnsw_request).await?.wait().await?;
 
     // 5 Search for similar vectors
     let query_vector = generate_random_vector(128, 1.0);
     let results = client
-        .search(
+        .query(
             "example_collection",
-            30,
-            SearchRequest { vector: query_vector, limit: Some(5) },
+            SearchRequest { vector: query_vector, limit: Some(5), ..Default::default() },
         )
         .await?;
 
@@ -0,0 +1,111 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Record of one mutating call, passed to the hook registered with
+/// [`crate::CasperClientBuilder::audit_hook`]
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    /// Operation name, e.g. `"insert"`, `"delete_collection"`, `"upload_matrix"`
+    pub operation: &'static str,
+    /// Collection, matrix or PQ the operation applies to
+    pub target: String,
+    /// Number of items (vectors, matrix rows) the operation carried
+    pub items: usize,
+    /// `Err` with the error message if the call failed
+    pub result: std::result::Result<(), String>,
+    pub duration: Duration,
+}
+
+/// Callback receiving every [`AuditEvent`]
+#[derive(Clone)]
+pub(crate) struct AuditHook(Arc<dyn Fn(&AuditEvent) + Send + Sync>);
+
+impl AuditHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&AuditEvent) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for AuditHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditHook")
+    }
+}
+
+/// Run `call`, reporting its outcome to `hook` if there is one
+pub(crate) async fn record<T, F>(
+    hook: Option<AuditHook>,
+    operation: &'static str,
+    target: &str,
+    items: usize,
+    call: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let Some(hook) = hook else {
+        return call.await;
+    };
+
+    let start = Instant::now();
+    let result = call.await;
+    (hook.0)(&AuditEvent {
+        operation,
+        target: target.to_string(),
+        items,
+        result: result.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        duration: start.elapsed(),
+    });
+    result
+}
+
+impl CasperClient {
+    /// Run a mutating `call` under the client's audit hook
+    pub(crate) async fn audited<T, F>(
+        &self,
+        operation: &'static str,
+        target: &str,
+        items: usize,
+        call: F,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        record(self.audit.clone(), operation, target, items, call).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CasperError;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_record_reports_outcome() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let hook = AuditHook::new(move |event| sink.lock().unwrap().push(event.clone()));
+
+        record(Some(hook.clone()), "insert", "docs", 3, async { Ok(()) }).await.unwrap();
+        let failed: Result<()> = record(Some(hook), "delete_collection", "docs", 0, async {
+            Err(CasperError::CollectionNotFound("docs".to_string()))
+        })
+        .await;
+        assert!(failed.is_err());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].operation, events[0].items), ("insert", 3));
+        assert!(events[0].result.is_ok());
+        assert_eq!(events[1].target, "docs");
+        assert!(events[1].result.is_err());
+    }
+}
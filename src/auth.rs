@@ -0,0 +1,110 @@
+use crate::error::{CasperError, Result};
+use std::fmt;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Class of operation a request belongs to, used to pick its credentials.
+///
+/// Servers issuing scoped tokens typically hand out read-only tokens to
+/// query services, write tokens to ingestion jobs and admin tokens for
+/// schema changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Searches, lookups and listings
+    Read,
+    /// Vector inserts, updates and deletes
+    Write,
+    /// Creating and deleting collections, indexes, matrices and PQs
+    Admin,
+}
+
+/// Bearer tokens per [`Scope`], configured on the builder
+#[derive(Clone, Default)]
+pub(crate) struct ScopedTokens {
+    read: Option<String>,
+    write: Option<String>,
+    admin: Option<String>,
+}
+
+impl ScopedTokens {
+    pub(crate) fn set(&mut self, scope: Scope, token: &str) {
+        let slot = match scope {
+            Scope::Read => &mut self.read,
+            Scope::Write => &mut self.write,
+            Scope::Admin => &mut self.admin,
+        };
+        *slot = Some(token.to_string());
+    }
+
+    pub(crate) fn get(&self, scope: Scope) -> Option<&str> {
+        match scope {
+            Scope::Read => self.read.as_deref(),
+            Scope::Write => self.write.as_deref(),
+            Scope::Admin => self.admin.as_deref(),
+        }
+    }
+}
+
+// Tokens must never end up in logs through `{:?}` of the client
+impl fmt::Debug for ScopedTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = |token: &Option<String>| token.as_ref().map(|_| "<redacted>");
+        f.debug_struct("ScopedTokens")
+            .field("read", &redact(&self.read))
+            .field("write", &redact(&self.write))
+            .field("admin", &redact(&self.admin))
+            .finish()
+    }
+}
+
+/// gRPC interceptor attaching a bearer token to every call
+#[derive(Clone, Default)]
+pub(crate) struct BearerAuth(Option<MetadataValue<Ascii>>);
+
+impl BearerAuth {
+    pub(crate) fn new(token: Option<&str>) -> Result<Self> {
+        token
+            .map(|token| {
+                format!("Bearer {}", token).parse().map_err(|_| {
+                    CasperError::InvalidArgument("token is not a valid header value".to_string())
+                })
+            })
+            .transpose()
+            .map(Self)
+    }
+}
+
+impl fmt::Debug for BearerAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BearerAuth").field(&self.0.as_ref().map(|_| "<redacted>")).finish()
+    }
+}
+
+impl Interceptor for BearerAuth {
+    fn call(&mut self, mut request: Request<()>) -> std::result::Result<Request<()>, Status> {
+        if let Some(value) = &self.0 {
+            request.metadata_mut().insert("authorization", value.clone());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_tokens() {
+        let mut tokens = ScopedTokens::default();
+        tokens.set(Scope::Read, "r");
+        tokens.set(Scope::Admin, "a");
+        assert_eq!(tokens.get(Scope::Read), Some("r"));
+        assert_eq!(tokens.get(Scope::Write), None);
+        assert!(!format!("{:?}", tokens).contains("\"r\""));
+
+        let mut auth = BearerAuth::new(Some("secret")).unwrap();
+        let request = auth.call(Request::new(())).unwrap();
+        assert_eq!(request.metadata().get("authorization").unwrap(), "Bearer secret");
+    }
+}
@@ -0,0 +1,258 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch, DEFAULT_SEARCH_LIMIT};
+use crate::error::{CasperError, Result};
+use crate::models::{SearchRequest, SearchResponse, SearchVectorBody};
+use crate::parallel;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+/// Searches in flight at once when a batch is pipelined
+const PIPELINE_PARALLELISM: usize = 32;
+
+/// Batch search request body
+#[derive(Debug, Serialize)]
+struct BatchSearchBody {
+    queries: Vec<BatchQueryBody>,
+}
+
+/// One query of a batch, with the parameters it overrides
+#[derive(Debug, Serialize)]
+struct BatchQueryBody {
+    #[serde(flatten)]
+    body: SearchVectorBody,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ef: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nprobe: Option<usize>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    include_deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score_threshold: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_time_ms: Option<u64>,
+}
+
+/// Batch search response: one result list per query, in query order,
+/// optionally wrapped in `{"results": [...]}`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchSearchResults {
+    List(Vec<SearchResponse>),
+    Wrapped { results: Vec<SearchResponse> },
+}
+
+impl CasperClient {
+    /// Run several searches, returning up to `limit` results for each in
+    /// query order.
+    ///
+    /// The queries are sent as one request, saving the per-request overhead
+    /// of issuing thousands of small searches. Servers without the batch
+    /// endpoint (404, 405 or 501) get the searches pipelined instead, 32 at
+    /// a time, failing on the first error. `limit` replaces the queries' own
+    /// [`SearchRequest::limit`], and [`SearchRequest::ef`] is only honored
+    /// when pipelining; the other parameters are sent with each query.
+    /// Scores are on the client's [`crate::ScoreScale`] either way.
+    pub async fn search_batch(
+        &self,
+        collection_name: &str,
+        queries: Vec<SearchRequest>,
+        limit: usize,
+    ) -> Result<Vec<SearchResponse>> {
+        let queries = queries.into_iter().map(|query| SearchRequest { limit: Some(limit), ..query }).collect();
+        self.send_batch(collection_name, queries, false).await
+    }
+
+    /// [`CasperClient::search_batch`] with each query's own
+    /// [`SearchRequest::limit`], [`SearchRequest::filter`] and
+    /// [`SearchRequest::ef`], e.g. to multiplex the searches of several
+    /// tenants. Results are paired with the index of their query.
+    ///
+    /// Batch endpoints that ignore per-query limits are asked for the
+    /// largest one and their lists are cut to each query's limit; `ef` is
+    /// only honored by servers taking it per query, and when pipelining.
+    pub async fn search_batch_with(
+        &self,
+        collection_name: &str,
+        queries: Vec<SearchRequest>,
+    ) -> Result<Vec<(usize, SearchResponse)>> {
+        let limits: Vec<usize> = queries.iter().map(|query| query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)).collect();
+        let results = self.send_batch(collection_name, queries, true).await?;
+        Ok(results
+            .into_iter()
+            .zip(limits)
+            .map(|(mut results, limit)| {
+                results.truncate(limit);
+                results
+            })
+            .enumerate()
+            .collect())
+    }
+
+    /// Send `queries` as one batch, each with its limit set, with their own
+    /// limits and `ef` in the body if `per_query`
+    async fn send_batch(
+        &self,
+        collection_name: &str,
+        queries: Vec<SearchRequest>,
+        per_query: bool,
+    ) -> Result<Vec<SearchResponse>> {
+        if queries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let limit = queries.iter().filter_map(|query| query.limit).max().unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let mut bodies = Vec::with_capacity(queries.len());
+        let mut cutoffs = Vec::with_capacity(queries.len());
+        for query in &queries {
+            let query = self.prepare_query(collection_name, query.clone()).await?;
+            self.check_search(&query)?;
+            cutoffs.push(self.cutoffs(collection_name, &query).await?);
+            bodies.push(BatchQueryBody {
+                body: SearchVectorBody { vector: query.vector, filter: query.filter, decay: query.decay },
+                limit: query.limit.filter(|_| per_query),
+                ef: query.ef.filter(|_| per_query),
+                nprobe: query.nprobe,
+                include_deleted: query.include_deleted,
+                score_threshold: query.score_threshold,
+                max_time_ms: query.max_time_ms,
+            });
+        }
+
+        let url = self.collection_url(collection_name, "/search/batch")?;
+        let response = self
+            .http(Scope::Read, Method::POST, url)
+            .query(&[("limit", limit.to_string())])
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(self.json_body(&BatchSearchBody { queries: bodies })?)
+            .dispatch(self)
+            .await?;
+        match response.status().as_u16() {
+            405 | 501 => return self.search_pipelined(collection_name, queries).await,
+            // A missing collection answers 404 too, which pipelining would
+            // only repeat once per query
+            404 => {
+                self.get_collection(collection_name).await?;
+                return self.search_pipelined(collection_name, queries).await;
+            }
+            _ => {}
+        }
+
+        let (BatchSearchResults::List(mut results) | BatchSearchResults::Wrapped { mut results }) =
+            self.handle_response(response).await?;
+        if results.len() != queries.len() {
+            return Err(CasperError::InvalidResponse(format!(
+                "batch search returned {} result lists for {} queries",
+                results.len(),
+                queries.len()
+            )));
+        }
+        // Servers may ignore the threshold, as for single searches
+        for (results, cutoffs) in results.iter_mut().zip(&cutoffs) {
+            cutoffs.apply(results);
+            self.rescale(collection_name, self.score_scale, results).await?;
+        }
+        Ok(results)
+    }
+
+    /// A batch as concurrent single searches
+    async fn search_pipelined(
+        &self,
+        collection_name: &str,
+        queries: Vec<SearchRequest>,
+    ) -> Result<Vec<SearchResponse>> {
+        let searches = parallel::fan_out(queries, PIPELINE_PARALLELISM, |query| {
+            let client = self.clone();
+            let collection_name = collection_name.to_string();
+            async move { client.query(&collection_name, query).await }
+        });
+        searches.collect().await?.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn test_search_batch() {
+        let scripted = Scripted::new([
+            (200, r#"{"results":[[[1,0.9]],[[2,0.8],[3,0.7]]]}"#),
+            (404, "not found"),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":2}"#),
+            (200, r#"[[4,0.6]]"#),
+            (200, r#"[[5,0.5]]"#),
+            (404, r#"{"error":"collection not found"}"#),
+            (404, r#"{"error":"collection not found"}"#),
+        ]);
+        let client = scripted.client();
+        let queries = vec![
+            SearchRequest { vector: vec![1.0, 0.0], ..Default::default() },
+            SearchRequest { vector: vec![0.0, 1.0], ..Default::default() },
+        ];
+
+        let batched = client.search_batch("docs", queries.clone(), 2).await.unwrap();
+        assert_eq!((batched[0][0].id, batched[1].len()), (1, 2));
+
+        // Without the batch endpoint the searches are sent one by one
+        let pipelined = client.search_batch("docs", queries, 2).await.unwrap();
+        assert_eq!((pipelined[0][0].id, pipelined[1][0].id), (4, 5));
+        assert_eq!(scripted.last_query("limit").as_deref(), Some("2"));
+        assert_eq!(scripted.requests()[..3], [
+            "POST /collection/docs/search/batch",
+            "POST /collection/docs/search/batch",
+            "GET /collection/docs"
+        ]);
+
+        // A missing collection is not retried as single searches
+        let missing = client.search_batch("docs", vec![SearchRequest::default()], 2).await;
+        assert!(matches!(missing, Err(CasperError::CollectionNotFound(_))));
+        assert_eq!(scripted.requests().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_with() {
+        let scripted = Scripted::new([(200, r#"[[[1,0.9],[2,0.8],[3,0.7]],[[4,0.6],[5,0.5],[6,0.4]]]"#)]);
+        let queries = vec![
+            SearchRequest { vector: vec![1.0, 0.0], limit: Some(1), ..Default::default() },
+            SearchRequest { vector: vec![0.0, 1.0], limit: Some(3), ef: Some(64), ..Default::default() },
+        ];
+
+        let results = scripted.client().search_batch_with("docs", queries).await.unwrap();
+        assert_eq!((results[0].0, results[0].1.len()), (0, 1));
+        assert_eq!((results[1].0, results[1].1.len()), (1, 3));
+        assert_eq!(scripted.last_query("limit").as_deref(), Some("3"));
+        let body = scripted.last_json();
+        let overrides: Vec<_> = body["queries"].as_array().unwrap().iter().map(|q| (&q["limit"], &q["ef"])).collect();
+        assert_eq!(overrides, [(&json!(1), &Value::Null), (&json!(3), &json!(64))]);
+    }
+
+    #[tokio::test]
+    async fn test_search_batch_on_client_scale() {
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":1,
+            "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+            "normalization":false}}"#;
+        let scripted = Scripted::new([
+            (200, r#"[[[1,3.0]]]"#),
+            (200, docs),
+            (404, "not found"),
+            (200, docs),
+            (200, r#"[[1,3.0]]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(crate::ScoreScale::Similarity)
+            .build()
+            .unwrap();
+        let queries = vec![SearchRequest { vector: vec![1.0, 0.0], ..Default::default() }];
+
+        let batched = client.search_batch("docs", queries.clone(), 1).await.unwrap();
+        let pipelined = client.search_batch("docs", queries, 1).await.unwrap();
+        assert_eq!((batched[0][0].score, pipelined[0][0].score), (0.25, 0.25));
+        assert_eq!(scripted.requests().len(), 5);
+    }
+}
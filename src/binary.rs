@@ -0,0 +1,116 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::encoding::SearchEncoding;
+use crate::error::{CasperError, Result};
+use crate::models::{
+    BatchResult, BinaryBatchInsertRequest, BinaryInsertOperation, BinarySearchBody,
+    BinarySearchResult,
+};
+use crate::outbox::WriteOp;
+use reqwest::Method;
+
+/// Number of bytes needed to pack `dim` bits
+pub fn packed_len(dim: usize) -> usize {
+    dim.div_ceil(8)
+}
+
+/// Pack bits MSB-first into bytes; the last byte is zero-padded
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| byte | ((bit as u8) << (7 - i)))
+        })
+        .collect()
+}
+
+/// Unpack the first `dim` bits of an MSB-first packed buffer
+pub fn unpack_bits(packed: &[u8], dim: usize) -> Vec<bool> {
+    (0..dim.min(packed.len() * 8))
+        .map(|i| packed[i / 8] & (1 << (7 - i % 8)) != 0)
+        .collect()
+}
+
+/// Binarize a float vector by sign (`x > 0` -> 1) and pack it
+pub fn binarize(vector: &[f32]) -> Vec<u8> {
+    let bits: Vec<bool> = vector.iter().map(|&x| x > 0.0).collect();
+    pack_bits(&bits)
+}
+
+/// Number of differing bits between two packed vectors of equal length
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+impl CasperClient {
+    /// Insert packed binary vectors into a binary collection
+    pub async fn insert_binary_vectors(
+        &self,
+        collection_name: &str,
+        inserts: Vec<BinaryInsertOperation>,
+    ) -> Result<BatchResult> {
+        let ids = inserts.iter().map(|op| op.id).collect();
+        let body = self
+            .write(collection_name, WriteOp::InsertBinary(BinaryBatchInsertRequest { insert: inserts }))
+            .await?;
+        self.parse_batch_result(ids, body.as_deref())
+    }
+
+    /// Hamming-distance search in a binary collection.
+    ///
+    /// `bits` is the packed query (see [`pack_bits`] / [`binarize`]). Results
+    /// are ordered by increasing distance.
+    pub async fn search_binary(
+        &self,
+        collection_name: &str,
+        bits: Vec<u8>,
+        limit: usize,
+    ) -> Result<Vec<BinarySearchResult>> {
+        let url = self.collection_url(collection_name, "/search")?;
+        let response = self
+            .http(Scope::Read, Method::POST, url)
+            .query(&[
+                ("limit", limit.to_string()),
+                ("metric", "hamming".to_string()),
+                ("output", "bin".to_string()),
+            ])
+            .header("Content-Type", "application/json")
+            .header("Accept", SearchEncoding::Binary.accept())
+            .json(&BinarySearchBody { bits })
+            .dispatch(self)
+            .await?;
+
+        let results = self.handle_search_response(response).await?;
+        results
+            .into_iter()
+            .map(|r| {
+                if r.score < 0.0 || r.score.fract() != 0.0 {
+                    return Err(CasperError::InvalidResponse(format!(
+                        "invalid Hamming distance {} for id {}",
+                        r.score, r.id
+                    )));
+                }
+                Ok(BinarySearchResult { id: r.id, distance: r.score as u32 })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack_and_distance() {
+        let bits = vec![true, false, true, true, false, false, false, true, true, true];
+        let packed = pack_bits(&bits);
+        assert_eq!(packed, vec![0b1011_0001, 0b1100_0000]);
+        assert_eq!(packed.len(), packed_len(bits.len()));
+        assert_eq!(unpack_bits(&packed, bits.len()), bits);
+
+        assert_eq!(binarize(&[0.5, -0.1, 0.0, 2.0]), vec![0b1001_0000]);
+        assert_eq!(hamming_distance(&[0b1111_0000], &[0b1010_0101]), 4);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::models::{SearchRequest, SearchResponse};
+use crate::parallel;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Searches of a budgeted batch in flight at once
+const BUDGET_PARALLELISM: usize = 32;
+
+impl CasperClient {
+    /// Run several searches concurrently within a time budget, returning
+    /// whatever has arrived when it runs out.
+    ///
+    /// Results are in input order: `Some` once a search completed
+    /// (successfully or not), `None` if it was still running at the
+    /// deadline; unfinished searches are cancelled. Requests without their
+    /// own [`SearchRequest::max_time_ms`] pass the remaining budget to the
+    /// server, so it can answer with partial results in time.
+    pub async fn query_within(
+        &self,
+        collection_name: &str,
+        requests: Vec<SearchRequest>,
+        budget: Duration,
+    ) -> Result<Vec<Option<Result<SearchResponse>>>> {
+        let deadline = Instant::now() + budget;
+        let mut results: Vec<Option<Result<SearchResponse>>> = requests.iter().map(|_| None).collect();
+        let mut searches = parallel::fan_out(requests, BUDGET_PARALLELISM, |mut request| {
+            let client = self.clone();
+            let collection_name = collection_name.to_string();
+            async move {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                request.max_time_ms.get_or_insert(remaining.as_millis() as u64);
+                client.query(&collection_name, request).await
+            }
+        });
+
+        while Instant::now() < deadline {
+            let Ok(Some(joined)) = tokio::time::timeout_at(deadline, searches.next()).await else {
+                break;
+            };
+            let (idx, result) = joined?;
+            results[idx] = Some(result);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_query_within() {
+        let transport = Scripted::new([(200, r#"[{"id":7,"score":0.9}]"#)]);
+        let client = transport.client();
+        let request = SearchRequest { vector: vec![1.0, 0.0], ..Default::default() };
+
+        let results = client.query_within("docs", vec![request.clone()], Duration::from_secs(5)).await.unwrap();
+        assert_eq!(results[0].as_ref().unwrap().as_ref().unwrap()[0].id, 7);
+        let max_time: u64 = transport.last_query("max_time_ms").unwrap().parse().unwrap();
+        assert!(max_time > 4_000 && max_time <= 5_000);
+
+        // Nothing can arrive within a zero budget
+        let late = client.query_within("docs", vec![request], Duration::ZERO).await.unwrap();
+        assert!(late[0].is_none());
+    }
+}
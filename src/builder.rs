@@ -0,0 +1,519 @@
+use crate::audit::{AuditEvent, AuditHook};
+use crate::auth::{Scope, ScopedTokens};
+use crate::cache::{SearchCache, SearchCachePolicy};
+use crate::client::CasperClient;
+use crate::coalesce::InFlight;
+#[cfg(feature = "zstd")]
+use crate::compression::{Compression, Compressor};
+use crate::encoding::{FloatFormat, SearchEncoding};
+use crate::endpoints::Endpoints;
+use crate::error::Result;
+use crate::hedge::{HedgePolicy, Hedger};
+use crate::limits::RequestLimits;
+use crate::dimensions::Dimensions;
+use crate::normalize::Normalization;
+use crate::outbox::Outbox;
+use crate::probe::{ProbePolicy, Prober};
+use crate::preflight::Preflight;
+use crate::progress::{Progress, ProgressHook};
+use crate::protect::DeleteProtection;
+use crate::reduce::DimReducer;
+use crate::retry::RetryPolicy;
+use crate::scores::ScoreScale;
+use crate::service::{BoxError, HttpLayers, HttpService};
+use crate::signing::RequestSigner;
+use crate::slow::{SlowCall, SlowCallPolicy};
+use crate::timeouts::Timeouts;
+use crate::transport::HttpTransport;
+use crate::upload::GrpcChannel;
+use crate::version::{ApiState, ApiVersion};
+use reqwest::{Client, Request, Response};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::{Layer, Service};
+
+/// Builder for [`CasperClient`] with optional client-level behaviour.
+///
+/// ```no_run
+/// # fn main() -> casper_client::Result<()> {
+/// use casper_client::CasperClient;
+/// use std::time::Duration;
+///
+/// let client = CasperClient::builder("http://localhost", 8080, 50051)
+///     .timeout(Duration::from_secs(10))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CasperClientBuilder {
+    endpoints: Endpoints,
+    timeouts: Timeouts,
+    outbox: Option<Outbox>,
+    float_format: FloatFormat,
+    reducers: HashMap<String, DimReducer>,
+    protection: DeleteProtection,
+    read_only: bool,
+    tokens: ScopedTokens,
+    signer: Option<RequestSigner>,
+    audit: Option<AuditHook>,
+    progress: Option<ProgressHook>,
+    namespace: Option<String>,
+    slow_calls: SlowCallPolicy,
+    hedging: Option<HedgePolicy>,
+    probing: Option<ProbePolicy>,
+    search_cache: Option<SearchCachePolicy>,
+    coalesce_searches: bool,
+    retry: RetryPolicy,
+    search_encoding: SearchEncoding,
+    score_scale: ScoreScale,
+    api_version: ApiVersion,
+    auto_normalize: bool,
+    validate_dimensions: bool,
+    layers: HttpLayers,
+    transport: Option<Arc<dyn HttpTransport>>,
+    preflight: Preflight,
+    index_wait: Option<Duration>,
+    memory_budget: Option<usize>,
+    limits: RequestLimits,
+    http_upload_fallback: bool,
+    #[cfg(feature = "zstd")]
+    compression: Option<Compression>,
+}
+
+impl CasperClientBuilder {
+    /// Builder for a client of the server at `endpoints`
+    pub fn new(endpoints: Endpoints) -> Self {
+        Self {
+            endpoints,
+            timeouts: Timeouts::default(),
+            outbox: None,
+            float_format: FloatFormat::default(),
+            reducers: HashMap::new(),
+            protection: DeleteProtection::default(),
+            read_only: false,
+            tokens: ScopedTokens::default(),
+            signer: None,
+            audit: None,
+            progress: None,
+            namespace: None,
+            slow_calls: SlowCallPolicy::default(),
+            hedging: None,
+            probing: None,
+            search_cache: None,
+            coalesce_searches: false,
+            retry: RetryPolicy::default(),
+            search_encoding: SearchEncoding::default(),
+            score_scale: ScoreScale::Raw,
+            api_version: ApiVersion::CURRENT,
+            auto_normalize: false,
+            validate_dimensions: false,
+            layers: HttpLayers::new(),
+            transport: None,
+            preflight: Preflight::default(),
+            index_wait: None,
+            memory_budget: None,
+            limits: RequestLimits::default(),
+            http_upload_fallback: false,
+            #[cfg(feature = "zstd")]
+            compression: None,
+        }
+    }
+
+    /// Timeout of each HTTP attempt (default 30s), shorthand for
+    /// [`Timeouts::attempt`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts = self.timeouts.attempt(timeout);
+        self
+    }
+
+    /// Connect, per-attempt and whole-operation timeouts, see [`Timeouts`]
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Queue writes in a durable outbox while the server is unreachable.
+    ///
+    /// See [`Outbox`] for the delivery semantics.
+    pub fn outbox(mut self, outbox: Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// How vector floats are written in JSON request bodies.
+    ///
+    /// Reduced precision (e.g. `FloatFormat::Significant(6)`) shrinks insert
+    /// and search payloads substantially when full f32 precision isn't needed.
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Response encoding requested for searches (default binary).
+    ///
+    /// Whichever encoding the server actually answers with is parsed, based
+    /// on the response `Content-Type`.
+    pub fn search_encoding(mut self, encoding: SearchEncoding) -> Self {
+        self.search_encoding = encoding;
+        self
+    }
+
+    /// Scale of the scores returned by every search of the client, native
+    /// or emulated (default [`ScoreScale::Raw`]).
+    ///
+    /// Rescaling looks up the collection's index metric with each search.
+    pub fn score_scale(mut self, scale: ScoreScale) -> Self {
+        self.score_scale = scale;
+        self
+    }
+
+    /// Pin the API version requests are shaped for (default
+    /// [`ApiVersion::CURRENT`]), e.g. to keep talking to a server that is
+    /// upgraded later; features of newer versions are then not used.
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.api_version = version;
+        self
+    }
+
+    /// Reduce vectors of `collection_name` with `reducer` before every insert
+    /// and search, so stored and query vectors stay in the same space.
+    pub fn dim_reducer(mut self, collection_name: &str, reducer: DimReducer) -> Self {
+        self.reducers.insert(collection_name.to_string(), reducer);
+        self
+    }
+
+    /// Normalize vectors to unit length before inserting them into, or
+    /// searching, a collection whose index was created with
+    /// `normalization: true` (default off).
+    ///
+    /// Keeps stored and query vectors consistent when some writers send
+    /// unnormalized data. Zero vectors are then rejected with
+    /// [`crate::CasperError::ZeroNormVector`].
+    pub fn auto_normalize(mut self, enabled: bool) -> Self {
+        self.auto_normalize = enabled;
+        self
+    }
+
+    /// Check the length of insert and search vectors against the collection
+    /// dimension before sending them (default off), failing with
+    /// [`crate::CasperError::InvalidDimension`] instead of a server error.
+    ///
+    /// The dimension is fetched once per collection and cached until the
+    /// client deletes or recreates the collection.
+    pub fn validate_dimensions(mut self, enabled: bool) -> Self {
+        self.validate_dimensions = enabled;
+        self
+    }
+
+    /// Wrap every HTTP call in tower middleware, e.g. a timeout or a
+    /// concurrency limit. Layers added later wrap earlier ones.
+    ///
+    /// Middleware sees built requests, outside retries and signing. Its own
+    /// errors surface as [`crate::CasperError::Middleware`].
+    ///
+    /// ```no_run
+    /// # fn main() -> casper_client::Result<()> {
+    /// use casper_client::CasperClient;
+    /// use tower::limit::ConcurrencyLimitLayer;
+    ///
+    /// let client = CasperClient::builder("http://localhost", 8080, 50051)
+    ///     .http_layer(ConcurrencyLimitLayer::new(16))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn http_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<HttpService> + Send + 'static,
+        L::Service: Service<Request, Response = Response> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Error: Into<BoxError>,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Send HTTP requests through `transport` instead of reqwest, see
+    /// [`HttpTransport`]. The transport should honor the attempt timeout set
+    /// on each request ([`Request::timeout`]) and its own connect timeout.
+    pub fn transport<T: HttpTransport>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Retry writes rejected with
+    /// [`CasperError::IndexCreationInProgress`](crate::CasperError::IndexCreationInProgress)
+    /// instead of failing: the collection is polled with backoff until its
+    /// index is reported, for at most `max_wait` per write (default off).
+    ///
+    /// Streamed batch updates are not retried.
+    pub fn wait_for_index(mut self, max_wait: Duration) -> Self {
+        self.index_wait = Some(max_wait);
+        self
+    }
+
+    /// Bound the memory bulk helpers buffer on top of the caller's data to
+    /// about `bytes`, by shrinking their chunks and in-flight buffers
+    /// (default unbounded).
+    ///
+    /// Applies to chunked and streamed inserts and to matrix uploads. Chunks
+    /// never go below one item (one row for matrices), so a very small
+    /// budget only slows operations down.
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Reject requests over the server's size limits locally (default
+    /// unbounded), see [`RequestLimits`]
+    pub fn request_limits(mut self, limits: RequestLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Upload matrices through the server's chunked HTTP endpoint when the
+    /// gRPC endpoint cannot be reached (default off).
+    ///
+    /// Without the fallback, or on servers without the HTTP endpoint,
+    /// uploads fail with [`CasperError::GrpcUnavailable`](crate::CasperError::GrpcUnavailable).
+    /// HTTP uploads send one chunk at a time, unsharded, so they are slower
+    /// than gRPC streams.
+    pub fn http_upload_fallback(mut self, enabled: bool) -> Self {
+        self.http_upload_fallback = enabled;
+        self
+    }
+
+    /// Compress large request bodies and export files with zstd, and accept
+    /// zstd responses (default off), see [`crate::compression`].
+    ///
+    /// Request bodies are only compressed once the server has advertised
+    /// zstd support, so the first requests of a client go out as is.
+    #[cfg(feature = "zstd")]
+    pub fn compression(mut self, settings: Compression) -> Self {
+        self.compression = Some(settings);
+        self
+    }
+
+    /// Checks run by [`connect`](Self::connect) (default [`Preflight::default`])
+    pub fn preflight(mut self, checks: Preflight) -> Self {
+        self.preflight = checks;
+        self
+    }
+
+    /// Build the client and run the [`preflight`](Self::preflight) checks
+    /// against the server
+    pub async fn connect(mut self) -> Result<CasperClient> {
+        let checks = std::mem::take(&mut self.preflight);
+        let client = self.build()?;
+        client.preflight(&checks).await?;
+        Ok(client)
+    }
+
+    /// Protect collections, indexes, matrices and PQs whose name matches
+    /// `pattern` from deletion.
+    ///
+    /// `*` matches any run of characters; `protect("*")` turns on delete
+    /// protection for everything. Protected deletes fail with
+    /// [`crate::CasperError::OperationNotAllowed`] unless issued through
+    /// [`CasperClient::force`].
+    pub fn protect(mut self, pattern: &str) -> Self {
+        self.protection.add(pattern);
+        self
+    }
+
+    /// Reject every mutating call locally with
+    /// [`crate::CasperError::OperationNotAllowed`], without contacting the
+    /// server. Meant for services that only ever read.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Bearer token sent with every request, whatever its [`Scope`]
+    pub fn token(mut self, token: &str) -> Self {
+        for scope in [Scope::Read, Scope::Write, Scope::Admin] {
+            self.tokens.set(scope, token);
+        }
+        self
+    }
+
+    /// Bearer token for requests of one [`Scope`], overriding
+    /// [`CasperClientBuilder::token`] for that scope.
+    ///
+    /// Each endpoint is mapped to its scope automatically: searches and
+    /// lookups use `Read`, vector writes `Write`, and collection, index,
+    /// matrix and PQ management (including gRPC uploads) `Admin`.
+    pub fn scoped_token(mut self, scope: Scope, token: &str) -> Self {
+        self.tokens.set(scope, token);
+        self
+    }
+
+    /// Sign every HTTP request with HMAC-SHA256, see [`RequestSigner`]
+    pub fn request_signer(mut self, signer: RequestSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Call `hook` after every mutating operation (writes, collection, index,
+    /// matrix and PQ management, uploads) with what was done and how it ended.
+    ///
+    /// The hook runs inline on the calling task and should return quickly.
+    pub fn audit_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&AuditEvent) + Send + Sync + 'static,
+    {
+        self.audit = Some(AuditHook::new(hook));
+        self
+    }
+
+    /// Call `hook` as long operations advance: matrix uploads, streamed
+    /// batch updates and waits for an index build.
+    ///
+    /// Every operation reports the same [`Progress`] shape, so one progress
+    /// bar renders them all. The hook runs inline on the task doing the
+    /// work; forward events to a channel to consume them as a stream:
+    ///
+    /// ```no_run
+    /// let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    /// let client = casper_client::CasperClient::builder("http://localhost", 8080, 50051)
+    ///     .progress_hook(move |progress| {
+    ///         let _ = tx.send(progress.clone());
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), casper_client::CasperError>(())
+    /// ```
+    pub fn progress_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some(ProgressHook::new(hook));
+        self
+    }
+
+    /// Scope the client to a tenant namespace, see
+    /// [`CasperClient::with_namespace`]
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Report HTTP calls to `endpoint` slower than `threshold`.
+    ///
+    /// Endpoints are keyed as described in [`crate::slow::endpoint_key`],
+    /// e.g. `"collection/search"` or `"collection/update"`. Slow calls are
+    /// logged as a `tracing` warning and passed to the
+    /// [`CasperClientBuilder::slow_call_hook`], if any.
+    pub fn slow_call_threshold(mut self, endpoint: &str, threshold: Duration) -> Self {
+        self.slow_calls.set_threshold(endpoint, threshold);
+        self
+    }
+
+    /// Slow-call threshold for endpoints without their own threshold
+    pub fn default_slow_call_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_calls.set_default(threshold);
+        self
+    }
+
+    /// Call `hook` for every call exceeding its slow-call threshold
+    pub fn slow_call_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&SlowCall) + Send + Sync + 'static,
+    {
+        self.slow_calls.set_hook(Arc::new(hook));
+        self
+    }
+
+    /// Hedge slow idempotent reads (searches and GETs), see [`HedgePolicy`]
+    pub fn hedging(mut self, policy: HedgePolicy) -> Self {
+        self.hedging = Some(policy);
+        self
+    }
+
+    /// Measure the latency of the server and its hedging replicas in the
+    /// background (default off), see [`crate::probe`]
+    pub fn probing(mut self, policy: ProbePolicy) -> Self {
+        self.probing = Some(policy);
+        self
+    }
+
+    /// Cache search results (default off), see [`SearchCachePolicy`]
+    pub fn search_cache(mut self, policy: SearchCachePolicy) -> Self {
+        self.search_cache = Some(policy);
+        self
+    }
+
+    /// Send identical concurrent searches once and share the results among
+    /// their callers (default off).
+    ///
+    /// Searches are identical when they would share a
+    /// [search cache](Self::search_cache) entry, which makes this worthwhile
+    /// under bursts of the same query that the cache alone would all miss.
+    pub fn coalesce_searches(mut self, enabled: bool) -> Self {
+        self.coalesce_searches = enabled;
+        self
+    }
+
+    /// Retry behaviour for reads and writes (default: nothing is retried),
+    /// see [`RetryPolicy`]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Build the client
+    pub fn build(self) -> Result<CasperClient> {
+        let base_url = self.endpoints.http_url()?;
+        let client = Client::builder().connect_timeout(self.timeouts.connect_timeout()).build()?;
+
+        let transport = self.transport.unwrap_or_else(|| Arc::new(client.clone()));
+        let prober = self.probing.map(|policy| {
+            let replicas = self.hedging.as_ref().map_or(&[][..], HedgePolicy::replicas);
+            let urls = std::iter::once(base_url.clone()).chain(replicas.iter().cloned()).collect();
+            Arc::new(Prober::new(policy, urls))
+        });
+        let mut client = CasperClient {
+            client,
+            transport,
+            base_url,
+            grpc: Arc::new(GrpcChannel::new(self.endpoints.grpc, self.timeouts.connect_timeout())),
+            outbox: self.outbox.map(Arc::new),
+            float_format: self.float_format,
+            reducers: Arc::new(self.reducers),
+            protection: Arc::new(self.protection),
+            forced: false,
+            read_only: self.read_only,
+            tokens: Arc::new(self.tokens),
+            signer: self.signer.map(Arc::new),
+            audit: self.audit,
+            progress: self.progress,
+            namespace: self.namespace.map(Arc::from),
+            slow_calls: Arc::new(self.slow_calls),
+            hedger: self.hedging.map(|policy| Arc::new(Hedger::new(policy, prober.clone()))),
+            prober,
+            session: None,
+            search_cache: self.search_cache.map(|policy| Arc::new(SearchCache::new(policy))),
+            in_flight: self.coalesce_searches.then(|| Arc::new(InFlight::default())),
+            retry: Arc::new(self.retry),
+            search_encoding: self.search_encoding,
+            score_scale: self.score_scale,
+            api: Arc::new(ApiState::new(self.api_version)),
+            normalization: Arc::new(Normalization::new(self.auto_normalize)),
+            dimensions: Arc::new(Dimensions::new(self.validate_dimensions)),
+            middleware: None,
+            index_wait: self.index_wait,
+            timeouts: self.timeouts,
+            memory_budget: self.memory_budget,
+            limits: self.limits,
+            http_upload_fallback: self.http_upload_fallback,
+            #[cfg(feature = "zstd")]
+            compression: self.compression.map(|settings| Arc::new(Compressor::new(settings))),
+        };
+        client.middleware = self.layers.apply(&client);
+        if let Some(prober) = &client.prober {
+            prober.start(&client);
+        }
+        Ok(client)
+    }
+}
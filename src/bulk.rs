@@ -0,0 +1,259 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::memory;
+use crate::duplicates::DuplicatePolicy;
+use crate::models::{BatchInsertOperation, BatchResult, BatchUpdateRequest};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+/// IDs per batch of [`CasperClient::delete_vectors`]
+const DELETE_BATCH: usize = 1000;
+
+/// An item a bulk operation could not apply, with the error that caused it.
+///
+/// Items of a chunk that failed as a whole share the same error.
+#[derive(Debug, Clone)]
+pub struct DeadLetter<T> {
+    pub item: T,
+    pub error: Arc<CasperError>,
+}
+
+/// Outcome of a chunked bulk operation
+#[derive(Debug, Clone)]
+pub struct BulkReport<T> {
+    /// Number of items applied successfully
+    pub succeeded: usize,
+    /// Items that failed, in input order
+    pub dead_letters: Vec<DeadLetter<T>>,
+}
+
+impl<T> Default for BulkReport<T> {
+    fn default() -> Self {
+        Self { succeeded: 0, dead_letters: Vec::new() }
+    }
+}
+
+impl<T> BulkReport<T> {
+    /// Whether every item was applied
+    pub fn is_complete(&self) -> bool {
+        self.dead_letters.is_empty()
+    }
+
+    /// Take the failed items back, e.g. to retry them
+    pub fn into_failed_items(self) -> Vec<T> {
+        self.dead_letters.into_iter().map(|d| d.item).collect()
+    }
+}
+
+impl<T: Serialize> BulkReport<T> {
+    /// Append the dead letters to `path` as JSON lines of `{"item", "error"}`
+    pub fn persist_dead_letters(&self, path: impl AsRef<Path>) -> Result<()> {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        for dead in &self.dead_letters {
+            let line = serde_json::json!({
+                "item": dead.item,
+                "error": dead.error.to_string(),
+            });
+            serde_json::to_writer(&mut file, &line)?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Apply `items` in chunks of `chunk_size` through `send`.
+///
+/// `send` returns the items of the chunk the server rejected individually;
+/// they are dead-lettered while the rest of the chunk counts as applied. A
+/// chunk rejected by the server as invalid is bisected until the offending
+/// items are isolated, so one bad item does not dead-letter its whole chunk.
+/// Chunks that fail for other reasons (unreachable server, server error) are
+/// dead-lettered as a whole.
+pub(crate) async fn run_chunked<T, F, Fut>(items: Vec<T>, chunk_size: usize, send: F) -> BulkReport<T>
+where
+    T: Clone,
+    F: Fn(Vec<T>) -> Fut,
+    Fut: Future<Output = Result<Vec<(T, CasperError)>>>,
+{
+    let mut pending: VecDeque<Vec<T>> = items
+        .chunks(chunk_size.max(1))
+        .map(|chunk| chunk.to_vec())
+        .collect();
+    let mut report = BulkReport::default();
+
+    while let Some(chunk) = pending.pop_front() {
+        let len = chunk.len();
+        match send(chunk.clone()).await {
+            Ok(failed) => {
+                report.succeeded += len - failed.len();
+                report.dead_letters.extend(
+                    failed.into_iter().map(|(item, e)| DeadLetter { item, error: Arc::new(e) }),
+                );
+            }
+            Err(e) if len > 1 && is_item_rejection(&e) => {
+                let mut chunk = chunk;
+                let second = chunk.split_off(len / 2);
+                pending.push_front(second);
+                pending.push_front(chunk);
+            }
+            Err(e) => {
+                let error = Arc::new(e);
+                report.dead_letters.extend(
+                    chunk.into_iter().map(|item| DeadLetter { item, error: error.clone() }),
+                );
+            }
+        }
+    }
+
+    report
+}
+
+/// Whether the server rejected the request content (as opposed to failing)
+fn is_item_rejection(error: &CasperError) -> bool {
+    matches!(
+        error,
+        CasperError::Client { .. }
+            | CasperError::InvalidDimension { .. }
+            | CasperError::IdExceedsMaxSize { .. }
+            | CasperError::ZeroNormVector
+    )
+}
+
+impl CasperClient {
+    /// Insert many vectors in chunks of `chunk_size` batch updates.
+    ///
+    /// Items that cannot be inserted are collected in the report's dead
+    /// letters instead of failing the whole call.
+    pub async fn insert_vectors_chunked(
+        &self,
+        collection_name: &str,
+        inserts: Vec<BatchInsertOperation>,
+        chunk_size: usize,
+    ) -> BulkReport<BatchInsertOperation> {
+        // A chunk is held both as operations and as the JSON request body
+        let item_bytes = inserts.first().map_or(0, |op| op.vector.len() * memory::JSON_FLOAT_BYTES);
+        let chunk_size = self.fit_budget(chunk_size, item_bytes, 2);
+        run_chunked(inserts, chunk_size, |insert| async move {
+            let request = BatchUpdateRequest { insert: insert.clone(), delete: vec![], upsert: false };
+            let result = self.batch_update_results(collection_name, request).await?;
+            Ok(insert
+                .into_iter()
+                .filter_map(|op| {
+                    let id = op.id;
+                    let reason = result.failure(id)?.to_string();
+                    Some((op, CasperError::ItemRejected { id, reason }))
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// [`CasperClient::insert_vectors_chunked`] handling existing IDs as
+    /// `policy` says, see [`CasperClient::batch_insert`]. Under
+    /// [`DuplicatePolicy::Error`] existing IDs are dead-lettered; skipped
+    /// ones count as succeeded.
+    pub async fn insert_vectors_chunked_with(
+        &self,
+        collection_name: &str,
+        inserts: Vec<BatchInsertOperation>,
+        chunk_size: usize,
+        policy: DuplicatePolicy,
+    ) -> BulkReport<BatchInsertOperation> {
+        let item_bytes = inserts.first().map_or(0, |op| op.vector.len() * memory::JSON_FLOAT_BYTES);
+        let chunk_size = self.fit_budget(chunk_size, item_bytes, 2);
+        run_chunked(inserts, chunk_size, |insert| async move {
+            let result = self.batch_insert(collection_name, insert.clone(), policy).await?;
+            Ok(insert
+                .into_iter()
+                .filter_map(|op| {
+                    let id = op.id;
+                    let reason = result.failure(id)?.to_string();
+                    Some((op, CasperError::ItemRejected { id, reason }))
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Delete many vectors by ID, in batch updates of 1000 IDs.
+    ///
+    /// `ids` is consumed one batch at a time, so large ranges or iterators
+    /// are never held in memory whole. IDs the server could not delete (e.g.
+    /// missing ones) are reported in the result; a failed batch stops the
+    /// call with the earlier batches applied.
+    pub async fn delete_vectors(
+        &self,
+        collection_name: &str,
+        ids: impl IntoIterator<Item = u32>,
+    ) -> Result<BatchResult> {
+        let mut ids = ids.into_iter().peekable();
+        let mut result = BatchResult::default();
+        while ids.peek().is_some() {
+            let delete = ids.by_ref().take(DELETE_BATCH).collect();
+            let request = BatchUpdateRequest { insert: Vec::new(), delete, upsert: false };
+            result.merge(self.batch_update_results(collection_name, request).await?);
+        }
+        Ok(result)
+    }
+
+    /// Delete every ID in `ids`, see [`CasperClient::delete_vectors`]
+    pub async fn delete_vector_range(&self, collection_name: &str, ids: Range<u32>) -> Result<BatchResult> {
+        self.delete_vectors(collection_name, ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bisects_rejected_chunks() {
+        let report = run_chunked((0..10u32).collect(), 4, |chunk: Vec<u32>| async move {
+            if chunk.contains(&5) {
+                Err(CasperError::Client { status: 400, message: "bad item".to_string() })
+            } else {
+                Ok(vec![])
+            }
+        })
+        .await;
+
+        assert_eq!(report.succeeded, 9);
+        assert_eq!(report.into_failed_items(), vec![5]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_vector_range() {
+        let scripted = crate::testing::Scripted::new([
+            (200, ""),
+            (200, r#"{"results":[{"id":1400,"error":"not found"}]}"#),
+        ]);
+        let result = scripted.client().delete_vector_range("docs", 0..1500).await.unwrap();
+        assert_eq!(scripted.requests(), ["POST /collection/docs/update", "POST /collection/docs/update"]);
+        assert_eq!((result.succeeded.len(), result.failure(1400)), (1499, Some("not found")));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_whole_chunk_on_server_error() {
+        let report = run_chunked((0..6u32).collect(), 3, |chunk: Vec<u32>| async move {
+            if chunk[0] == 0 {
+                Err(CasperError::Server { status: 500, message: "boom".to_string() })
+            } else {
+                Ok(vec![])
+            }
+        })
+        .await;
+
+        assert_eq!(report.succeeded, 3);
+        assert_eq!(report.into_failed_items(), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::client::{CasperClient, DEFAULT_SEARCH_LIMIT};
+use crate::error::Result;
+use crate::models::{SearchRequest, SearchResponse};
+use crate::upload::fnv1a_64;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Query vector components are rounded to multiples of 1/4096 before
+/// hashing, so re-computed embeddings of the same text share an entry
+const QUANTIZATION_STEPS: f32 = 4096.0;
+
+/// How many search results to keep and for how long.
+///
+/// Searches are cached by collection, quantized query vector, limit and
+/// every other search option (filter, decay, `ef`, ...). Writes made through
+/// the client drop the cached results of their collection, including those
+/// of searches still in flight; writes made elsewhere show up once the
+/// cached entries expire. Searches with a time budget
+/// ([`SearchRequest::max_time_ms`]) may return partial results and are
+/// never cached. [Sessions](CasperClient::session) bypass the cache, and
+/// coalescing, so their searches see their own writes.
+///
+/// ```
+/// use casper_client::SearchCachePolicy;
+/// use std::time::Duration;
+///
+/// let policy = SearchCachePolicy::new(10_000, Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchCachePolicy {
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl SearchCachePolicy {
+    /// Keep up to `capacity` searches, each for `ttl`
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self { capacity, ttl }
+    }
+}
+
+/// Canonical identity of a search, shared by the search cache and
+/// in-flight coalescing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SearchKey {
+    collection: String,
+    url: String,
+    params: Vec<(String, String)>,
+    limit: usize,
+    vector: u64,
+    /// Every other search option, as JSON
+    options: String,
+}
+
+impl SearchKey {
+    /// Key of `request` sent to `url`; the limit defaults and parameters are
+    /// sorted, so equivalent searches share a key
+    pub(crate) fn new(
+        url: &Url,
+        collection_name: &str,
+        params: &[(&str, String)],
+        request: &SearchRequest,
+    ) -> Result<Self> {
+        let mut params: Vec<(String, String)> =
+            params.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+        params.sort();
+        let options = (
+            &request.filter,
+            &request.decay,
+            request.max_time_ms,
+            request.ef,
+            request.nprobe,
+            request.include_deleted,
+            request.score_threshold,
+            request.max_distance,
+        );
+        Ok(Self {
+            collection: collection_name.to_string(),
+            url: url.to_string(),
+            params,
+            limit: request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            vector: fnv1a_64(
+                request.vector.iter().flat_map(|x| ((x * QUANTIZATION_STEPS).round() as i32).to_le_bytes()),
+            ),
+            options: serde_json::to_string(&options)?,
+        })
+    }
+}
+
+/// Invalidations a search started after, see [`SearchCache::generation`]
+pub(crate) type Generation = (u64, u64);
+
+#[derive(Debug, Default)]
+struct Entries {
+    results: HashMap<SearchKey, (Instant, SearchResponse)>,
+    /// Keys by insertion time, which is also expiry order; keys whose entry
+    /// was replaced since are skipped
+    order: VecDeque<(Instant, SearchKey)>,
+    /// Times the whole cache was cleared
+    epoch: u64,
+    /// Times each collection's entries were dropped
+    generations: HashMap<String, u64>,
+}
+
+impl Entries {
+    fn generation(&self, collection_name: &str) -> Generation {
+        (self.epoch, self.generations.get(collection_name).copied().unwrap_or_default())
+    }
+}
+
+/// Cached search results shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct SearchCache {
+    policy: SearchCachePolicy,
+    entries: Mutex<Entries>,
+}
+
+impl SearchCache {
+    pub(crate) fn new(policy: SearchCachePolicy) -> Self {
+        Self { policy, entries: Mutex::new(Entries::default()) }
+    }
+
+    fn get(&self, key: &SearchKey) -> Option<SearchResponse> {
+        let entries = self.entries.lock().expect("search cache poisoned");
+        let (inserted, results) = entries.results.get(key)?;
+        (inserted.elapsed() < self.policy.ttl).then(|| results.clone())
+    }
+
+    /// Taken before a search is sent and passed to [`SearchCache::insert`],
+    /// so results a write may have made stale are not cached
+    fn generation(&self, collection_name: &str) -> Generation {
+        self.entries.lock().expect("search cache poisoned").generation(collection_name)
+    }
+
+    fn insert(&self, key: SearchKey, results: SearchResponse, generation: Generation) {
+        if self.policy.capacity == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let mut entries = self.entries.lock().expect("search cache poisoned");
+        if entries.generation(&key.collection) != generation {
+            return;
+        }
+        let Entries { results: cached, order, .. } = &mut *entries;
+        // Drop expired entries, then the oldest ones while full
+        while let Some((inserted, oldest)) = order.front() {
+            let expired = now.duration_since(*inserted) >= self.policy.ttl;
+            if !expired && cached.len() < self.policy.capacity {
+                break;
+            }
+            if cached.get(oldest).is_some_and(|(current, _)| current == inserted) {
+                cached.remove(oldest);
+            }
+            order.pop_front();
+        }
+        order.push_back((now, key.clone()));
+        cached.insert(key, (now, results));
+    }
+
+    fn invalidate(&self, collection_name: &str) {
+        let mut entries = self.entries.lock().expect("search cache poisoned");
+        entries.results.retain(|key, _| key.collection != collection_name);
+        entries.order.retain(|(_, key)| key.collection != collection_name);
+        *entries.generations.entry(collection_name.to_string()).or_default() += 1;
+    }
+
+    fn clear(&self) {
+        let mut entries = self.entries.lock().expect("search cache poisoned");
+        let epoch = entries.epoch + 1;
+        *entries = Entries { epoch, ..Entries::default() };
+    }
+}
+
+impl CasperClient {
+    /// [`CasperClient::send_search`] through the search cache and in-flight
+    /// coalescing, if enabled
+    pub(crate) async fn cached_search(
+        &self,
+        url: Url,
+        collection_name: &str,
+        params: &[(&str, String)],
+        request: SearchRequest,
+    ) -> Result<SearchResponse> {
+        // Another search's results may predate the session's writes
+        if self.session.is_some() || (self.search_cache.is_none() && self.in_flight.is_none()) {
+            return self.send_search(url, collection_name, params, request).await;
+        }
+
+        let key = SearchKey::new(&url, collection_name, params, &request)?;
+        // Results cut short by a time budget may be partial
+        let cache = self.search_cache.as_ref().filter(|_| request.max_time_ms.is_none());
+        if let Some(results) = cache.and_then(|cache| cache.get(&key)) {
+            return Ok(results);
+        }
+        let generation = cache.map(|cache| cache.generation(collection_name));
+        let send = self.send_search(url, collection_name, params, request);
+        let results = match &self.in_flight {
+            Some(in_flight) => in_flight.coalesce(&key, send).await?,
+            None => send.await?,
+        };
+        if let (Some(cache), Some(generation)) = (cache, generation) {
+            cache.insert(key, results.clone(), generation);
+        }
+        Ok(results)
+    }
+
+    /// Drop the cached search results of a collection written to
+    pub(crate) fn invalidate_search_cache(&self, collection_name: &str) {
+        if let Some(cache) = &self.search_cache {
+            cache.invalidate(collection_name);
+        }
+    }
+
+    /// Drop every cached search result, see [`crate::CasperClientBuilder::search_cache`]
+    pub fn clear_search_cache(&self) {
+        if let Some(cache) = &self.search_cache {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InsertRequest;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_search_cache() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":0.9}]"#),
+            (200, r#"[{"id":2,"score":0.8}]"#),
+            (200, ""),
+            (200, r#"[{"id":3,"score":0.7}]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .search_cache(SearchCachePolicy::new(10, Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        let search = |vector: Vec<f32>, limit| SearchRequest { vector, limit: Some(limit), ..Default::default() };
+
+        assert_eq!(client.query("docs", search(vec![0.5, 0.25], 5)).await.unwrap()[0].id, 1);
+        // Rounding noise hits the cache, another limit does not
+        assert_eq!(client.query("docs", search(vec![0.50001, 0.25], 5)).await.unwrap()[0].id, 1);
+        assert_eq!(client.query("docs", search(vec![0.5, 0.25], 10)).await.unwrap()[0].id, 2);
+        assert_eq!(scripted.requests().len(), 2);
+
+        // A write drops the collection's entries
+        client.insert_vector("docs", InsertRequest { id: 4, vector: vec![1.0, 0.0], payload: None }).await.unwrap();
+        assert_eq!(client.query("docs", search(vec![0.5, 0.25], 5)).await.unwrap()[0].id, 3);
+        assert_eq!(scripted.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_search_cache_skips_stale_and_partial_results() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":0.9}]"#),
+            (200, r#"[{"id":2,"score":0.8}]"#),
+            (200, r#"[{"id":3,"score":0.7}]"#),
+            (200, r#"[{"id":4,"score":0.6}]"#),
+        ])
+        .delayed(Duration::from_millis(50));
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .search_cache(SearchCachePolicy::new(10, Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        let search = || SearchRequest { vector: vec![0.5, 0.25], ..Default::default() };
+
+        // A write lands while the search is in flight
+        let invalidate = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            client.invalidate_search_cache("docs");
+        };
+        let (stale, ()) = tokio::join!(client.query("docs", search()), invalidate);
+        assert_eq!(stale.unwrap()[0].id, 1);
+        assert_eq!(client.query("docs", search()).await.unwrap()[0].id, 2);
+
+        let budgeted = || SearchRequest { max_time_ms: Some(5), ..search() };
+        assert_eq!(client.query("docs", budgeted()).await.unwrap()[0].id, 3);
+        assert_eq!(client.query("docs", budgeted()).await.unwrap()[0].id, 4);
+        assert_eq!(scripted.requests().len(), 4);
+    }
+}
@@ -0,0 +1,375 @@
+//! Bulk deletion and dependency-aware cleanup of matrices and PQs.
+//!
+//! Matrices serve as PQ codebooks and PQs are referenced by collection
+//! indexes; the server refuses to delete a resource that is still in use.
+
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{CollectionInfo, MatrixInfo, PqInfo};
+use crate::parallel;
+use crate::protect::glob_match;
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+
+/// Deletions in flight at once when purging
+const PURGE_PARALLELISM: usize = 8;
+
+/// What [`CasperClient::delete_pq_cascade`] deleted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PqCascade {
+    /// Collections whose index used the PQ and was deleted
+    pub indexes_deleted: Vec<String>,
+    /// Codebook matrices deleted along with the PQ
+    pub codebooks_deleted: Vec<String>,
+    /// Codebook matrices kept because another PQ still uses them
+    pub codebooks_kept: Vec<String>,
+}
+
+/// Which unused matrices [`CasperClient::purge_unused_matrices`] deletes
+#[derive(Debug, Clone, Default)]
+pub struct MatrixPurge {
+    pattern: Option<String>,
+    dry_run: bool,
+}
+
+impl MatrixPurge {
+    /// Only consider matrices whose whole name matches `pattern`, where `*`
+    /// matches any run of characters (as for delete protection)
+    pub fn matching(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Report what would be deleted without deleting anything
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled;
+        self
+    }
+}
+
+/// What [`CasperClient::purge_unused_matrices`] deleted, or would delete
+#[derive(Debug, Default)]
+pub struct MatrixPurgeReport {
+    /// Unused matrices matching the filter, protected ones excluded
+    pub candidates: Vec<String>,
+    /// Unused matrices matching the filter but skipped as delete-protected
+    pub protected: Vec<String>,
+    /// One deletion result per candidate; empty on a dry run
+    pub deleted: Vec<(String, Result<()>)>,
+}
+
+/// Cross-reference of collections, PQs and matrices, for finding what can
+/// be cleaned up; see [`CasperClient::resource_report`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceReport {
+    pub collections: Vec<String>,
+    pub pqs: Vec<String>,
+    pub matrices: Vec<String>,
+    /// Collections whose index uses each PQ (every PQ has an entry)
+    pub pq_users: BTreeMap<String, Vec<String>>,
+    /// Matrices that are not a codebook of any PQ
+    pub unused_matrices: Vec<String>,
+    /// PQs no index uses
+    pub unused_pqs: Vec<String>,
+    /// `(collection, PQ)` pairs whose index uses a PQ that does not exist
+    pub missing_pqs: Vec<(String, String)>,
+    /// `(PQ, matrix)` pairs whose codebook matrix does not exist
+    pub missing_codebooks: Vec<(String, String)>,
+}
+
+impl ResourceReport {
+    /// Cross-reference the listed resources
+    pub fn new(collections: &[CollectionInfo], pqs: &[PqInfo], matrices: &[MatrixInfo]) -> Self {
+        let mut report = ResourceReport {
+            collections: collections.iter().map(|c| c.name.clone()).collect(),
+            pqs: pqs.iter().map(|pq| pq.name.clone()).collect(),
+            matrices: matrices.iter().map(|m| m.name.clone()).collect(),
+            pq_users: pqs.iter().map(|pq| (pq.name.clone(), Vec::new())).collect(),
+            ..Default::default()
+        };
+
+        for collection in collections {
+            let Some(pq_name) = index_pq(collection) else {
+                continue;
+            };
+            match report.pq_users.get_mut(pq_name) {
+                Some(users) => users.push(collection.name.clone()),
+                None => report.missing_pqs.push((collection.name.clone(), pq_name.to_string())),
+            }
+        }
+
+        let codebooks: HashSet<&str> = pqs.iter().flat_map(|pq| &pq.codebooks).map(String::as_str).collect();
+        let existing: HashSet<&str> = report.matrices.iter().map(String::as_str).collect();
+        for pq in pqs {
+            for codebook in &pq.codebooks {
+                if !existing.contains(codebook.as_str()) {
+                    report.missing_codebooks.push((pq.name.clone(), codebook.clone()));
+                }
+            }
+        }
+        report.unused_matrices = report
+            .matrices
+            .iter()
+            .filter(|name| !codebooks.contains(name.as_str()))
+            .cloned()
+            .collect();
+        report.unused_pqs = report
+            .pq_users
+            .iter()
+            .filter(|(_, users)| users.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect();
+        report
+    }
+
+    /// Whether anything is unused or references something missing
+    pub fn has_orphans(&self) -> bool {
+        !(self.unused_matrices.is_empty()
+            && self.unused_pqs.is_empty()
+            && self.missing_pqs.is_empty()
+            && self.missing_codebooks.is_empty())
+    }
+}
+
+impl CasperClient {
+    /// List collections, PQs and matrices and cross-reference them, see
+    /// [`ResourceReport`]
+    pub async fn resource_report(&self) -> Result<ResourceReport> {
+        let (collections, pqs, matrices) =
+            tokio::try_join!(self.list_collections(), self.list_pqs(), self.list_matrices())?;
+        Ok(ResourceReport::new(&collections.collections, &pqs, &matrices))
+    }
+
+    /// Delete several matrices, at most `parallelism` at once.
+    ///
+    /// Returns one `(name, result)` entry per matrix, in input order; a
+    /// failed deletion does not stop the others. Matrices still used as PQ
+    /// codebooks are rejected by the server.
+    pub async fn delete_matrices<I, S>(&self, names: I, parallelism: usize) -> Result<Vec<(String, Result<()>)>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect();
+        self.delete_each(names, parallelism, |client, name| async move { client.delete_matrix(&name).await })
+            .await
+    }
+
+    /// Delete several PQs, at most `parallelism` at once; results as for
+    /// [`CasperClient::delete_matrices`]
+    pub async fn delete_pqs<I, S>(&self, names: I, parallelism: usize) -> Result<Vec<(String, Result<()>)>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect();
+        self.delete_each(names, parallelism, |client, name| async move { client.delete_pq(&name).await })
+            .await
+    }
+
+    /// Delete the matrices that are not a codebook of any PQ and match
+    /// `purge`'s filter.
+    ///
+    /// Delete-protected matrices are skipped and reported as such unless the
+    /// client is [`force`](CasperClient::force)d. With
+    /// [`MatrixPurge::dry_run`], nothing is deleted.
+    pub async fn purge_unused_matrices(&self, purge: &MatrixPurge) -> Result<MatrixPurgeReport> {
+        if !purge.dry_run {
+            self.check_writable("purge_unused_matrices")?;
+        }
+        let used: HashSet<String> = self
+            .list_pqs()
+            .await?
+            .into_iter()
+            .flat_map(|pq| pq.codebooks)
+            .collect();
+        let (protected, candidates): (Vec<String>, Vec<String>) = self
+            .list_matrices()
+            .await?
+            .into_iter()
+            .map(|matrix| matrix.name)
+            .filter(|name| !used.contains(name))
+            .filter(|name| purge.pattern.as_deref().is_none_or(|pattern| glob_match(pattern, name)))
+            .partition(|name| !self.forced && self.protection.is_protected(name));
+
+        let mut report = MatrixPurgeReport { candidates, protected, deleted: Vec::new() };
+        if !purge.dry_run {
+            report.deleted = self.delete_matrices(report.candidates.clone(), PURGE_PARALLELISM).await?;
+        }
+        Ok(report)
+    }
+
+    /// Collections whose index (HNSW or IVF-PQ) uses the PQ `pq_name`
+    pub async fn collections_using_pq(&self, pq_name: &str) -> Result<Vec<String>> {
+        let collections = self.list_collections().await?.collections;
+        Ok(collections
+            .into_iter()
+            .filter(|collection| index_pq(collection) == Some(pq_name))
+            .map(|collection| collection.name)
+            .collect())
+    }
+
+    /// Delete a PQ together with what depends on it.
+    ///
+    /// Indexes using the PQ make the call fail with
+    /// [`CasperError::OperationNotAllowed`] naming their collections, unless
+    /// `cascade` is set, in which case those indexes are deleted first. With
+    /// `delete_codebooks`, the PQ's codebook matrices are deleted afterwards,
+    /// except those another PQ still uses. Delete protection applies to the
+    /// PQ as usual.
+    pub async fn delete_pq_cascade(&self, name: &str, cascade: bool, delete_codebooks: bool) -> Result<PqCascade> {
+        self.check_destructive("PQ", name)?;
+        let pq = self.get_pq(name).await?;
+        let users = self.collections_using_pq(name).await?;
+        if !users.is_empty() && !cascade {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "PQ '{}' is used by the index of {}; pass cascade to delete the indexes too",
+                name,
+                users.join(", ")
+            )));
+        }
+
+        let mut cascade = PqCascade::default();
+        for collection in users {
+            self.delete_index(&collection).await?;
+            cascade.indexes_deleted.push(collection);
+        }
+        self.delete_pq(name).await?;
+        if !delete_codebooks {
+            return Ok(cascade);
+        }
+
+        let still_used: HashSet<String> = self
+            .list_pqs()
+            .await?
+            .into_iter()
+            .flat_map(|pq| pq.codebooks)
+            .collect();
+        let (kept, unused): (Vec<String>, Vec<String>) =
+            pq.codebooks.into_iter().partition(|codebook| still_used.contains(codebook));
+        cascade.codebooks_kept = kept;
+        for (codebook, result) in self.delete_matrices(unused, PURGE_PARALLELISM).await? {
+            result?;
+            cascade.codebooks_deleted.push(codebook);
+        }
+        Ok(cascade)
+    }
+
+    /// Run `delete` for every name with bounded concurrency, keeping input
+    /// order in the results
+    async fn delete_each<F, Fut>(
+        &self,
+        names: Vec<String>,
+        parallelism: usize,
+        delete: F,
+    ) -> Result<Vec<(String, Result<()>)>>
+    where
+        F: Fn(CasperClient, String) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let deletions = parallel::fan_out(names.clone(), parallelism, |name| delete(self.clone(), name));
+        Ok(names.into_iter().zip(deletions.collect().await?).collect())
+    }
+}
+
+/// PQ used by the index of `collection`, if any
+fn index_pq(collection: &CollectionInfo) -> Option<&str> {
+    let index = collection.index.as_ref()?;
+    let hnsw = index.hnsw.as_ref().and_then(|hnsw| hnsw.pq_name.as_deref());
+    hnsw.or_else(|| index.ivf_pq.as_ref().map(|ivf_pq| ivf_pq.pq_name.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    const PQ: &str = r#"{"name":"pq","dim":4,"codebooks":["cb0","cb1"],"enabled":true}"#;
+    const COLLECTIONS: &str = r#"{"collections":[{"name":"docs","dimension":4,"mutable":true,"has_index":true,
+        "max_size":10,"size":0,"index":{"hnsw":null,"normalization":false,
+        "ivf_pq":{"metric":"l2","nlist":16,"nprobe":4,"pq_name":"pq"}}}]}"#;
+
+    #[tokio::test]
+    async fn test_delete_pq_cascade() {
+        let transport = Scripted::new([(200, PQ), (200, COLLECTIONS)]);
+        let refused = transport.client().force().delete_pq_cascade("pq", false, true).await;
+        assert!(matches!(refused, Err(CasperError::OperationNotAllowed(_))));
+
+        let transport = Scripted::new([
+            (200, PQ),
+            (200, COLLECTIONS),
+            (204, ""),
+            (204, ""),
+            (200, r#"[{"name":"other","dim":2,"codebooks":["cb1"],"enabled":true}]"#),
+            (204, ""),
+        ]);
+        let cascade = transport.client().delete_pq_cascade("pq", true, true).await.unwrap();
+        assert_eq!(cascade.indexes_deleted, vec!["docs"]);
+        assert_eq!(cascade.codebooks_deleted, vec!["cb0"]);
+        assert_eq!(cascade.codebooks_kept, vec!["cb1"]);
+        assert_eq!(
+            transport.requests(),
+            vec![
+                "GET /pq/pq",
+                "GET /collections",
+                "DELETE /collection/docs/index",
+                "DELETE /pq/pq",
+                "GET /pq/list",
+                "DELETE /matrix/cb0",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_unused_matrices() {
+        const PQS: &str = r#"[{"name":"pq","dim":4,"codebooks":["cb0","cb1"],"enabled":true}]"#;
+        const MATRICES: &str = r#"[{"name":"cb0","dim":2,"len":16,"enabled":true},
+            {"name":"tmp-stale","dim":2,"len":16,"enabled":true},
+            {"name":"tmp-keep","dim":2,"len":16,"enabled":true},
+            {"name":"other","dim":2,"len":16,"enabled":true},
+            {"name":"cb1","dim":2,"len":16,"enabled":true}]"#;
+        let protected = |transport: &Scripted| {
+            CasperClient::builder("http://localhost", 8080, 50051)
+                .transport(transport.clone())
+                .protect("*-keep")
+                .build()
+                .unwrap()
+        };
+
+        let transport = Scripted::new([(200, PQS), (200, MATRICES)]);
+        let purge = MatrixPurge::default().matching("tmp-*").dry_run(true);
+        let report = protected(&transport).purge_unused_matrices(&purge).await.unwrap();
+        assert_eq!(report.candidates, vec!["tmp-stale"]);
+        assert_eq!(report.protected, vec!["tmp-keep"]);
+        assert!(report.deleted.is_empty());
+        assert_eq!(transport.requests(), vec!["GET /pq/list", "GET /matrix/list"]);
+
+        let transport = Scripted::new([(200, PQS), (200, MATRICES), (204, ""), (204, "")]);
+        let report = protected(&transport).purge_unused_matrices(&MatrixPurge::default()).await.unwrap();
+        assert_eq!(report.candidates, vec!["tmp-stale", "other"]);
+        assert!(report.deleted.iter().all(|(_, result)| result.is_ok()));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests.contains(&"DELETE /matrix/tmp-stale".to_string()));
+        assert!(requests.contains(&"DELETE /matrix/other".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_resource_report() {
+        let transport = Scripted::new([
+            (200, COLLECTIONS),
+            (200, r#"[{"name":"pq","dim":4,"codebooks":["cb0","gone"],"enabled":true},
+                      {"name":"idle","dim":2,"codebooks":["cb0"],"enabled":true}]"#),
+            (200, r#"[{"name":"cb0","dim":2,"len":16,"enabled":true},
+                      {"name":"stale","dim":2,"len":16,"enabled":true}]"#),
+        ]);
+
+        let report = transport.client().resource_report().await.unwrap();
+        assert_eq!(report.pq_users["pq"], vec!["docs"]);
+        assert_eq!(report.unused_pqs, vec!["idle"]);
+        assert_eq!(report.unused_matrices, vec!["stale"]);
+        assert_eq!(report.missing_codebooks, vec![("pq".to_string(), "gone".to_string())]);
+        assert!(report.missing_pqs.is_empty());
+        assert!(report.has_orphans());
+    }
+}
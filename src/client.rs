@@ -1,21 +1,99 @@
+use crate::audit::AuditHook;
+use crate::auth::{Scope, ScopedTokens};
 use crate::error::{CasperError, Result};
+use crate::hedge::Hedger;
+use crate::limits::RequestLimits;
 use crate::models::*;
-use crate::grpc::service::matrix_service::{
-    matrix_service_client::MatrixServiceClient,
-    upload_matrix_request, MatrixData, MatrixHeader, UploadMatrixRequest,
-};
-use reqwest::Client;
-use std::time::Duration;
-use tokio_stream::wrappers::ReceiverStream;
-use tonic::Request;
+use crate::normalize::Normalization;
+use crate::builder::CasperClientBuilder;
+use crate::cache::SearchCache;
+use crate::coalesce::InFlight;
+use crate::dimensions::{self, Dimensions};
+#[cfg(feature = "zstd")]
+use crate::compression::Compressor;
+use crate::encoding::{self, FloatFormat, SearchEncoding};
+use crate::endpoints::Endpoints;
+use crate::outbox::{Outbox, WriteOp};
+use crate::job::Job;
+use crate::matrix::Matrix;
+use crate::probe::Prober;
+use crate::progress::ProgressHook;
+use crate::protect::DeleteProtection;
+use crate::session::SessionToken;
+use crate::upload::{self, GrpcChannel, GrpcTarget, MatrixDigest, MatrixUpload, UploadHandle};
+use crate::reduce::DimReducer;
+use crate::retry::RetryPolicy;
+use crate::scores::ScoreScale;
+use crate::service::HttpService;
+use crate::signing::RequestSigner;
+use crate::slow::SlowCallPolicy;
+use crate::timeouts::Timeouts;
+use crate::transport::HttpTransport;
+use crate::vector;
+use crate::version::{self, ApiState, ApiVersion};
+use reqwest::{Client, Method, RequestBuilder, Response};
+use std::future::Future;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use url::Url;
 
+/// Number of results of a search whose [`SearchRequest::limit`] is unset
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Response header carrying the server-assigned query ID
+pub const QUERY_ID_HEADER: &str = "x-query-id";
+
 /// Casper vector database client
 #[derive(Debug, Clone)]
 pub struct CasperClient {
-    client: Client,
-    base_url: Url,
-    grpc_addr: String,
+    /// Builds requests; they are sent through `transport`
+    pub(crate) client: Client,
+    pub(crate) transport: Arc<dyn HttpTransport>,
+    pub(crate) base_url: Url,
+    pub(crate) grpc: Arc<GrpcChannel>,
+    pub(crate) outbox: Option<Arc<Outbox>>,
+    pub(crate) float_format: FloatFormat,
+    pub(crate) reducers: Arc<HashMap<String, DimReducer>>,
+    pub(crate) protection: Arc<DeleteProtection>,
+    /// Bypass delete protection, see [`CasperClient::force`]
+    pub(crate) forced: bool,
+    /// Reject every mutating call locally
+    pub(crate) read_only: bool,
+    pub(crate) tokens: Arc<ScopedTokens>,
+    pub(crate) signer: Option<Arc<RequestSigner>>,
+    pub(crate) audit: Option<AuditHook>,
+    pub(crate) progress: Option<ProgressHook>,
+    /// Prefix of server-side names, see [`CasperClient::with_namespace`]
+    pub(crate) namespace: Option<Arc<str>>,
+    pub(crate) slow_calls: Arc<SlowCallPolicy>,
+    pub(crate) hedger: Option<Arc<Hedger>>,
+    /// Started by the first request if the client was built outside a
+    /// Tokio runtime
+    pub(crate) prober: Option<Arc<Prober>>,
+    /// Read-your-writes token, see [`CasperClient::session`]
+    pub(crate) session: Option<Arc<SessionToken>>,
+    pub(crate) search_cache: Option<Arc<SearchCache>>,
+    pub(crate) in_flight: Option<Arc<InFlight>>,
+    pub(crate) retry: Arc<RetryPolicy>,
+    pub(crate) search_encoding: SearchEncoding,
+    pub(crate) score_scale: ScoreScale,
+    pub(crate) api: Arc<ApiState>,
+    pub(crate) normalization: Arc<Normalization>,
+    pub(crate) dimensions: Arc<Dimensions>,
+    /// User middleware around [`CasperClient::execute_request`], see
+    /// [`crate::service`]
+    pub(crate) middleware: Option<HttpService>,
+    /// How long writes wait out an index build, `None` to fail right away
+    pub(crate) index_wait: Option<Duration>,
+    pub(crate) timeouts: Timeouts,
+    /// Bytes bulk operations may buffer, see [`crate::memory`]
+    pub(crate) memory_budget: Option<usize>,
+    pub(crate) limits: RequestLimits,
+    /// Upload matrices over HTTP when gRPC is unreachable
+    pub(crate) http_upload_fallback: bool,
+    #[cfg(feature = "zstd")]
+    pub(crate) compression: Option<Arc<Compressor>>,
 }
 
 impl CasperClient {
@@ -25,32 +103,32 @@ impl CasperClient {
     /// - `http_port`: HTTP API port (e.g. 8080)
     /// - `grpc_port`: gRPC API port (e.g. 50051)
     pub fn new(host: &str, http_port: u16, grpc_port: u16) -> Result<Self> {
-        let base_url_str = format!("{}:{}", host, http_port);
-        let base_url = Url::parse(&base_url_str)?;
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()?;
-        
-        let grpc_addr = format!("{}:{}", host, grpc_port);
+        Self::builder(host, http_port, grpc_port).build()
+    }
 
-        Ok(Self { client, base_url, grpc_addr })
+    /// Create a client from its HTTP base URL (e.g. "http://localhost:8080"),
+    /// with the gRPC service on the same host at the default port
+    pub fn from_url(base_url: &str) -> Result<Self> {
+        Self::from_endpoints(Endpoints::from_url(base_url)?)
     }
 
-    /// Create a new Casper client with custom timeout
+    /// Create a client for the server at `endpoints`
+    pub fn from_endpoints(endpoints: Endpoints) -> Result<Self> {
+        CasperClientBuilder::new(endpoints).build()
+    }
+
+    /// Create a new Casper client with a custom per-attempt HTTP timeout
     ///
     /// - `host`: hostname or IP of the Casper server (e.g. "127.0.0.1")
     /// - `http_port`: HTTP API port (e.g. 8080)
     /// - `grpc_port`: gRPC API port (e.g. 50051)
     pub fn with_timeout(host: &str, http_port: u16, grpc_port: u16, timeout: Duration) -> Result<Self> {
-        let base_url_str = format!("{}:{}", host, http_port);
-        let base_url = Url::parse(&base_url_str)?;
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()?;
-        
-        let grpc_addr = format!("{}:{}", host, grpc_port);
+        Self::builder(host, http_port, grpc_port).timeout(timeout).build()
+    }
 
-        Ok(Self { client, base_url, grpc_addr })
+    /// Start configuring a client with non-default options
+    pub fn builder(host: &str, http_port: u16, grpc_port: u16) -> CasperClientBuilder {
+        CasperClientBuilder::new(Endpoints::from_host(host, http_port, grpc_port))
     }
 
     /// Get the base URL
@@ -60,23 +138,36 @@ impl CasperClient {
 
     /// Get the gRPC address
     pub fn grpc_addr(&self) -> &str {
-        &self.grpc_addr
+        self.grpc.addr()
+    }
+
+    /// Endpoints the client talks to
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints::new(self.base_url.as_str(), self.grpc.addr())
     }
 
     /// List all collections
     pub async fn list_collections(&self) -> Result<CollectionsListResponse> {
         let url = self.base_url.join("collections")?;
-        let response = self.client.get(url).send().await?;
-        
-        self.handle_response(response).await
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+
+        let mut list: CollectionsListResponse = self.handle_response(response).await?;
+        list.collections = list
+            .collections
+            .into_iter()
+            .filter_map(|info| self.localize_collection(info))
+            .collect();
+        Ok(list)
     }
 
     /// Get collection information
     pub async fn get_collection(&self, collection_name: &str) -> Result<CollectionInfo> {
-        let url = self.base_url.join(&format!("collection/{}", collection_name))?;
-        let response = self.client.get(url).send().await?;
-        
-        self.handle_response(response).await
+        let url = self.collection_url(collection_name, "")?;
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+
+        let info: CollectionInfo = self.handle_response(response).await?;
+        // The server only returns the qualified name we asked for
+        Ok(self.localize_collection(info.clone()).unwrap_or(info))
     }
 
     /// Create a new collection
@@ -85,24 +176,116 @@ impl CasperClient {
         collection_name: &str,
         request: CreateCollectionRequest,
     ) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}", collection_name))?;
-        let response = self
-            .client
-            .post(url)
-            .query(&request)
-            .header("Content-Type", "application/json")
-            .send()
-            .await?;
-        
-        self.handle_empty_response(response).await
+        self.audited("create_collection", collection_name, 0, async {
+            self.check_writable("create_collection")?;
+            self.dimensions.forget(&self.qualify(collection_name));
+            if !request.labels.is_empty() {
+                self.require_api(ApiVersion::V1_1, "collection labels")?;
+            }
+            let url = self.collection_url(collection_name, "")?;
+            let mut http = self
+                .http(Scope::Admin, Method::POST, url)
+                .query(&[("dim", request.dim.to_string()), ("max_size", request.max_size.to_string())])
+                .header("Content-Type", "application/json");
+            if !request.labels.is_empty() {
+                http = http.json(&CollectionLabelsBody { labels: request.labels });
+            }
+            let response = http.dispatch(self).await?;
+
+            self.handle_empty_response(response).await
+        })
+        .await
+    }
+
+    /// Replace the labels of a collection
+    pub async fn set_collection_labels(&self, collection_name: &str, labels: Labels) -> Result<()> {
+        self.audited("set_collection_labels", collection_name, labels.len(), async {
+            self.check_writable("set_collection_labels")?;
+            self.require_api(ApiVersion::V1_1, "collection labels")?;
+            let url = self.collection_url(collection_name, "/labels")?;
+            let response = self
+                .http(Scope::Admin, Method::PUT, url)
+                .header("Content-Type", "application/json")
+                .json(&CollectionLabelsBody { labels })
+                .dispatch(self)
+                .await?;
+
+            self.handle_empty_response(response).await
+        })
+        .await
+    }
+
+    /// Add or overwrite some labels of a collection, keeping the others
+    pub async fn update_collection_labels(&self, collection_name: &str, labels: Labels) -> Result<()> {
+        let mut merged = self.get_collection(collection_name).await?.labels;
+        merged.extend(labels);
+        self.set_collection_labels(collection_name, merged).await
+    }
+
+    /// Freeze (`false`) or unfreeze (`true`) a collection.
+    ///
+    /// A frozen collection rejects writes with
+    /// [`CasperError::CollectionNotMutable`], which lets the server switch to
+    /// read-optimized structures; freeze collections after their bulk load.
+    pub async fn set_mutable(&self, collection_name: &str, mutable: bool) -> Result<()> {
+        self.audited("set_mutable", collection_name, 0, async {
+            self.check_writable("set_mutable")?;
+            let url = self.collection_url(collection_name, "/mutable")?;
+            let response = self
+                .http(Scope::Admin, Method::PUT, url)
+                .header("Content-Type", "application/json")
+                .json(&SetMutableBody { mutable })
+                .dispatch(self)
+                .await?;
+
+            self.handle_empty_response(response).await
+        })
+        .await
+    }
+
+    /// Change the capacity (`max_size`) of a collection in place, e.g. when
+    /// it is about to run out of IDs.
+    ///
+    /// The new capacity must exceed the number of vectors currently stored;
+    /// this is checked against the collection info before resizing. The
+    /// collection's cached shape is dropped, as the server may rebuild it.
+    pub async fn resize_collection(&self, collection_name: &str, new_max_size: u32) -> Result<()> {
+        self.audited("resize_collection", collection_name, 0, async {
+            self.check_writable("resize_collection")?;
+            let info = self.get_collection(collection_name).await?;
+            if new_max_size as usize <= info.size {
+                return Err(CasperError::InvalidArgument(format!(
+                    "new max_size {} of collection '{}' must exceed its current size {}",
+                    new_max_size, collection_name, info.size
+                )));
+            }
+
+            self.dimensions.forget(&self.qualify(collection_name));
+            let url = self.collection_url(collection_name, "/resize")?;
+            let response = self
+                .http(Scope::Admin, Method::POST, url)
+                .query(&[("max_size", new_max_size.to_string())])
+                .dispatch(self)
+                .await?;
+
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// Delete a collection
     pub async fn delete_collection(&self, collection_name: &str) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}", collection_name))?;
-        let response = self.client.delete(url).send().await?;
-        
-        self.handle_empty_response(response).await
+        self.audited("delete_collection", collection_name, 0, async {
+            self.check_destructive("collection", collection_name)?;
+            self.normalization.forget(collection_name);
+            self.dimensions.forget(&self.qualify(collection_name));
+            let url = self.collection_url(collection_name, "")?;
+            let response = self.http(Scope::Admin, Method::DELETE, url).dispatch(self).await?;
+            self.invalidate_search_cache(collection_name);
+
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// Insert a vector into a collection
@@ -111,17 +294,43 @@ impl CasperClient {
         collection_name: &str,
         request: InsertRequest,
     ) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}/insert", collection_name))?;
-        let response = self
-            .client
-            .post(url)
-            .query(&[("id", request.id.to_string())])
-            .header("Content-Type", "application/json")
-            .json(&InsertVectorBody { vector: request.vector })
-            .send()
-            .await?;
-        
-        self.handle_empty_response(response).await
+        self.write(collection_name, WriteOp::Insert(request)).await?;
+        Ok(())
+    }
+
+    /// Overwrite selected components of a stored vector.
+    ///
+    /// `updates` are `(component index, new value)` pairs. Requires a server
+    /// supporting partial updates; saves re-sending the whole vector when only
+    /// a few dimensions change.
+    pub async fn update_vector_components(
+        &self,
+        collection_name: &str,
+        id: u32,
+        updates: &[(usize, f32)],
+    ) -> Result<()> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let (indices, values) = updates.iter().copied().unzip();
+        let request = UpdateComponentsRequest { id, indices, values };
+        self.write(collection_name, WriteOp::UpdateComponents(request)).await?;
+        Ok(())
+    }
+
+    /// Payload stored with vector `id`, `None` if it has none.
+    ///
+    /// Fails with [`CasperError::VectorNotFound`] if the vector does not exist.
+    pub async fn get_payload(&self, collection_name: &str, id: u32) -> Result<Option<serde_json::Value>> {
+        let vector = self.fetch_vector(collection_name, id).await?.ok_or(CasperError::VectorNotFound(id))?;
+        Ok(vector.payload)
+    }
+
+    /// Replace the payload stored with vector `id`, leaving the vector as is
+    pub async fn update_payload(&self, collection_name: &str, id: u32, payload: serde_json::Value) -> Result<()> {
+        self.write(collection_name, WriteOp::UpdatePayload(UpdatePayloadRequest { id, payload })).await?;
+        Ok(())
     }
 
     /// Delete a vector from a collection
@@ -130,282 +339,736 @@ impl CasperClient {
         collection_name: &str,
         request: DeleteRequest,
     ) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}/delete", collection_name))?;
-        let response = self
-            .client
-            .delete(url)
-            .query(&[("id", request.id.to_string())])
-            .header("Content-Type", "application/json")
-            .send()
-            .await?;
-        
-        self.handle_empty_response(response).await
+        self.write(collection_name, WriteOp::Delete(request)).await?;
+        Ok(())
     }
 
-    /// Search for similar vectors
+    /// Search for similar vectors.
+    ///
+    /// The `limit` argument overrides [`SearchRequest::limit`], which made the
+    /// two easy to confuse; use [`CasperClient::query`] instead.
+    #[deprecated(note = "use `query`, which takes the limit from `SearchRequest::limit`")]
     pub async fn search(
         &self,
         collection_name: &str,
         limit: usize,
         request: SearchRequest,
     ) -> Result<SearchResponse> {
-        let url = self.base_url.join(&format!("collection/{}/search", collection_name))?;
+        self.query(collection_name, SearchRequest { limit: Some(limit), ..request }).await
+    }
+
+    /// Search for the vectors most similar to `request.vector`.
+    ///
+    /// Returns up to `request.limit` results, [`DEFAULT_SEARCH_LIMIT`] if unset.
+    ///
+    /// Scores are on the client's [`ScoreScale`], see
+    /// [`crate::CasperClientBuilder::score_scale`].
+    pub async fn query(&self, collection_name: &str, request: SearchRequest) -> Result<SearchResponse> {
+        let url = self.collection_url(collection_name, "/search")?;
+        let mut results = self.cached_search(url, collection_name, &[], request).await?;
+        self.rescale(collection_name, self.score_scale, &mut results).await?;
+        Ok(results)
+    }
+
+    /// [`CasperClient::query`], also returning the query ID the server
+    /// assigned (if it does), which can be passed to
+    /// [`CasperClient::get_query_profile`]
+    pub async fn search_with_query_id(
+        &self,
+        collection_name: &str,
+        request: SearchRequest,
+    ) -> Result<SearchOutcome> {
+        let url = self.collection_url(collection_name, "/search")?;
+        let mut outcome = self.send_search_outcome(url, collection_name, &[], request).await?;
+        self.rescale(collection_name, self.score_scale, &mut outcome.results).await?;
+        Ok(outcome)
+    }
+
+    /// Fetch the server-side profile of a past query.
+    ///
+    /// Returns `None` when the server no longer (or never) had a profile for
+    /// `query_id`.
+    pub async fn get_query_profile(&self, query_id: &str) -> Result<Option<QueryProfile>> {
+        self.require_api(ApiVersion::V1_1, "query profiles")?;
+        let url = self.segments_url(&["query", query_id, "profile"])?;
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+
+        if response.status() == 404 {
+            return Ok(None);
+        }
+        Ok(Some(self.handle_response(response).await?))
+    }
+
+    /// Search and return server-side diagnostics alongside the results.
+    ///
+    /// Reports nodes visited, distance computations and time per search
+    /// phase; useful when tuning index parameters. Requires a server that
+    /// supports `explain`. Results are cut off and rescaled as by
+    /// [`CasperClient::query`].
+    pub async fn search_explain(
+        &self,
+        collection_name: &str,
+        request: SearchRequest,
+    ) -> Result<ExplainedSearch> {
+        self.require_api(ApiVersion::V1_1, "search explain")?;
+        let url = self.collection_url(collection_name, "/search")?;
+        let params = [("explain", "true".to_string())];
+        let request = self.prepare_query(collection_name, request).await?;
+        let cutoffs = self.cutoffs(collection_name, &request).await?;
+        let response = self
+            .search_request(url, SearchEncoding::Json, &params, request)?
+            .dispatch(self)
+            .await?;
+
+        let query_id = header_query_id(&response);
+        let mut explained: ExplainedSearch = self.handle_response(response).await?;
+        explained.query_id = explained.query_id.or(query_id);
+        cutoffs.apply(&mut explained.results);
+        self.rescale(collection_name, self.score_scale, &mut explained.results).await?;
+        Ok(explained)
+    }
+
+    /// Send a search to a pre-built search URL with additional query parameters
+    pub(crate) async fn send_search(
+        &self,
+        url: Url,
+        collection_name: &str,
+        params: &[(&str, String)],
+        request: SearchRequest,
+    ) -> Result<SearchResponse> {
+        let outcome = self.send_search_outcome(url, collection_name, params, request).await?;
+        Ok(outcome.results)
+    }
+
+    /// [`CasperClient::send_search`] keeping the query ID
+    pub(crate) async fn send_search_outcome(
+        &self,
+        url: Url,
+        collection_name: &str,
+        params: &[(&str, String)],
+        request: SearchRequest,
+    ) -> Result<SearchOutcome> {
+        let request = self.prepare_query(collection_name, request).await?;
+        let cutoffs = self.cutoffs(collection_name, &request).await?;
         let response = self
-            .client
-            .post(url)
+            .search_request(url, self.search_encoding, params, request)?
+            .dispatch(self)
+            .await?;
+
+        let query_id = header_query_id(&response);
+        let mut results = self.handle_search_response(response).await?;
+        cutoffs.apply(&mut results);
+        Ok(SearchOutcome { results, query_id })
+    }
+
+    /// Reject search parameters that are invalid or that the server does not
+    /// support
+    pub(crate) fn check_search(&self, request: &SearchRequest) -> Result<()> {
+        if request.filter.is_some() {
+            self.require_api(ApiVersion::V1_1, "search filters")?;
+        }
+        if request.decay.is_some() {
+            self.require_api(ApiVersion::V1_1, "recency decay")?;
+        }
+        if request.nprobe.is_some() {
+            self.require_api(ApiVersion::V1_1, "nprobe overrides")?;
+        }
+        if request.ef == Some(0) || request.nprobe == Some(0) {
+            return Err(CasperError::InvalidArgument("search ef and nprobe must be positive".to_string()));
+        }
+        if request.score_threshold.is_some_and(f32::is_nan) {
+            return Err(CasperError::InvalidArgument("search score threshold must not be NaN".to_string()));
+        }
+        if request.max_distance.is_some_and(|distance| distance.is_nan() || distance < 0.0) {
+            return Err(CasperError::InvalidArgument("search distance cutoff must be non-negative".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Build a search request asking for the given response encoding; the
+    /// query vector must already be prepared, see [`CasperClient::prepare_query`]
+    pub(crate) fn search_request(
+        &self,
+        url: Url,
+        encoding: SearchEncoding,
+        params: &[(&str, String)],
+        request: SearchRequest,
+    ) -> Result<RequestBuilder> {
+        self.check_search(&request)?;
+        let limit = request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let index_params = [
+            request.max_time_ms.map(|ms| ("max_time_ms", ms.to_string())),
+            request.ef.map(|ef| ("ef", ef.to_string())),
+            request.nprobe.map(|nprobe| ("nprobe", nprobe.to_string())),
+            request.include_deleted.then(|| ("include_deleted", "true".to_string())),
+            request.score_threshold.map(|threshold| ("score_threshold", threshold.to_string())),
+        ];
+        Ok(self
+            .http(Scope::Read, Method::POST, url)
             .query(&[
                 ("limit", limit.to_string()),
-                ("output", "bin".to_string()),
+                ("output", encoding.output().to_string()),
             ])
+            .query(&index_params.into_iter().flatten().collect::<Vec<_>>())
+            .query(params)
             .header("Content-Type", "application/json")
-            .json(&SearchVectorBody { vector: request.vector })
-            .send()
-            .await?;
+            .header("Accept", encoding.accept())
+            .body(self.json_body(&SearchVectorBody {
+                vector: request.vector,
+                filter: request.filter,
+                decay: request.decay,
+            })?))
+    }
 
+    /// Handle a search response, binary or JSON depending on its content type
+    pub(crate) async fn handle_search_response(&self, response: reqwest::Response) -> Result<SearchResponse> {
         let status = response.status();
         if !status.is_success() {
             let text = response.text().await?;
             return Err(self.parse_error_response(status.as_u16(), &text));
         }
 
+        let is_json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
         let bytes = response.bytes().await?;
-        let buf = bytes.as_ref();
-
-        // Binary format:
-        // [u32 LE: count] followed by `count` * (u32 LE id, f32 LE score)
-        if buf.len() < 4 {
-            return Err(CasperError::InvalidResponse(
-                "binary search response too short (missing count)".to_string(),
-            ));
+        if is_json {
+            decode_json_search_results(bytes.as_ref())
+        } else {
+            decode_search_results(bytes.as_ref())
         }
+    }
 
-        let mut offset = 0;
-        let mut count_bytes = [0u8; 4];
-        count_bytes.copy_from_slice(&buf[offset..offset + 4]);
-        let count = u32::from_le_bytes(count_bytes) as usize;
-        offset += 4;
+    /// Get vector by ID
+    pub async fn get_vector(&self, collection_name: &str, id: u32) -> Result<Option<Vec<f32>>> {
+        Ok(self.fetch_vector(collection_name, id).await?.map(|response| response.vector))
+    }
 
-        let expected_len = 4 + count * (4 + 4);
-        if buf.len() < expected_len {
-            return Err(CasperError::InvalidResponse(format!(
-                "binary search response truncated: expected at least {} bytes, got {}",
-                expected_len,
-                buf.len()
-            )));
+    /// Vector `id` with its payload, `None` if it does not exist
+    pub(crate) async fn fetch_vector(&self, collection_name: &str, id: u32) -> Result<Option<GetVectorResponse>> {
+        let url = self.collection_url(collection_name, &format!("/vector/{}", id))?;
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+        
+        if response.status() == 404 {
+            return Ok(None);
         }
+        
+        Ok(Some(self.handle_response(response).await?))
+    }
+
+    /// Number of vectors in a collection.
+    ///
+    /// Servers without the count endpoint (404, 405 or 501) report it with
+    /// the collection info instead.
+    pub async fn count_vectors(&self, collection_name: &str) -> Result<usize> {
+        let url = self.collection_url(collection_name, "/count")?;
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+        if matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return Ok(self.get_collection(collection_name).await?.size);
+        }
+
+        let response: CountVectorsResponse = self.handle_response(response).await?;
+        Ok(response.count)
+    }
 
-        let mut results = Vec::with_capacity(count);
-        for _ in 0..count {
-            let mut id_bytes = [0u8; 4];
-            id_bytes.copy_from_slice(&buf[offset..offset + 4]);
-            let id = u32::from_le_bytes(id_bytes);
-            offset += 4;
+    /// Whether vector `id` exists, checked with a `HEAD` request so the
+    /// vector itself is not downloaded.
+    ///
+    /// Servers not answering `HEAD` (405 or 501) get the vector fetched.
+    pub async fn vector_exists(&self, collection_name: &str, id: u32) -> Result<bool> {
+        let url = self.collection_url(collection_name, &format!("/vector/{}", id))?;
+        let response = self.http(Scope::Read, Method::HEAD, url).dispatch(self).await?;
+        match response.status().as_u16() {
+            404 => Ok(false),
+            405 | 501 => Ok(self.fetch_vector(collection_name, id).await?.is_some()),
+            _ => {
+                self.handle_empty_response(response).await?;
+                Ok(true)
+            }
+        }
+    }
 
-            let mut score_bytes = [0u8; 4];
-            score_bytes.copy_from_slice(&buf[offset..offset + 4]);
-            let score = f32::from_le_bytes(score_bytes);
-            offset += 4;
+    /// Which of `ids` exist in the collection, in the same order.
+    ///
+    /// Sent as one request; servers without the bulk endpoint (404, 405 or
+    /// 501) are asked per ID, 32 at a time. A missing collection fails with
+    /// [`CasperError::CollectionNotFound`] either way.
+    pub async fn contains_ids(&self, collection_name: &str, ids: &[u32]) -> Result<Vec<bool>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
 
-            results.push(SearchResult { id, score });
+        let url = self.collection_url(collection_name, "/contains")?;
+        let response = self
+            .http(Scope::Read, Method::POST, url)
+            .header("Content-Type", "application/json")
+            .json(&ContainsIdsBody { ids: ids.to_vec() })
+            .dispatch(self)
+            .await?;
+        match response.status().as_u16() {
+            405 | 501 => return self.contains_ids_each(collection_name, ids).await,
+            // A missing collection answers 404 too, which would read as
+            // every ID missing when asked per ID
+            404 => {
+                self.get_collection(collection_name).await?;
+                return self.contains_ids_each(collection_name, ids).await;
+            }
+            _ => {}
         }
 
-        Ok(results)
+        let response: ContainsIdsResponse = self.handle_response(response).await?;
+        if response.exists.len() != ids.len() {
+            return Err(CasperError::InvalidResponse(format!(
+                "existence check returned {} flags for {} IDs",
+                response.exists.len(),
+                ids.len()
+            )));
+        }
+        Ok(response.exists)
     }
 
-    /// Get vector by ID
-    pub async fn get_vector(&self, collection_name: &str, id: u32) -> Result<Option<Vec<f32>>> {
-        let url = self.base_url.join(&format!("collection/{}/vector/{}", collection_name, id))?;
-        let response = self.client.get(url).send().await?;
-        
-        if response.status() == 404 {
-            return Ok(None);
-        }
-        
-        let vector_response: GetVectorResponse = self.handle_response(response).await?;
-        Ok(Some(vector_response.vector))
+    /// [`CasperClient::contains_ids`] with one request per ID
+    async fn contains_ids_each(&self, collection_name: &str, ids: &[u32]) -> Result<Vec<bool>> {
+        let checks = crate::parallel::fan_out(ids.iter().copied(), crate::parallel::FAN_OUT_LIMIT, |id| {
+            let client = self.clone();
+            let collection_name = collection_name.to_string();
+            async move { client.get_vector(&collection_name, id).await }
+        });
+        checks.collect().await?.into_iter().map(|vector| Ok(vector?.is_some())).collect()
     }
 
     /// Batch update operations
+    #[deprecated(note = "use `batch_update_results`, which reports the outcome of every operation")]
     pub async fn batch_update(
         &self,
         collection_name: &str,
         request: BatchUpdateRequest,
     ) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}/update", collection_name))?;
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-        
-        self.handle_empty_response(response).await
+        self.batch_update_results(collection_name, request).await?;
+        Ok(())
     }
 
+    /// Batch update operations, reporting the outcome of each.
+    ///
+    /// When the server reports per-operation statuses, rejected IDs are listed
+    /// in [`BatchResult::failed`]; otherwise every ID counts as succeeded. A
+    /// batch queued in the outbox lists every ID in [`BatchResult::pending`].
+    pub async fn batch_update_results(
+        &self,
+        collection_name: &str,
+        request: BatchUpdateRequest,
+    ) -> Result<BatchResult> {
+        let ids: Vec<u32> = request
+            .insert
+            .iter()
+            .map(|op| op.id)
+            .chain(request.delete.iter().copied())
+            .collect();
+
+        let body = self.write(collection_name, WriteOp::BatchUpdate(request)).await?;
+        self.parse_batch_result(ids, body.as_deref())
+    }
+
+    /// Parse a batch update response body for the given request IDs, `None`
+    /// if the batch was queued in the outbox
+    pub(crate) fn parse_batch_result(&self, ids: Vec<u32>, body: Option<&str>) -> Result<BatchResult> {
+        let Some(body) = body else {
+            return Ok(BatchResult { pending: ids, ..Default::default() });
+        };
+        let response = if body.trim().is_empty() {
+            BatchUpdateResponse::default()
+        } else {
+            serde_json::from_str(body).map_err(|e| CasperError::InvalidResponse(format!(
+                "Failed to parse batch update response: {} - {}", e, body
+            )))?
+        };
+
+        Ok(BatchResult::from_response(ids, &response))
+    }
+
+    /// Send a write straight to the server, bypassing the outbox and
+    /// [`CasperClient::send_write`]'s index wait.
+    ///
+    /// Returns the response body, which is empty for most writes.
+    pub(crate) async fn send_write_once(&self, collection_name: &str, op: WriteOp) -> Result<String> {
+        let upsert = matches!(op, WriteOp::Upsert(_));
+        let response = match op {
+            WriteOp::Insert(request) | WriteOp::Upsert(request) => {
+                let url = self.collection_url(collection_name, "/insert")?;
+                let mut query = vec![("id", request.id.to_string())];
+                if upsert {
+                    query.push(("upsert", "true".to_string()));
+                }
+                self.http(Scope::Write, Method::POST, url)
+                    .query(&query)
+                    .header("Content-Type", "application/json")
+                    .body(self.json_body(&InsertVectorBody { vector: request.vector, payload: request.payload })?)
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::UpdateComponents(request) => {
+                let url = self.collection_url(collection_name, &format!("/vector/{}", request.id))?;
+                let body = UpdateComponentsBody { indices: request.indices, values: request.values };
+                self.http(Scope::Write, Method::PATCH, url)
+                    .header("Content-Type", "application/json")
+                    .body(self.json_body(&body)?)
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::UpdatePayload(request) => {
+                let url = self.collection_url(collection_name, &format!("/vector/{}/payload", request.id))?;
+                self.http(Scope::Write, Method::PUT, url)
+                    .header("Content-Type", "application/json")
+                    .body(self.json_body(&request.payload)?)
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::Delete(request) => {
+                let url = self.collection_url(collection_name, "/delete")?;
+                self.http(Scope::Write, Method::DELETE, url)
+                    .query(&[("id", request.id.to_string())])
+                    .header("Content-Type", "application/json")
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::BatchUpdate(request) => {
+                let url = self.collection_url(collection_name, "/update")?;
+                self.http(Scope::Write, Method::POST, url)
+                    .header("Content-Type", "application/json")
+                    .body(self.json_body(&request)?)
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::InsertQuantized(request) => {
+                let url = self.collection_url(collection_name, "/update")?;
+                self.http(Scope::Write, Method::POST, url)
+                    .query(&[("quantization", "i8")])
+                    .header("Content-Type", "application/json")
+                    .json(&request)
+                    .dispatch(self)
+                    .await?
+            }
+            WriteOp::InsertBinary(request) => {
+                let url = self.collection_url(collection_name, "/update")?;
+                self.http(Scope::Write, Method::POST, url)
+                    .query(&[("format", "binary")])
+                    .header("Content-Type", "application/json")
+                    .json(&request)
+                    .dispatch(self)
+                    .await?
+            }
+        };
+        // Searches sent from now on must not see results cached before the write
+        self.invalidate_search_cache(collection_name);
+
+        self.handle_text_response(response).await
+    }
+
+    /// Create an HNSW index, returning the [`Job`] building it; servers
+    /// without jobs have their build followed through the collection info
     pub async fn create_hnsw_index(
         &self,
         collection_name: &str,
         request: CreateHNSWIndexRequest,
-    ) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}/index", collection_name))?;
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-        
-        self.handle_empty_response(response).await
+    ) -> Result<Job> {
+        self.audited("create_hnsw_index", collection_name, 0, async {
+            self.check_writable("create_hnsw_index")?;
+            self.normalization.forget(collection_name);
+            self.dimensions.forget(&self.qualify(collection_name));
+            let url = self.collection_url(collection_name, "/index")?;
+            let request = self.qualify_index(request);
+            let response = self
+                .http(Scope::Admin, Method::POST, url)
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .dispatch(self)
+                .await?;
+
+            let body = self.handle_text_response(response).await?;
+            Ok(Job::index_build_started(self, collection_name, &body))
+        })
+        .await
+    }
+
+    /// Create an IVF-PQ index: IVF coarse quantization with PQ-encoded
+    /// residuals, for collections too large for HNSW in memory.
+    ///
+    /// Checks before sending that `nlist` and `nprobe` are consistent, and
+    /// that the PQ exists and matches the collection dimension. Returns the
+    /// [`Job`] building the index, see [`CasperClient::create_hnsw_index`].
+    pub async fn create_ivf_pq_index(
+        &self,
+        collection_name: &str,
+        request: CreateIvfPqIndexRequest,
+    ) -> Result<Job> {
+        self.audited("create_ivf_pq_index", collection_name, 0, async {
+            self.check_writable("create_ivf_pq_index")?;
+            self.normalization.forget(collection_name);
+            self.dimensions.forget(&self.qualify(collection_name));
+            self.require_api(ApiVersion::V1_1, "IVF-PQ indexes")?;
+            let config = &request.ivf_pq;
+            if config.nlist == 0 || config.nprobe == 0 || config.nprobe > config.nlist {
+                return Err(CasperError::InvalidArgument(format!(
+                    "IVF-PQ index needs 0 < nprobe <= nlist, got nprobe {} and nlist {}",
+                    config.nprobe, config.nlist
+                )));
+            }
+
+            let collection = self.get_collection(collection_name).await?;
+            let pq = match self.get_pq(&config.pq_name).await {
+                Err(CasperError::CollectionNotFound(_)) => {
+                    return Err(CasperError::InvalidArgument(format!("PQ '{}' does not exist", config.pq_name)));
+                }
+                result => result?,
+            };
+            if pq.dim != collection.dimension {
+                return Err(CasperError::InvalidDimension { expected: collection.dimension, actual: pq.dim });
+            }
+
+            let url = self.collection_url(collection_name, "/index")?;
+            let request = self.qualify_ivf_pq(request);
+            let response = self
+                .http(Scope::Admin, Method::POST, url)
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .dispatch(self)
+                .await?;
+
+            let body = self.handle_text_response(response).await?;
+            Ok(Job::index_build_started(self, collection_name, &body))
+        })
+        .await
     }
 
     /// Delete index from collection
     pub async fn delete_index(&self, collection_name: &str) -> Result<()> {
-        let url = self.base_url.join(&format!("collection/{}/index", collection_name))?;
-        let response = self.client.delete(url).send().await?;
-        
-        self.handle_empty_response(response).await
+        self.audited("delete_index", collection_name, 0, async {
+            self.check_destructive("index of collection", collection_name)?;
+            self.normalization.forget(collection_name);
+            self.dimensions.forget(&self.qualify(collection_name));
+            let url = self.collection_url(collection_name, "/index")?;
+            let response = self.http(Scope::Admin, Method::DELETE, url).dispatch(self).await?;
+
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// Upload a matrix via gRPC streaming using the configured gRPC address.
     ///
+    /// The connection is opened by the first upload and shared by later ones
+    /// (and by clones of the client); see [`CasperClient::upload_matrix_grpc`]
+    /// to target another address.
+    ///
     /// - `matrix_name`: name of the matrix to create/overwrite
     /// - `dimension`: vector dimensionality
     /// - `vectors`: flat list of all vectors, concatenated row-wise
     /// - `chunk_floats`: number of f32 values per chunk (must be >= dimension)
+    ///
+    /// Returns a [`Matrix`] handle to the uploaded matrix.
     pub async fn upload_matrix(
         &self,
         matrix_name: &str,
         dimension: usize,
         vectors: Vec<f32>,
         chunk_floats: usize,
-    ) -> Result<UploadMatrixResult> {
-        use crate::error::CasperError;
-
-        if dimension == 0 {
-            return Err(CasperError::InvalidResponse(
-                "dimension must be greater than 0".to_string(),
-            ));
-        }
+    ) -> Result<Matrix> {
+        let result = self.spawn_matrix_upload(matrix_name, dimension, vectors, chunk_floats)?.await?;
+        Ok(Matrix::uploaded(self.clone(), matrix_name, dimension, &result))
+    }
 
-        if vectors.len() % dimension != 0 {
-            return Err(CasperError::InvalidResponse(format!(
-                "vector buffer length {} is not divisible by dimension {}",
-                vectors.len(),
-                dimension
-            )));
-        }
+    /// [`CasperClient::upload_matrix`] from a buffer of little-endian f32
+    /// bytes, e.g. a file read or memory-mapped from disk, or a network
+    /// payload.
+    ///
+    /// The buffer is chunked as is and only a chunk at a time is decoded, so
+    /// multi-GB inputs are never copied into a `Vec<f32>`. It must outlive
+    /// the upload, hence the `'static` bound: pass an owned buffer (`Vec<u8>`,
+    /// `bytes::Bytes`, a memory map) rather than a borrowed slice.
+    pub async fn upload_matrix_bytes<B>(
+        &self,
+        matrix_name: &str,
+        dimension: usize,
+        bytes: B,
+        chunk_floats: usize,
+    ) -> Result<UploadMatrixResult>
+    where
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        self.check_writable("upload_matrix_bytes")?;
+        let chunk_floats = self.upload_chunk_floats(chunk_floats, 1);
+        let upload = MatrixUpload::from_le_bytes(&self.qualify(matrix_name), dimension, Arc::new(bytes), chunk_floats)?;
+        upload::spawn(self.grpc_target(), upload, self.audit.clone()).await
+    }
 
-        let chunk_floats = if chunk_floats < dimension {
-            dimension
-        } else {
-            chunk_floats
+    /// Upload a matrix to the gRPC service at `grpc_addr` instead of the
+    /// configured endpoint, e.g. a dedicated ingest node.
+    ///
+    /// Opens a connection for this upload only; otherwise the same as
+    /// [`CasperClient::upload_matrix`].
+    pub async fn upload_matrix_grpc(
+        &self,
+        grpc_addr: &str,
+        matrix_name: &str,
+        dimension: usize,
+        vectors: Vec<f32>,
+        chunk_floats: usize,
+    ) -> Result<Matrix> {
+        self.check_writable("upload_matrix")?;
+        let chunk_floats = self.upload_chunk_floats(chunk_floats, 1);
+        let upload = MatrixUpload::new(&self.qualify(matrix_name), dimension, vectors, chunk_floats)?;
+        let target = GrpcTarget {
+            channel: Arc::new(GrpcChannel::new(grpc_addr.to_string(), self.timeouts.connect_timeout())),
+            ..self.grpc_target()
         };
+        let result = upload::spawn(target, upload, self.audit.clone()).await?;
+        Ok(Matrix::uploaded(self.clone(), matrix_name, dimension, &result))
+    }
 
-        let total_floats = vectors.len();
-        let total_chunks = (total_floats + chunk_floats - 1) / chunk_floats;
+    /// Upload a matrix and verify it against the server afterwards.
+    ///
+    /// Same parameters as [`CasperClient::upload_matrix`]. Once the upload
+    /// completes, the server-side row count, dimension and (when reported)
+    /// checksum are compared with the local data, returning
+    /// [`CasperError::MatrixMismatch`] on any difference.
+    pub async fn upload_matrix_verified(
+        &self,
+        matrix_name: &str,
+        dimension: usize,
+        vectors: Vec<f32>,
+        chunk_floats: usize,
+    ) -> Result<UploadMatrixResult> {
+        let digest = MatrixDigest::compute(dimension, &vectors);
+        let result = self.spawn_matrix_upload(matrix_name, dimension, vectors, chunk_floats)?.await?;
+        self.verify_matrix(matrix_name, &digest).await?;
+        Ok(result)
+    }
 
-        let mut client = MatrixServiceClient::connect(self.grpc_addr.clone())
-            .await
-            .map_err(|e| CasperError::Grpc(e.to_string()))?;
-
-        let (tx, rx) = tokio::sync::mpsc::channel::<UploadMatrixRequest>(4);
-
-        // Spawn producer task to send header + chunks
-        let name = matrix_name.to_string();
-        let vectors_clone = vectors.clone();
-        tokio::spawn(async move {
-            // Header first
-            let max_vectors_per_chunk = (chunk_floats / dimension).max(1) as u32;
-            let header = MatrixHeader {
-                name: name.clone(),
-                dimension: dimension as u32,
-                total_chunks: total_chunks as u32,
-                max_vectors_per_chunk,
-            };
-            let header_msg = UploadMatrixRequest {
-                payload: Some(upload_matrix_request::Payload::Header(header)),
-            };
-            if tx.send(header_msg).await.is_err() {
-                return;
-            }
+    /// Compare a server-side matrix with a locally computed digest
+    pub async fn verify_matrix(&self, matrix_name: &str, digest: &MatrixDigest) -> Result<()> {
+        let info = self.get_matrix_info(matrix_name).await?;
+        digest.verify(&info)
+    }
 
-            // Then data chunks
-            for chunk_idx in 0..total_chunks {
-                let start = chunk_idx * chunk_floats;
-                let end = (start + chunk_floats).min(total_floats);
-                let slice = &vectors_clone[start..end];
-
-                let data = MatrixData {
-                    chunk_index: chunk_idx as u32,
-                    vector: slice.to_vec(),
-                };
-                let msg = UploadMatrixRequest {
-                    payload: Some(upload_matrix_request::Payload::Data(data)),
-                };
-
-                if tx.send(msg).await.is_err() {
-                    break;
-                }
-            }
-        });
+    /// Start a matrix upload in the background and return a handle to it.
+    ///
+    /// Same parameters as [`CasperClient::upload_matrix`]. The returned
+    /// [`UploadHandle`] can be awaited for the result or aborted, in which
+    /// case the server discards the partially uploaded matrix.
+    pub fn spawn_matrix_upload(
+        &self,
+        matrix_name: &str,
+        dimension: usize,
+        vectors: Vec<f32>,
+        chunk_floats: usize,
+    ) -> Result<UploadHandle> {
+        self.check_writable("spawn_matrix_upload")?;
+        let chunk_floats = self.upload_chunk_floats(chunk_floats, 1);
+        let upload = MatrixUpload::new(&self.qualify(matrix_name), dimension, vectors, chunk_floats)?;
+        Ok(upload::spawn(self.grpc_target(), upload, self.audit.clone()))
+    }
 
-        let request = Request::new(ReceiverStream::new(rx));
-        let response = client
-            .upload_matrix(request)
-            .await
-            .map_err(|e| CasperError::Grpc(e.to_string()))?
-            .into_inner();
-
-        Ok(UploadMatrixResult {
-            success: true,
-            message: format!(
-                "Successfully uploaded {} vectors in {} chunks",
-                response.total_vectors, response.total_chunks
-            ),
-            total_vectors: response.total_vectors,
-            total_chunks: response.total_chunks,
+    /// Upload a large matrix over several concurrent gRPC streams.
+    ///
+    /// The matrix is sharded by chunk range, one shard per stream, which
+    /// requires API [`ApiVersion::V1_2`]; older servers would ignore the shard
+    /// fields, so they are refused before anything is sent. If any shard fails
+    /// the others are aborted and the server discards the partial matrix.
+    ///
+    /// - `streams`: number of concurrent streams (clamped to the chunk count)
+    pub async fn upload_matrix_parallel(
+        &self,
+        matrix_name: &str,
+        dimension: usize,
+        vectors: Vec<f32>,
+        chunk_floats: usize,
+        streams: usize,
+    ) -> Result<UploadMatrixResult> {
+        self.audited("upload_matrix_parallel", matrix_name, vectors.len() / dimension.max(1), async {
+            self.check_writable("upload_matrix_parallel")?;
+            self.ensure_api(ApiVersion::V1_2, "sharded matrix uploads").await?;
+            let chunk_floats = self.upload_chunk_floats(chunk_floats, streams);
+            let upload = MatrixUpload::new(&self.qualify(matrix_name), dimension, vectors, chunk_floats)?;
+            upload::upload_sharded(self.grpc_target(), upload, streams).await
         })
+        .await
+    }
+
+    /// Upload several matrices over one gRPC connection, e.g. all PQ codebooks.
+    ///
+    /// - `matrices`: `(name, dimension, vectors)` triples
+    /// - `chunk_floats`: number of f32 values per chunk, applied to every matrix
+    /// - `parallelism`: maximum number of concurrent uploads (1 = sequential)
+    ///
+    /// Returns one `(name, result)` entry per matrix, in input order. Only a
+    /// failure to connect fails the call as a whole.
+    pub async fn upload_matrices<I, S>(
+        &self,
+        matrices: I,
+        chunk_floats: usize,
+        parallelism: usize,
+    ) -> Result<Vec<(String, Result<UploadMatrixResult>)>>
+    where
+        I: IntoIterator<Item = (S, usize, Vec<f32>)>,
+        S: Into<String>,
+    {
+        self.check_writable("upload_matrices")?;
+        let chunk_floats = self.upload_chunk_floats(chunk_floats, parallelism);
+        let (names, uploads): (Vec<String>, Vec<_>) = matrices
+            .into_iter()
+            .map(|(name, dimension, vectors)| {
+                let name = name.into();
+                let upload = MatrixUpload::new(&self.qualify(&name), dimension, vectors, chunk_floats);
+                (name, upload)
+            })
+            .unzip();
+
+        let results = upload::upload_many(self.grpc_target(), uploads, parallelism, self.audit.clone()).await?;
+        Ok(names.into_iter().zip(results).collect())
     }
 
     /// Delete a matrix by name (HTTP)
     pub async fn delete_matrix(&self, name: &str) -> Result<()> {
-        let url = self.base_url.join(&format!("matrix/{}", name))?;
-        let response = self
-            .client
-            .delete(url)
-            .header("Content-Type", "application/json")
-            .send()
-            .await?;
+        self.audited("delete_matrix", name, 0, async {
+            self.check_destructive("matrix", name)?;
+            let url = self.matrix_url(name)?;
+            let response = self
+                .http(Scope::Admin, Method::DELETE, url)
+                .header("Content-Type", "application/json")
+                .dispatch(self)
+                .await?;
 
-        self.handle_empty_response(response).await
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// List all matrices (HTTP)
     pub async fn list_matrices(&self) -> Result<Vec<MatrixInfo>> {
         let url = self.base_url.join("matrix/list")?;
         let response = self
-            .client
-            .get(url)
+            .http(Scope::Read, Method::GET, url)
             .header("Content-Type", "application/json")
-            .send()
+            .dispatch(self)
             .await?;
 
-        self.handle_response(response).await
+        let matrices: Vec<MatrixInfo> = self.handle_response(response).await?;
+        Ok(matrices.into_iter().filter_map(|info| self.localize_matrix(info)).collect())
     }
 
-    /// Get matrix info by name (HTTP)
-    pub async fn get_matrix_info(&self, name: &str) -> Result<MatrixInfo> {
-        let url = self.base_url.join(&format!("matrix/{}", name))?;
+    /// Get matrix info by name (HTTP), as a handle to the matrix
+    pub async fn get_matrix_info(&self, name: &str) -> Result<Matrix> {
+        let url = self.matrix_url(name)?;
         let response = self
-            .client
-            .get(url)
+            .http(Scope::Read, Method::GET, url)
             .header("Content-Type", "application/json")
-            .send()
+            .dispatch(self)
             .await?;
 
-        self.handle_response(response).await
+        let info: MatrixInfo = self.handle_response(response).await?;
+        Ok(Matrix::new(self.clone(), self.localize_matrix(info.clone()).unwrap_or(info)))
     }
 
     /// Create a PQ entry
@@ -414,59 +1077,218 @@ impl CasperClient {
         name: &str,
         request: CreatePqRequest,
     ) -> Result<()> {
-        let url = self.base_url.join(&format!("pq/{}", name))?;
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        self.audited("create_pq", name, request.codebooks.len(), async {
+            self.check_writable("create_pq")?;
+            let url = self.pq_url(name)?;
+            let request = self.qualify_pq(request);
+            let response = self
+                .http(Scope::Admin, Method::POST, url)
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .dispatch(self)
+                .await?;
 
-        self.handle_empty_response(response).await
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// Delete a PQ entry
     pub async fn delete_pq(&self, name: &str) -> Result<()> {
-        let url = self.base_url.join(&format!("pq/{}", name))?;
-        let response = self
-            .client
-            .delete(url)
-            .header("Content-Type", "application/json")
-            .send()
-            .await?;
+        self.audited("delete_pq", name, 0, async {
+            self.check_destructive("PQ", name)?;
+            let url = self.pq_url(name)?;
+            let response = self
+                .http(Scope::Admin, Method::DELETE, url)
+                .header("Content-Type", "application/json")
+                .dispatch(self)
+                .await?;
 
-        self.handle_empty_response(response).await
+            self.handle_empty_response(response).await
+        })
+        .await
     }
 
     /// List all PQs
     pub async fn list_pqs(&self) -> Result<Vec<PqInfo>> {
         let url = self.base_url.join("pq/list")?;
         let response = self
-            .client
-            .get(url)
+            .http(Scope::Read, Method::GET, url)
             .header("Content-Type", "application/json")
-            .send()
+            .dispatch(self)
             .await?;
 
-        self.handle_response(response).await
+        let pqs: Vec<PqInfo> = self.handle_response(response).await?;
+        Ok(pqs.into_iter().filter_map(|info| self.localize_pq(info)).collect())
     }
 
     /// Get PQ info by name
     pub async fn get_pq(&self, name: &str) -> Result<PqInfo> {
-        let url = self.base_url.join(&format!("pq/{}", name))?;
+        let url = self.pq_url(name)?;
         let response = self
-            .client
-            .get(url)
+            .http(Scope::Read, Method::GET, url)
             .header("Content-Type", "application/json")
-            .send()
+            .dispatch(self)
             .await?;
 
-        self.handle_response(response).await
+        let info: PqInfo = self.handle_response(response).await?;
+        Ok(self.localize_pq(info.clone()).unwrap_or(info))
+    }
+
+    /// Apply client-side preprocessing configured for the collection to an
+    /// insert or query vector: dimension reduction, then normalization if
+    /// `normalize` (see [`CasperClient::normalizes`])
+    pub(crate) fn prepare_vector(&self, collection_name: &str, vector: Vec<f32>, normalize: bool) -> Result<Vec<f32>> {
+        let mut vector = match self.reducers.get(collection_name) {
+            Some(reducer) => reducer.transform(&vector)?,
+            None => vector,
+        };
+        if normalize {
+            vector::normalize(&mut vector)?;
+        }
+        Ok(vector)
+    }
+
+    /// Apply [`CasperClient::prepare_vector`] to the vector of a search
+    pub(crate) async fn prepare_query(&self, collection_name: &str, mut request: SearchRequest) -> Result<SearchRequest> {
+        dimensions::check_dimension(self.expected_dimension(collection_name).await?, &request.vector)?;
+        let normalize = self.normalizes(collection_name).await?;
+        request.vector = self.prepare_vector(collection_name, request.vector, normalize)?;
+        Ok(request)
+    }
+
+    /// Apply [`CasperClient::prepare_vector`] to every vector of a write
+    pub(crate) async fn prepare_write(&self, collection_name: &str, op: WriteOp) -> Result<WriteOp> {
+        if matches!(op, WriteOp::UpdateComponents(_)) && self.reducers.contains_key(collection_name) {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "partial updates are not supported on collection '{}' with a dimension reducer",
+                collection_name
+            )));
+        }
+        let dimension = self.expected_dimension(collection_name).await?;
+        let normalize = self.normalizes(collection_name).await?;
+        op.map_vectors(|vector| {
+            dimensions::check_dimension(dimension, &vector)?;
+            self.prepare_vector(collection_name, vector, normalize)
+        })
+    }
+
+    /// Start an HTTP request carrying the credentials for `scope`
+    pub(crate) fn http(&self, scope: Scope, method: Method, url: Url) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, url)
+            .header(version::API_VERSION_HEADER, self.api.pinned().to_string());
+        match self.tokens.get(scope) {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Build and send an HTTP request through the configured middleware
+    pub(crate) async fn execute(&self, request: RequestBuilder) -> Result<Response> {
+        self.dispatch_request(request.build()?).await
+    }
+
+    /// Send a built HTTP request, signing it if a signer is configured
+    pub(crate) async fn execute_request(&self, mut request: reqwest::Request) -> Result<Response> {
+        if let Some(prober) = &self.prober {
+            prober.start(self);
+        }
+        let attempts = self.retry.prepare(&mut request);
+        // Signatures cover the body as sent
+        #[cfg(feature = "zstd")]
+        if let Some(compressor) = &self.compression {
+            compressor.encode_request(&mut request)?;
+        }
+        self.limits.check_body(&request)?;
+        if let Some(session) = &self.session {
+            session.attach(&mut request);
+        }
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut request)?;
+        }
+
+        if request.timeout().is_none() {
+            *request.timeout_mut() = self.timeouts.attempt_timeout();
+        }
+
+        let Some(slow_call) = self.slow_calls.start(&request) else {
+            return self.send_within_deadline(request, attempts).await;
+        };
+        let start = Instant::now();
+        let response = self.send_within_deadline(request, attempts).await;
+        self.slow_calls.finish(slow_call, start.elapsed());
+        response
+    }
+
+    /// [`CasperClient::send_with_retries`], bound by the operation deadline
+    async fn send_within_deadline(&self, request: reqwest::Request, attempts: u32) -> Result<Response> {
+        let Some(deadline) = self.timeouts.operation_deadline() else {
+            return self.send_with_retries(request, attempts).await;
+        };
+        tokio::time::timeout(deadline, self.send_with_retries(request, attempts))
+            .await
+            .map_err(|_| CasperError::DeadlineExceeded(deadline))?
+    }
+
+    /// Send a built request up to `attempts` times, see [`RetryPolicy`]
+    async fn send_with_retries(&self, mut request: reqwest::Request, attempts: u32) -> Result<Response> {
+        let mut attempt = 1;
+        loop {
+            // Streamed bodies cannot be cloned and are never retried
+            let retry = if attempt < attempts { request.try_clone() } else { None };
+            let result = self.send_request(request).await;
+            match retry {
+                Some(next) if RetryPolicy::should_retry(&result) => {
+                    let Some(delay) = self.retry.delay(attempt, &result) else {
+                        return result;
+                    };
+                    tokio::time::sleep(delay).await;
+                    request = next;
+                    attempt += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Send a built request, hedging idempotent reads if configured
+    async fn send_request(&self, request: reqwest::Request) -> Result<Response> {
+        let response = match &self.hedger {
+            Some(hedger) if Hedger::applies_to(&request) => hedger.execute(self.transport.as_ref(), request).await?,
+            _ => self.transport.send(request).await?,
+        };
+        self.api.observe(&response);
+        if let Some(session) = &self.session {
+            session.observe(&response);
+        }
+        #[cfg(feature = "zstd")]
+        if let Some(compressor) = &self.compression {
+            return compressor.decode_response(response).await;
+        }
+        Ok(response)
+    }
+
+    /// gRPC endpoint for matrix uploads, authenticated with the admin token
+    pub(crate) fn grpc_target(&self) -> GrpcTarget {
+        GrpcTarget {
+            channel: self.grpc.clone(),
+            token: self.tokens.get(Scope::Admin).map(str::to_string),
+            deadline: self.timeouts.operation_deadline(),
+            progress: self.progress.clone(),
+            http_fallback: self.http_upload_fallback.then(|| self.clone()),
+            limits: self.limits,
+        }
+    }
+
+    /// Serialize a JSON request body using the configured float format
+    pub(crate) fn json_body<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        Ok(encoding::to_json_vec(value, self.float_format)?)
     }
 
     /// Handle JSON response
-    async fn handle_response<T>(&self, response: reqwest::Response) -> Result<T>
+    pub(crate) async fn handle_response<T>(&self, response: reqwest::Response) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
@@ -494,14 +1316,25 @@ impl CasperClient {
         }
     }
 
+    /// Handle a response whose body is returned as raw text
+    pub(crate) async fn handle_text_response(&self, response: reqwest::Response) -> Result<String> {
+        let status = response.status();
+        let text = response.text().await?;
+
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(self.parse_error_response(status.as_u16(), &text))
+        }
+    }
 
     /// Parse error response
     fn parse_error_response(&self, status: u16, text: &str) -> CasperError {
         // Try to parse as JSON error response
-        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(text) {
-            if let Some(message) = error_json.get("error").and_then(|v| v.as_str()) {
-                return CasperError::from_status(status, message.to_string());
-            }
+        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(text)
+            && let Some(message) = error_json.get("error").and_then(|v| v.as_str())
+        {
+            return CasperError::from_status(status, message.to_string());
         }
         
         // Fallback to status-based error
@@ -509,13 +1342,401 @@ impl CasperClient {
     }
 }
 
+/// JSON search response: a list of results (`[id, score]` pairs or
+/// `{"id", "score"}` objects), optionally wrapped in `{"results": [...]}`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum JsonSearchResults {
+    List(SearchResponse),
+    Wrapped { results: SearchResponse },
+}
+
+/// Decode a JSON search response
+pub(crate) fn decode_json_search_results(buf: &[u8]) -> Result<SearchResponse> {
+    match serde_json::from_slice(buf) {
+        Ok(JsonSearchResults::List(results) | JsonSearchResults::Wrapped { results }) => Ok(results),
+        Err(e) => Err(CasperError::InvalidResponse(format!(
+            "Failed to parse JSON search response: {} - {}",
+            e,
+            String::from_utf8_lossy(buf)
+        ))),
+    }
+}
+
+/// Query ID from the response headers, if the server sent one
+fn header_query_id(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(QUERY_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Sends a request through [`CasperClient::execute`], so that client-wide
+/// request processing applies to every endpoint
+pub(crate) trait Dispatch {
+    fn dispatch(self, client: &CasperClient) -> impl Future<Output = Result<Response>> + Send;
+}
+
+impl Dispatch for RequestBuilder {
+    fn dispatch(self, client: &CasperClient) -> impl Future<Output = Result<Response>> + Send {
+        client.execute(self)
+    }
+}
+
+/// Decode a binary search response:
+/// `[u32 LE: count]` followed by `count` * `(u32 LE id, f32 LE score)`
+pub(crate) fn decode_search_results(buf: &[u8]) -> Result<SearchResponse> {
+    if buf.len() < 4 {
+        return Err(CasperError::InvalidResponse(
+            "binary search response too short (missing count)".to_string(),
+        ));
+    }
+
+    let mut offset = 0;
+    let mut count_bytes = [0u8; 4];
+    count_bytes.copy_from_slice(&buf[offset..offset + 4]);
+    let count = u32::from_le_bytes(count_bytes) as usize;
+    offset += 4;
+
+    let expected_len = 4 + count * (4 + 4);
+    if buf.len() < expected_len {
+        return Err(CasperError::InvalidResponse(format!(
+            "binary search response truncated: expected at least {} bytes, got {}",
+            expected_len,
+            buf.len()
+        )));
+    }
+
+    let mut results = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut id_bytes = [0u8; 4];
+        id_bytes.copy_from_slice(&buf[offset..offset + 4]);
+        let id = u32::from_le_bytes(id_bytes);
+        offset += 4;
+
+        let mut score_bytes = [0u8; 4];
+        score_bytes.copy_from_slice(&buf[offset..offset + 4]);
+        let score = f32::from_le_bytes(score_bytes);
+        offset += 4;
+
+        results.push(SearchResult { id, score });
+    }
+
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Scripted;
 
     #[test]
     fn test_client_creation() {
         let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
         assert_eq!(client.base_url(), "http://localhost:8080/");
     }
+
+    #[test]
+    fn test_search_limit_from_request() {
+        let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        let limit_of = |request: SearchRequest| {
+            let url = client.collection_url("docs", "/search").unwrap();
+            let built = client
+                .search_request(url, SearchEncoding::Binary, &[], request)
+                .unwrap()
+                .build()
+                .unwrap();
+            built.url().query_pairs().find(|(k, _)| k == "limit").unwrap().1.into_owned()
+        };
+
+        assert_eq!(limit_of(SearchRequest { vector: vec![1.0], limit: Some(3), ..Default::default() }), "3");
+        assert_eq!(limit_of(SearchRequest { vector: vec![1.0], ..Default::default() }), "10");
+    }
+
+    #[test]
+    fn test_search_index_overrides() {
+        let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        let url = client.collection_url("docs", "/search").unwrap();
+        let request = SearchRequest { vector: vec![1.0], ef: Some(256), nprobe: Some(8), ..Default::default() };
+        let built = client.search_request(url.clone(), SearchEncoding::Json, &[], request).unwrap().build().unwrap();
+        assert_eq!(built.url().query(), Some("limit=10&output=json&ef=256&nprobe=8"));
+
+        let zero = SearchRequest { vector: vec![1.0], ef: Some(0), ..Default::default() };
+        assert!(client.search_request(url, SearchEncoding::Json, &[], zero).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_payload() {
+        let scripted = Scripted::new([(200, "")]);
+        scripted.client().update_payload("docs", 7, serde_json::json!({"tag": "a"})).await.unwrap();
+        assert_eq!(scripted.requests(), ["PUT /collection/docs/vector/7/payload"]);
+        assert_eq!(scripted.last_json(), serde_json::json!({"tag": "a"}));
+        assert_eq!(scripted.last_header("content-type").as_deref(), Some("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_update_vector_components() {
+        let scripted = Scripted::new([(200, "")]);
+        let client = scripted.client();
+        client.update_vector_components("docs", 7, &[(0, 0.5), (3, -1.0)]).await.unwrap();
+        client.update_vector_components("docs", 7, &[]).await.unwrap();
+        assert_eq!(scripted.requests(), ["PATCH /collection/docs/vector/7"]);
+        assert_eq!(scripted.last_json(), serde_json::json!({"indices": [0, 3], "values": [0.5, -1.0]}));
+    }
+
+    #[tokio::test]
+    async fn test_collection_labels() {
+        let scripted = Scripted::new([
+            (200, ""),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":0,
+                "index":null,"labels":{"env":"dev","owner":"search"}}"#),
+            (200, ""),
+        ]);
+        let client = scripted.client();
+        let labels = |pairs: &[(&str, &str)]| -> Labels {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        let request = CreateCollectionRequest { dim: 2, max_size: 10, labels: labels(&[("env", "dev")]) };
+        client.create_collection("docs", request).await.unwrap();
+        assert_eq!(scripted.last_query("dim").as_deref(), Some("2"));
+        assert_eq!(scripted.last_json(), serde_json::json!({"labels": {"env": "dev"}}));
+
+        // Updates read the current labels and write back the merged set
+        client.update_collection_labels("docs", labels(&[("env", "prod"), ("team", "ml")])).await.unwrap();
+        assert_eq!(scripted.requests()[1..], ["GET /collection/docs", "PUT /collection/docs/labels"]);
+        assert_eq!(
+            scripted.last_json(),
+            serde_json::json!({"labels": {"env": "prod", "owner": "search", "team": "ml"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_mutable() {
+        let scripted = Scripted::new([(200, "")]);
+        scripted.client().set_mutable("docs", false).await.unwrap();
+        assert_eq!(scripted.requests(), ["PUT /collection/docs/mutable"]);
+        assert_eq!(scripted.last_json(), serde_json::json!({"mutable": false}));
+
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .read_only()
+            .build()
+            .unwrap();
+        let err = client.set_mutable("docs", true).await.unwrap_err();
+        assert!(matches!(err, CasperError::OperationNotAllowed(msg) if msg.contains("set_mutable")));
+        assert_eq!(scripted.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resize_collection() {
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":4,
+            "index":null}"#;
+        let scripted =
+            Scripted::new([(200, docs), (200, ""), (200, docs), (200, ""), (200, docs), (200, ""), (200, docs)]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .validate_dimensions(true)
+            .build()
+            .unwrap();
+        let insert = |id| InsertRequest { id, vector: vec![1.0, 0.0], payload: None };
+
+        client.insert_vector("docs", insert(1)).await.unwrap();
+        client.resize_collection("docs", 20).await.unwrap();
+        assert_eq!(scripted.last_query("max_size").as_deref(), Some("20"));
+        // The shape cached by the first insert is fetched again
+        client.insert_vector("docs", insert(2)).await.unwrap();
+        assert_eq!(
+            scripted.requests()[1..],
+            [
+                "POST /collection/docs/insert",
+                "GET /collection/docs",
+                "POST /collection/docs/resize",
+                "GET /collection/docs",
+                "POST /collection/docs/insert",
+            ]
+        );
+
+        let err = client.resize_collection("docs", 4).await.unwrap_err();
+        assert!(matches!(err, CasperError::InvalidArgument(msg) if msg.contains("current size 4")));
+    }
+
+    #[tokio::test]
+    async fn test_search_explain() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"cosine","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"{"results":[{"id":1,"score":0.5},{"id":2,"score":-0.5}],
+                "explain":{"nodes_visited":42,"distance_computations":120,"phase_micros":{"search":350}}}"#),
+        ])
+        .header(QUERY_ID_HEADER, "q-7");
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        let request = SearchRequest { vector: vec![1.0, 0.0], score_threshold: Some(0.0), ..Default::default() };
+        let explained = client.search_explain("docs", request).await.unwrap();
+        assert_eq!(explained.results.iter().map(|r| (r.id, r.score)).collect::<Vec<_>>(), [(1, 0.75)]);
+        assert_eq!((explained.explain.nodes_visited, explained.explain.distance_computations), (42, 120));
+        assert_eq!(explained.explain.phase_micros["search"], 350);
+        assert_eq!(explained.query_id.as_deref(), Some("q-7"));
+        assert_eq!(scripted.requests()[1], "POST /collection/docs/search");
+        assert_eq!(scripted.last_query("explain").as_deref(), Some("true"));
+        assert_eq!(scripted.last_query("output").as_deref(), Some("json"));
+    }
+
+    #[tokio::test]
+    async fn test_score_threshold() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"docs","dimension":1,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"[{"id":1,"score":0.4},{"id":2,"score":0.9}]"#),
+            (200, r#"[{"id":1,"score":0.4},{"id":2,"score":0.9}]"#),
+        ]);
+        let client = scripted.client();
+
+        // L2 scores are distances, so the threshold keeps the lower ones
+        let request = SearchRequest { vector: vec![1.0], score_threshold: Some(0.5), ..Default::default() };
+        let results = client.query("docs", request).await.unwrap();
+        assert_eq!((results.len(), results[0].id), (1, 1));
+        assert_eq!(scripted.last_query("score_threshold").as_deref(), Some("0.5"));
+
+        let request = SearchRequest { vector: vec![1.0], max_distance: Some(0.5), ..Default::default() };
+        assert_eq!(client.query("docs", request).await.unwrap().len(), 1);
+        assert_eq!(scripted.last_query("max_distance"), None);
+        assert_eq!(scripted.requests().len(), 3);
+
+        let request = SearchRequest { vector: vec![1.0], max_distance: Some(-1.0), ..Default::default() };
+        assert!(matches!(client.query("docs", request).await, Err(CasperError::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ivf_pq_probe_validation() {
+        let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        let request = CreateIvfPqIndexRequest {
+            ivf_pq: IvfPqIndexConfig {
+                metric: "inner-product".to_string(),
+                nlist: 16,
+                nprobe: 32,
+                pq_name: "pq".to_string(),
+            },
+            normalization: None,
+        };
+        let err = client.create_ivf_pq_index("docs", request).await.unwrap_err();
+        assert!(matches!(err, CasperError::InvalidArgument(msg) if msg.contains("nprobe")));
+    }
+
+    #[tokio::test]
+    async fn test_sharded_upload_needs_server_support() {
+        // A server without `GET /version` speaks 1.0 and would ignore the shards
+        let scripted = Scripted::new([(404, "not found")]);
+        let err = scripted.client().upload_matrix_parallel("m", 2, vec![0.0; 8], 2, 2).await.unwrap_err();
+        assert!(matches!(err, CasperError::ApiVersionUnsupported { required: ApiVersion::V1_2, .. }));
+        assert_eq!(scripted.requests(), ["GET /version"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_profile_id_is_escaped() {
+        let scripted = Scripted::new([(404, "")]);
+        assert!(scripted.client().get_query_profile("a/b?c").await.unwrap().is_none());
+        assert_eq!(scripted.requests(), ["GET /query/a%2Fb%3Fc/profile"]);
+    }
+
+    #[test]
+    fn test_decode_json_search_results() {
+        let pairs = decode_json_search_results(b"[[3, 0.5], [1, 0.25]]").unwrap();
+        assert_eq!((pairs[0].id, pairs[1].score), (3, 0.25));
+
+        let wrapped = decode_json_search_results(br#"{"results": [{"id": 7, "score": 1.0}]}"#).unwrap();
+        assert_eq!(wrapped[0].id, 7);
+
+        assert!(decode_json_search_results(b"\x01\x00").is_err());
+    }
+
+    #[test]
+    fn test_upload_status_classification() {
+        let status = tonic::Status::invalid_argument("bad dimension");
+        assert!(matches!(
+            CasperError::from_upload_status(status, None, false),
+            CasperError::UploadHeaderRejected { .. }
+        ));
+
+        let status = tonic::Status::invalid_argument("bad chunk");
+        assert!(matches!(
+            CasperError::from_upload_status(status, Some(3), false),
+            CasperError::UploadChunkRejected { chunk_index: 3, .. }
+        ));
+
+        let status = tonic::Status::failed_precondition("row count mismatch");
+        assert!(matches!(
+            CasperError::from_upload_status(status, Some(7), true),
+            CasperError::UploadValidation { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_payloads() {
+        let scripted = crate::testing::Scripted::new([
+            (200, ""),
+            (200, r#"{"id":1,"vector":[1.0],"payload":{"doc":"a"}}"#),
+            (404, "not found"),
+        ]);
+        let client = scripted.client();
+
+        client.update_payload("docs", 1, serde_json::json!({"doc": "a"})).await.unwrap();
+        let payload = client.get_payload("docs", 1).await.unwrap();
+        assert_eq!(payload, Some(serde_json::json!({"doc": "a"})));
+        assert!(matches!(client.get_payload("docs", 2).await, Err(CasperError::VectorNotFound(2))));
+        assert_eq!(scripted.requests()[0], "PUT /collection/docs/vector/1/payload");
+    }
+
+    #[tokio::test]
+    async fn test_count_and_exists() {
+        let scripted = crate::testing::Scripted::new([
+            (200, r#"{"count":42}"#),
+            (501, "not implemented"),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":100,"size":7,"index":null}"#),
+            (200, ""),
+            (404, ""),
+        ]);
+        let client = scripted.client();
+
+        assert_eq!(client.count_vectors("docs").await.unwrap(), 42);
+        assert_eq!(client.count_vectors("docs").await.unwrap(), 7);
+        assert!(client.vector_exists("docs", 1).await.unwrap());
+        assert!(!client.vector_exists("docs", 2).await.unwrap());
+        assert_eq!(scripted.requests()[3], "HEAD /collection/docs/vector/1");
+    }
+
+    #[tokio::test]
+    async fn test_contains_ids() {
+        let scripted = Scripted::new([
+            (200, r#"{"exists":[true,false]}"#),
+            (404, "not found"),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":1,
+                "index":null}"#),
+            (200, r#"{"id":1,"vector":[1.0,0.0]}"#),
+            (404, "not found"),
+            (404, "not found"),
+            (404, "collection not found"),
+        ]);
+        let client = scripted.client();
+
+        assert_eq!(client.contains_ids("docs", &[1, 2]).await.unwrap(), [true, false]);
+        assert_eq!(scripted.last_json(), serde_json::json!({"ids": [1, 2]}));
+
+        // Without the bulk endpoint each ID is fetched once the collection is found
+        assert_eq!(client.contains_ids("docs", &[1, 2]).await.unwrap(), [true, false]);
+        assert_eq!(scripted.requests()[1..3], ["POST /collection/docs/contains", "GET /collection/docs"]);
+        assert_eq!(scripted.requests().len(), 5);
+
+        let err = client.contains_ids("gone", &[1, 2]).await.unwrap_err();
+        assert!(matches!(err, CasperError::CollectionNotFound(_)));
+        assert_eq!(scripted.requests()[5..], ["POST /collection/gone/contains", "GET /collection/gone"]);
+    }
 }
@@ -0,0 +1,180 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::kernels::squared_l2;
+use crate::parallel;
+use crate::rng::Rng;
+
+/// Mini-batch k-means parameters
+#[derive(Debug, Clone)]
+pub struct KMeansConfig {
+    /// Number of clusters
+    pub k: usize,
+    /// Points sampled per iteration
+    pub batch_size: usize,
+    /// Number of mini-batch iterations
+    pub max_iterations: usize,
+    /// Seed for initialization and sampling
+    pub seed: u64,
+}
+
+impl KMeansConfig {
+    /// Config with `k` clusters and default batch size / iterations
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            batch_size: 1024,
+            max_iterations: 100,
+            seed: 42,
+        }
+    }
+}
+
+/// Fitted k-means centroids
+#[derive(Debug, Clone)]
+pub struct KMeansModel {
+    pub centroids: Vec<Vec<f32>>,
+}
+
+impl KMeansModel {
+    /// Index of the centroid closest to `vector` (squared L2)
+    pub fn predict(&self, vector: &[f32]) -> usize {
+        nearest(&self.centroids, vector).0
+    }
+
+    /// Closest centroid for each vector
+    pub fn predict_all<V: AsRef<[f32]> + Sync>(&self, vectors: &[V]) -> Vec<usize> {
+        parallel::map(vectors, |v| self.predict(v.as_ref()))
+    }
+}
+
+/// Clustering of a set of collection vectors
+#[derive(Debug, Clone)]
+pub struct ClusterResult {
+    pub model: KMeansModel,
+    /// `(vector id, cluster index)` for every clustered vector
+    pub assignments: Vec<(u32, usize)>,
+}
+
+fn nearest(centroids: &[Vec<f32>], vector: &[f32]) -> (usize, f32) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| (idx, squared_l2(c, vector)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, f32::INFINITY))
+}
+
+/// k-means++ seeding over `data`
+fn init_centroids<V: AsRef<[f32]>>(data: &[V], k: usize, rng: &mut Rng) -> Vec<Vec<f32>> {
+    let mut centroids = vec![data[rng.below(data.len())].as_ref().to_vec()];
+    let mut distances: Vec<f32> = data
+        .iter()
+        .map(|v| squared_l2(v.as_ref(), &centroids[0]))
+        .collect();
+
+    while centroids.len() < k {
+        let total: f64 = distances.iter().map(|&d| d as f64).sum();
+        let next = if total > 0.0 {
+            let mut target = rng.next_f64() * total;
+            distances
+                .iter()
+                .position(|&d| {
+                    target -= d as f64;
+                    target <= 0.0
+                })
+                .unwrap_or(data.len() - 1)
+        } else {
+            rng.below(data.len())
+        };
+
+        let centroid = data[next].as_ref().to_vec();
+        for (d, v) in distances.iter_mut().zip(data) {
+            *d = d.min(squared_l2(v.as_ref(), &centroid));
+        }
+        centroids.push(centroid);
+    }
+
+    centroids
+}
+
+/// Fit mini-batch k-means (Sculley, 2010) on `data`.
+///
+/// Assignment of each mini-batch runs on the rayon pool when the `rayon`
+/// feature is enabled.
+pub fn fit<V: AsRef<[f32]> + Sync>(config: &KMeansConfig, data: &[V]) -> Result<KMeansModel> {
+    if config.k == 0 || data.len() < config.k {
+        return Err(CasperError::InvalidArgument(format!(
+            "k-means needs 0 < k <= number of vectors (k = {}, vectors = {})",
+            config.k,
+            data.len()
+        )));
+    }
+    let dim = data[0].as_ref().len();
+    if let Some(bad) = data.iter().find(|v| v.as_ref().len() != dim) {
+        return Err(CasperError::InvalidDimension { expected: dim, actual: bad.as_ref().len() });
+    }
+
+    let mut rng = Rng::new(config.seed);
+    let mut centroids = init_centroids(data, config.k, &mut rng);
+    let mut counts = vec![0usize; config.k];
+    let batch_size = config.batch_size.clamp(1, data.len());
+
+    for _ in 0..config.max_iterations {
+        let batch: Vec<&[f32]> = (0..batch_size)
+            .map(|_| data[rng.below(data.len())].as_ref())
+            .collect();
+        let assigned = parallel::map(&batch, |v| nearest(&centroids, v).0);
+
+        for (v, c) in batch.iter().zip(assigned) {
+            counts[c] += 1;
+            let rate = 1.0 / counts[c] as f32;
+            for (x, y) in centroids[c].iter_mut().zip(v.iter()) {
+                *x += rate * (y - *x);
+            }
+        }
+    }
+
+    Ok(KMeansModel { centroids })
+}
+
+impl CasperClient {
+    /// Fetch vectors `ids` from a collection and cluster them locally
+    pub async fn cluster_collection(
+        &self,
+        collection_name: &str,
+        ids: &[u32],
+        config: &KMeansConfig,
+    ) -> Result<ClusterResult> {
+        let vectors = self.fetch_vectors(collection_name, ids).await?;
+        let model = fit(config, &vectors)?;
+        let assignments = ids.iter().copied().zip(model.predict_all(&vectors)).collect();
+
+        Ok(ClusterResult { model, assignments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separates_two_blobs() {
+        let mut data = Vec::new();
+        for i in 0..50 {
+            let jitter = (i % 5) as f32 * 0.01;
+            data.push(vec![0.0 + jitter, 0.0 - jitter]);
+            data.push(vec![10.0 - jitter, 10.0 + jitter]);
+        }
+
+        let mut config = KMeansConfig::new(2);
+        config.batch_size = 20;
+        config.max_iterations = 50;
+        let model = fit(&config, &data).unwrap();
+
+        let a = model.predict(&[0.0, 0.0]);
+        let b = model.predict(&[10.0, 10.0]);
+        assert_ne!(a, b);
+        assert!(squared_l2(&model.centroids[a], &[0.0, 0.0]) < 0.1);
+        assert!(squared_l2(&model.centroids[b], &[10.0, 10.0]) < 0.1);
+    }
+}
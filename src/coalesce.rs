@@ -0,0 +1,62 @@
+use crate::cache::SearchKey;
+use crate::error::Result;
+use crate::models::SearchResponse;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Searches being sent, so identical concurrent ones share a request, see
+/// [`crate::CasperClientBuilder::coalesce_searches`]
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    searches: Mutex<HashMap<SearchKey, Arc<OnceCell<SearchResponse>>>>,
+}
+
+impl InFlight {
+    /// Results of the search `key` in flight, or of `send` if there is none.
+    ///
+    /// Errors are not shared: when the request fails, its caller gets the
+    /// error and the next waiting caller sends its own request.
+    pub(crate) async fn coalesce<F>(&self, key: &SearchKey, send: F) -> Result<SearchResponse>
+    where
+        F: Future<Output = Result<SearchResponse>>,
+    {
+        let search = self.searches.lock().expect("in-flight searches poisoned").entry(key.clone()).or_default().clone();
+        let results = search.get_or_try_init(|| send).await.cloned();
+
+        // Later searches must send a fresh request
+        let mut searches = self.searches.lock().expect("in-flight searches poisoned");
+        if searches.get(key).is_some_and(|current| Arc::ptr_eq(current, &search)) {
+            searches.remove(key);
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::SearchRequest;
+    use crate::testing::Scripted;
+    use crate::CasperClient;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_coalesces_identical_searches() {
+        let scripted = Scripted::new([(200, r#"[{"id":1,"score":0.9}]"#), (200, r#"[{"id":2,"score":0.8}]"#)])
+            .delayed(Duration::from_millis(20));
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .coalesce_searches(true)
+            .build()
+            .unwrap();
+        let search = || SearchRequest { vector: vec![0.5, 0.25], limit: Some(5), ..Default::default() };
+
+        let (first, second) = tokio::join!(client.query("docs", search()), client.query("docs", search()));
+        assert_eq!((first.unwrap()[0].id, second.unwrap()[0].id), (1, 1));
+        assert_eq!(scripted.requests().len(), 1);
+
+        // Once answered, the same search is sent again
+        assert_eq!(client.query("docs", search()).await.unwrap()[0].id, 2);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::filter::Filter;
+use crate::job::Job;
+use crate::models::{
+    CollectionInfo, CreateHNSWIndexRequest, CreateIvfPqIndexRequest, DeleteRequest, InsertRequest, RecencyDecay,
+    SearchRequest, SearchResponse,
+};
+use crate::scores::ScoreScale;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use url::Url;
+
+/// Handle to a single collection, see [`CasperClient::collection`].
+///
+/// Its methods are those of [`CasperClient`] with the collection name
+/// filled in. The handle builds its endpoint URLs once and caches the collection info
+/// on first use, so it is worth keeping around (it is cheap to clone).
+///
+/// ```no_run
+/// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+/// let results = client
+///     .collection("docs")
+///     .search(vec![0.1, 0.2, 0.3])
+///     .limit(10)
+///     .ef(128)
+///     .min_score(0.7)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CollectionHandle {
+    pub(crate) client: CasperClient,
+    pub(crate) name: String,
+    search_url: Url,
+    info: Arc<OnceCell<CollectionInfo>>,
+}
+
+impl CasperClient {
+    /// Handle for fluent operations on `collection_name`
+    pub fn collection(&self, collection_name: &str) -> CollectionHandle {
+        // The name only ever appears as a path segment, joining cannot fail
+        let search_url = self
+            .collection_url(collection_name, "/search")
+            .unwrap_or_else(|_| self.base_url.clone());
+        CollectionHandle {
+            client: self.clone(),
+            name: collection_name.to_string(),
+            search_url,
+            info: Arc::new(OnceCell::new()),
+        }
+    }
+}
+
+impl CollectionHandle {
+    /// Collection name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Collection info, fetched once and cached for the handle's lifetime
+    pub async fn info(&self) -> Result<&CollectionInfo> {
+        self.info
+            .get_or_try_init(|| self.client.get_collection(&self.name))
+            .await
+    }
+
+    /// Start a search with `vector` as the query; await the builder to send it
+    pub fn search(&self, vector: Vec<f32>) -> SearchBuilder<'_> {
+        SearchBuilder {
+            collection: self,
+            request: SearchRequest { vector, ..Default::default() },
+            min_score: None,
+            scores: self.client.score_scale,
+        }
+    }
+
+    /// The `limit` nearest neighbours of the stored vector `id`, see
+    /// [`CasperClient::search_by_id`]
+    pub async fn search_by_id(&self, id: u32, limit: usize) -> Result<SearchResponse> {
+        self.client.search_by_id(&self.name, id, limit).await
+    }
+
+    /// Insert a vector, see [`CasperClient::insert_vector`]
+    pub async fn insert(&self, request: InsertRequest) -> Result<()> {
+        self.client.insert_vector(&self.name, request).await
+    }
+
+    /// Delete the vector stored under `id`
+    pub async fn delete(&self, id: u32) -> Result<()> {
+        self.client.delete_vector(&self.name, DeleteRequest { id }).await
+    }
+
+    /// Create an HNSW or IVF-PQ index, returning the [`Job`] building it.
+    ///
+    /// The info cached by this handle is not refreshed; take a new handle to
+    /// see the index in [`CollectionHandle::info`].
+    pub async fn create_index(&self, request: impl Into<IndexRequest>) -> Result<Job> {
+        match request.into() {
+            IndexRequest::Hnsw(request) => self.client.create_hnsw_index(&self.name, request).await,
+            IndexRequest::IvfPq(request) => self.client.create_ivf_pq_index(&self.name, request).await,
+        }
+    }
+}
+
+/// Index built by [`CollectionHandle::create_index`]
+#[derive(Debug, Clone)]
+pub enum IndexRequest {
+    Hnsw(CreateHNSWIndexRequest),
+    IvfPq(CreateIvfPqIndexRequest),
+}
+
+impl From<CreateHNSWIndexRequest> for IndexRequest {
+    fn from(request: CreateHNSWIndexRequest) -> Self {
+        Self::Hnsw(request)
+    }
+}
+
+impl From<CreateIvfPqIndexRequest> for IndexRequest {
+    fn from(request: CreateIvfPqIndexRequest) -> Self {
+        Self::IvfPq(request)
+    }
+}
+
+/// Fluent search on a [`CollectionHandle`], sent when awaited
+#[derive(Debug)]
+#[must_use = "a search does nothing unless awaited"]
+pub struct SearchBuilder<'a> {
+    collection: &'a CollectionHandle,
+    request: SearchRequest,
+    min_score: Option<f32>,
+    scores: ScoreScale,
+}
+
+impl SearchBuilder<'_> {
+    /// Maximum number of results (default 10)
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.request.limit = Some(limit);
+        self
+    }
+
+    /// HNSW search breadth for this query
+    pub fn ef(mut self, ef: usize) -> Self {
+        self.request.ef = Some(ef);
+        self
+    }
+
+    /// IVF lists probed by this query, see [`SearchRequest::nprobe`]
+    pub fn nprobe(mut self, nprobe: usize) -> Self {
+        self.request.nprobe = Some(nprobe);
+        self
+    }
+
+    /// Also return deleted vectors not purged yet, see
+    /// [`SearchRequest::include_deleted`]
+    pub fn include_deleted(mut self, include: bool) -> Self {
+        self.request.include_deleted = include;
+        self
+    }
+
+    /// Server-side time budget, see [`SearchRequest::max_time_ms`]
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.request.max_time_ms = Some(max_time.as_millis() as u64);
+        self
+    }
+
+    /// Report scores on `scale` instead of the client's
+    /// [`score_scale`](crate::CasperClientBuilder::score_scale)
+    pub fn scores(mut self, scale: ScoreScale) -> Self {
+        self.scores = scale;
+        self
+    }
+
+    /// Drop results scoring below `min_score`, compared with the scores as
+    /// reported (see [`SearchBuilder::scores`])
+    pub fn min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Restrict results by payload filter
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.request.filter = Some(filter);
+        self
+    }
+
+    /// Apply recency-decay scoring
+    pub fn decay(mut self, decay: RecencyDecay) -> Self {
+        self.request.decay = Some(decay);
+        self
+    }
+
+    async fn send(self) -> Result<SearchResponse> {
+        let collection = self.collection;
+        let client = &collection.client;
+        let mut results = client
+            .cached_search(collection.search_url.clone(), &collection.name, &[], self.request)
+            .await?;
+
+        client.rescale(&collection.name, self.scores, &mut results).await?;
+        if let Some(min_score) = self.min_score {
+            results.retain(|r| r.score >= min_score);
+        }
+        Ok(results)
+    }
+}
+
+impl<'a> IntoFuture for SearchBuilder<'a> {
+    type Output = Result<SearchResponse>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_urls() {
+        let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        let docs = client.collection("docs");
+        assert_eq!(docs.name(), "docs");
+        assert_eq!(docs.search_url.as_str(), "http://localhost:8080/collection/docs/search");
+    }
+
+    #[tokio::test]
+    async fn test_handle_writes() {
+        let scripted = crate::testing::Scripted::new([(200, ""), (200, ""), (204, "")]);
+        let docs = scripted.client().collection("docs");
+        docs.insert(InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None }).await.unwrap();
+        docs.delete(1).await.unwrap();
+        let hnsw = crate::models::HNSWIndexConfig {
+            metric: "cosine".to_string(),
+            quantization: "f32".to_string(),
+            m: 16,
+            m0: 32,
+            ef_construction: 200,
+            pq_name: None,
+        };
+        docs.create_index(CreateHNSWIndexRequest { hnsw, normalization: None }).await.unwrap();
+        assert_eq!(
+            scripted.requests(),
+            ["POST /collection/docs/insert", "DELETE /collection/docs/delete", "POST /collection/docs/index"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_builder() {
+        let scripted = crate::testing::Scripted::new([
+            (200, r#"[{"id":1,"score":0.9},{"id":2,"score":0.4}]"#),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":2,
+                "index":null}"#),
+        ]);
+        let docs = scripted.client().collection("docs");
+
+        // The dimension is only fetched when the client validates it
+        let results = docs.search(vec![1.0, 0.0]).limit(2).min_score(0.5).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), [1]);
+        assert_eq!(scripted.requests(), ["POST /collection/docs/search"]);
+
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .validate_dimensions(true)
+            .build()
+            .unwrap();
+        let err = client.collection("docs").search(vec![1.0, 0.0, 0.0]).await.unwrap_err();
+        assert!(matches!(err, crate::CasperError::InvalidDimension { expected: 2, actual: 3 }));
+        assert_eq!(scripted.requests()[1..], ["GET /collection/docs"]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Client-side zstd compression, enabled with the `zstd` feature and
+//! [`CasperClientBuilder::compression`](crate::CasperClientBuilder::compression).
+//!
+//! Request bodies over [`Compression::min_body_bytes`] are sent with
+//! `Content-Encoding: zstd` once the server has advertised zstd in an
+//! `Accept-Encoding` response header, so servers without support never see
+//! a compressed body. Responses are requested with `Accept-Encoding: zstd`
+//! and decompressed before the client reads them, and
+//! [`CasperClient::export_collection`](crate::CasperClient::export_collection)
+//! writes compressed chunk files that imports decompress.
+
+use crate::error::{CasperError, Result};
+use reqwest::header::{HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH};
+use reqwest::{Request, Response};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// `Content-Encoding` of zstd bodies, also the compression recorded in
+/// [`crate::ExportManifest::compression`]
+pub const ZSTD_ENCODING: &str = "zstd";
+
+/// zstd settings of a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    level: i32,
+    min_body_bytes: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self { level: 3, min_body_bytes: 64 * 1024 }
+    }
+}
+
+impl Compression {
+    /// Level 3, compressing request bodies of 64 KiB and more
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// zstd level, from 1 (fastest) to 22 (smallest)
+    pub fn level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Smallest request body worth compressing; smaller ones are sent as is
+    pub fn min_body_bytes(mut self, bytes: usize) -> Self {
+        self.min_body_bytes = bytes;
+        self
+    }
+}
+
+/// [`Compression`] with what the server was seen to accept
+#[derive(Debug)]
+pub(crate) struct Compressor {
+    settings: Compression,
+    server_accepts: AtomicBool,
+}
+
+impl Compressor {
+    pub(crate) fn new(settings: Compression) -> Self {
+        Self { settings, server_accepts: AtomicBool::new(false) }
+    }
+
+    pub(crate) fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        Ok(zstd::bulk::compress(bytes, self.settings.level)?)
+    }
+
+    /// Ask for zstd responses, and compress the body of `request` if it is
+    /// large enough and the server takes zstd bodies. Streamed bodies are
+    /// left alone.
+    pub(crate) fn encode_request(&self, request: &mut Request) -> Result<()> {
+        request.headers_mut().insert(ACCEPT_ENCODING, HeaderValue::from_static(ZSTD_ENCODING));
+        if !self.server_accepts.load(Ordering::Relaxed) || request.headers().contains_key(CONTENT_ENCODING) {
+            return Ok(());
+        }
+        let Some(body) = request.body().and_then(|body| body.as_bytes()) else {
+            return Ok(());
+        };
+        if body.len() < self.settings.min_body_bytes {
+            return Ok(());
+        }
+
+        let compressed = self.compress(body)?;
+        *request.body_mut() = Some(compressed.into());
+        request.headers_mut().insert(CONTENT_ENCODING, HeaderValue::from_static(ZSTD_ENCODING));
+        request.headers_mut().remove(CONTENT_LENGTH);
+        Ok(())
+    }
+
+    /// Remember whether the server takes zstd bodies, and decompress a zstd
+    /// response
+    pub(crate) async fn decode_response(&self, response: Response) -> Result<Response> {
+        let advertised = response.headers().get_all(ACCEPT_ENCODING).iter().any(|value| {
+            value.to_str().is_ok_and(|value| value.split(',').any(|coding| is_zstd(coding.split(';').next())))
+        });
+        if advertised {
+            self.server_accepts.store(true, Ordering::Relaxed);
+        } else if response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+            // The server (or a proxy in front of it) refused an encoding
+            self.server_accepts.store(false, Ordering::Relaxed);
+        }
+
+        let encoding = response.headers().get(CONTENT_ENCODING).and_then(|value| value.to_str().ok());
+        if !is_zstd(encoding) {
+            return Ok(response);
+        }
+        let status = response.status();
+        let version = response.version();
+        let mut headers = response.headers().clone();
+        headers.remove(CONTENT_ENCODING);
+        headers.remove(CONTENT_LENGTH);
+
+        let mut decoded = http::Response::new(decompress(&response.bytes().await?)?);
+        *decoded.status_mut() = status;
+        *decoded.version_mut() = version;
+        *decoded.headers_mut() = headers;
+        Ok(Response::from(decoded))
+    }
+}
+
+fn is_zstd(coding: Option<&str>) -> bool {
+    coding.is_some_and(|coding| coding.trim().eq_ignore_ascii_case(ZSTD_ENCODING))
+}
+
+/// Decompress a zstd frame, e.g. a compressed export chunk
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Vec<u8>> {
+    zstd::stream::decode_all(bytes)
+        .map_err(|e| CasperError::InvalidResponse(format!("Failed to decompress zstd data: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compress_once_advertised() {
+        let compressor = Compressor::new(Compression::new().min_body_bytes(16));
+        let body = vec![b'7'; 1024];
+        let request = || {
+            reqwest::Client::new().post("http://localhost/collection/docs/update").body(body.clone()).build().unwrap()
+        };
+
+        // Nothing is compressed before the server advertises zstd
+        let mut plain = request();
+        compressor.encode_request(&mut plain).unwrap();
+        assert_eq!(plain.body().unwrap().as_bytes(), Some(&body[..]));
+        assert_eq!(plain.headers()[ACCEPT_ENCODING], ZSTD_ENCODING);
+
+        let advertising = http::Response::builder().header(ACCEPT_ENCODING, "gzip, zstd").body("").unwrap();
+        compressor.decode_response(Response::from(advertising)).await.unwrap();
+        let mut compressed = request();
+        compressor.encode_request(&mut compressed).unwrap();
+        assert_eq!(compressed.headers()[CONTENT_ENCODING], ZSTD_ENCODING);
+        let sent = compressed.body().unwrap().as_bytes().unwrap();
+        assert!(sent.len() < body.len());
+        assert_eq!(decompress(sent).unwrap(), body);
+
+        let encoded = http::Response::builder()
+            .header(CONTENT_ENCODING, ZSTD_ENCODING)
+            .body(compressor.compress(br#"{"count":3}"#).unwrap())
+            .unwrap();
+        let decoded = compressor.decode_response(Response::from(encoded)).await.unwrap();
+        assert!(!decoded.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(decoded.text().await.unwrap(), r#"{"count":3}"#);
+    }
+
+    #[tokio::test]
+    async fn test_compressed_export() {
+        let dir = std::env::temp_dir().join(format!("casper-zstd-export-{}", std::process::id()));
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":1,"index":null}"#;
+        let scripted = crate::testing::Scripted::new([
+            (200, docs),
+            (200, r#"{"vectors":[{"id":1,"vector":[0.1,0.2]}]}"#),
+            (200, docs),
+            (200, ""),
+        ]);
+        let client = crate::CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .compression(Compression::new())
+            .build()
+            .unwrap();
+
+        let manifest = client.export_collection("docs", &dir).await.unwrap();
+        assert_eq!(manifest.compression.as_deref(), Some(ZSTD_ENCODING));
+        assert_eq!(manifest.chunks[0].file, "chunk-00000.json.zst");
+        let chunk = tokio::fs::read(dir.join(&manifest.chunks[0].file)).await.unwrap();
+        assert!(decompress(&chunk).unwrap().starts_with(br#"{"insert":[{"id":1"#));
+
+        // Clients without compression still read compressed exports
+        let scripted = crate::testing::Scripted::new([(200, docs), (200, "")]);
+        scripted.client().import_collection(&dir, "docs").await.unwrap();
+        assert_eq!(scripted.requests(), ["GET /collection/docs", "POST /collection/docs/update"]);
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::models::SearchRequest;
+use crate::parallel;
+use std::collections::BTreeMap;
+use tokio_stream::StreamExt;
+
+/// Neighbours examined per vector; larger duplicate groups are still found
+/// through the pairs of their members
+const NEIGHBOURS: usize = 10;
+/// Scanned vectors searched for per batch
+const SEARCH_BATCH: usize = 256;
+/// Searches in flight at once
+const PROBE_PARALLELISM: usize = 32;
+
+/// Two stored vectors whose similarity reached the threshold (`a < b`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePair {
+    pub a: u32,
+    pub b: u32,
+    pub score: f32,
+}
+
+/// Near-duplicates found in a collection
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateReport {
+    /// Vectors examined
+    pub scanned: usize,
+    /// Every pair at or above the threshold, sorted by IDs
+    pub pairs: Vec<DuplicatePair>,
+    /// Connected groups of duplicates (transitively), each sorted, smallest
+    /// ID first
+    pub groups: Vec<Vec<u32>>,
+}
+
+impl CasperClient {
+    /// Find vectors of a collection whose similarity score to another vector
+    /// is at least `threshold`, for dataset cleaning.
+    ///
+    /// Stored vectors are read with [`CasperClient::scan_vectors`] and each
+    /// one is searched for, in concurrent batches. This costs a search per
+    /// stored vector, so it is meant for offline jobs.
+    pub async fn find_duplicates(&self, collection_name: &str, threshold: f32) -> Result<DuplicateReport> {
+        let client = self.stored_space();
+        let mut report = DuplicateReport::default();
+        let mut pairs = BTreeMap::new();
+
+        let mut scan = self.scan_vectors(collection_name);
+        let mut batch = Vec::with_capacity(SEARCH_BATCH);
+        loop {
+            let next = scan.next().await.transpose()?;
+            let done = next.is_none();
+            batch.extend(next);
+            if batch.len() < SEARCH_BATCH && !done {
+                continue;
+            }
+
+            let searches = parallel::fan_out(batch.drain(..), PROBE_PARALLELISM, |(id, vector)| {
+                let client = client.clone();
+                let collection_name = collection_name.to_string();
+                let request = SearchRequest { vector, limit: Some(NEIGHBOURS + 1), ..Default::default() };
+                async move { (id, client.query(&collection_name, request).await) }
+            });
+            for (id, neighbours) in searches.collect().await? {
+                report.scanned += 1;
+                for neighbour in neighbours? {
+                    if neighbour.id != id && neighbour.score >= threshold {
+                        pairs.entry((id.min(neighbour.id), id.max(neighbour.id))).or_insert(neighbour.score);
+                    }
+                }
+            }
+            if done {
+                break;
+            }
+        }
+
+        report.pairs = pairs
+            .into_iter()
+            .map(|((a, b), score)| DuplicatePair { a, b, score })
+            .collect();
+        report.groups = group_pairs(&report.pairs);
+        Ok(report)
+    }
+}
+
+/// Connected components of the duplicate graph
+fn group_pairs(pairs: &[DuplicatePair]) -> Vec<Vec<u32>> {
+    let mut parent: BTreeMap<u32, u32> = BTreeMap::new();
+
+    fn root(parent: &mut BTreeMap<u32, u32>, id: u32) -> u32 {
+        let mut current = *parent.entry(id).or_insert(id);
+        while parent[&current] != current {
+            current = parent[&current];
+        }
+        parent.insert(id, current);
+        current
+    }
+
+    for pair in pairs {
+        let (a, b) = (root(&mut parent, pair.a), root(&mut parent, pair.b));
+        parent.insert(a.max(b), a.min(b));
+    }
+
+    let mut groups: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    let ids: Vec<u32> = parent.keys().copied().collect();
+    for id in ids {
+        let group = root(&mut parent, id);
+        groups.entry(group).or_default().push(id);
+    }
+    groups.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_group_pairs() {
+        let pair = |a, b| DuplicatePair { a, b, score: 0.99 };
+        let groups = group_pairs(&[pair(4, 9), pair(1, 7), pair(7, 9), pair(2, 3)]);
+        assert_eq!(groups, vec![vec![1, 4, 7, 9], vec![2, 3]]);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicates_scans_stored_vectors() {
+        let neighbours = r#"[{"id":1,"score":1.0},{"id":2,"score":0.98}]"#;
+        let scripted = Scripted::new([
+            (200, r#"{"vectors":[{"id":1,"vector":[1.0,0.0]},{"id":2,"vector":[0.99,0.01]}]}"#),
+            (200, neighbours),
+            (200, neighbours),
+        ]);
+
+        let report = scripted.client().find_duplicates("docs", 0.95).await.unwrap();
+        assert_eq!(report.scanned, 2);
+        assert_eq!(report.pairs, [DuplicatePair { a: 1, b: 2, score: 0.98 }]);
+        assert_eq!(report.groups, [vec![1, 2]]);
+        assert_eq!(scripted.requests()[0], "GET /collection/docs/scan");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::scores;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Collection dimensions and index metrics learned from collection info and
+/// shared by all clones of a client, so vectors of the wrong length are
+/// rejected before being sent and scores are rescaled without fetching the
+/// collection on every search.
+///
+/// Dimensions are only checked when enabled, see
+/// [`crate::CasperClientBuilder::validate_dimensions`].
+#[derive(Debug, Default)]
+pub(crate) struct Dimensions {
+    enabled: bool,
+    known: RwLock<HashMap<String, Shape>>,
+}
+
+/// The parts of a collection's info searches and writes depend on
+#[derive(Debug, Clone)]
+struct Shape {
+    dimension: usize,
+    /// `None` while the collection has no index
+    metric: Option<String>,
+}
+
+impl Dimensions {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self { enabled, ..Default::default() }
+    }
+
+    fn cached(&self, collection_name: &str) -> Option<Shape> {
+        self.known.read().expect("dimension cache poisoned").get(collection_name).cloned()
+    }
+
+    fn remember(&self, collection_name: &str, shape: Shape) {
+        self.known.write().expect("dimension cache poisoned").insert(collection_name.to_string(), shape);
+    }
+
+    /// Drop the shape of a collection, by server-side name, that was
+    /// deleted, recreated or reindexed
+    pub(crate) fn forget(&self, collection_name: &str) {
+        self.known.write().expect("dimension cache poisoned").remove(collection_name);
+    }
+}
+
+impl CasperClient {
+    /// Shape of a collection, fetched on first use and cached until the
+    /// client deletes, recreates or reindexes the collection
+    async fn shape(&self, collection_name: &str) -> Result<Shape> {
+        // Keyed by server-side name, as namespaced clones share the cache
+        match self.dimensions.cached(&self.qualify(collection_name)) {
+            Some(shape) => Ok(shape),
+            None => self.fetch_shape(collection_name).await,
+        }
+    }
+
+    async fn fetch_shape(&self, collection_name: &str) -> Result<Shape> {
+        let info = self.get_collection(collection_name).await?;
+        let metric = scores::index_metric(&info).ok().map(str::to_string);
+        let shape = Shape { dimension: info.dimension, metric };
+        self.dimensions.remember(&self.qualify(collection_name), shape.clone());
+        Ok(shape)
+    }
+
+    /// Length vectors sent to a collection must have: the input dimension
+    /// of the collection's reducer, or the collection's dimension
+    pub(crate) async fn input_dimension(&self, collection_name: &str) -> Result<usize> {
+        match self.reducers.get(collection_name) {
+            Some(reducer) => Ok(reducer.input_dim()),
+            None => Ok(self.shape(collection_name).await?.dimension),
+        }
+    }
+
+    /// [`CasperClient::input_dimension`], `None` when not validated
+    pub(crate) async fn expected_dimension(&self, collection_name: &str) -> Result<Option<usize>> {
+        if !self.dimensions.enabled {
+            return Ok(None);
+        }
+        self.input_dimension(collection_name).await.map(Some)
+    }
+
+    /// Metric of a collection's index, the one its scores are compared and
+    /// rescaled by
+    pub(crate) async fn collection_metric(&self, collection_name: &str) -> Result<String> {
+        let shape = match self.dimensions.cached(&self.qualify(collection_name)) {
+            Some(shape) if shape.metric.is_some() => shape,
+            // Another client may have built the index since
+            _ => self.fetch_shape(collection_name).await?,
+        };
+        shape.metric.ok_or_else(|| {
+            CasperError::InvalidArgument(format!(
+                "collection '{}' has no index metric to compare scores by",
+                collection_name
+            ))
+        })
+    }
+}
+
+/// Reject `vector` unless it has the `expected` length (if known)
+pub(crate) fn check_dimension(expected: Option<usize>, vector: &[f32]) -> Result<()> {
+    match expected {
+        Some(expected) if vector.len() != expected => {
+            Err(CasperError::InvalidDimension { expected, actual: vector.len() })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{InsertRequest, SearchRequest};
+    use crate::testing::Scripted;
+    use crate::{CasperClient, CasperError, ScoreScale};
+
+    #[tokio::test]
+    async fn test_validates_dimensions_locally() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":0,"index":null}"#),
+            (200, ""),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .validate_dimensions(true)
+            .build()
+            .unwrap();
+
+        let wrong = InsertRequest { id: 1, vector: vec![0.5, 1.0, 0.0], payload: None };
+        let inserted = client.insert_vector("docs", wrong).await;
+        assert!(matches!(inserted, Err(CasperError::InvalidDimension { expected: 2, actual: 3 })));
+        let search = SearchRequest { vector: vec![0.5], ..Default::default() };
+        assert!(matches!(client.query("docs", search).await, Err(CasperError::InvalidDimension { .. })));
+
+        client.insert_vector("docs", InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None }).await.unwrap();
+        assert_eq!(scripted.requests(), ["GET /collection/docs", "POST /collection/docs/insert"]);
+    }
+
+    #[tokio::test]
+    async fn test_dimensions_and_metric_are_fetched_once() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":1,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"[{"id":1,"score":3.0}]"#),
+            (200, r#"[{"id":1,"score":1.0}]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .validate_dimensions(true)
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        let search = SearchRequest { vector: vec![0.5, 1.0], ..Default::default() };
+        assert_eq!(client.query("docs", search).await.unwrap()[0].score, 0.25);
+        let results = client.collection("docs").search(vec![0.5, 1.0]).await.unwrap();
+        assert_eq!(results[0].score, 0.5);
+        assert_eq!(
+            scripted.requests(),
+            ["GET /collection/docs", "POST /collection/docs/search", "POST /collection/docs/search"]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Embedding distribution statistics for detecting model drift.
+//!
+//! Compute [`DistributionStats`] over a collection (or a local reference
+//! matrix, e.g. the one a collection was first built from), keep them, and
+//! [`compare`](DistributionStats::compare) later snapshots against them.
+
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::vector;
+use serde::{Deserialize, Serialize};
+
+/// Summary of a set of vectors.
+///
+/// Serializable, so a baseline can be stored and compared against later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistributionStats {
+    pub count: usize,
+    pub dimension: usize,
+    /// Mean Euclidean norm
+    pub mean_norm: f32,
+    /// Per-dimension mean (the centroid)
+    pub mean: Vec<f32>,
+    /// Per-dimension population variance
+    pub variance: Vec<f32>,
+}
+
+/// Differences between two [`DistributionStats`], `current` relative to
+/// `baseline`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftReport {
+    /// Euclidean distance between the centroids
+    pub centroid_shift: f32,
+    /// Cosine similarity of the centroids (1 when they point the same way)
+    pub centroid_cosine: f32,
+    /// `current.mean_norm - baseline.mean_norm`
+    pub mean_norm_delta: f32,
+    /// Per-dimension `current.mean - baseline.mean`
+    pub mean_delta: Vec<f32>,
+    /// Per-dimension `current.variance / baseline.variance` (infinite where the
+    /// baseline variance is zero but the current one is not)
+    pub variance_ratio: Vec<f32>,
+}
+
+impl DriftReport {
+    /// Dimension whose mean moved the most, with its shift
+    pub fn max_mean_shift(&self) -> Option<(usize, f32)> {
+        self.mean_delta
+            .iter()
+            .map(|delta| delta.abs())
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+impl DistributionStats {
+    /// Statistics of `vectors`, all of the same dimension
+    pub fn from_vectors<V: AsRef<[f32]>>(vectors: &[V]) -> Result<Self> {
+        let mut acc = Accumulator::default();
+        for v in vectors {
+            acc.push(v.as_ref())?;
+        }
+        acc.finish()
+    }
+
+    /// Statistics of a row-major matrix with `dimension` columns, as passed
+    /// to [`CasperClient::upload_matrix`]
+    pub fn from_matrix(dimension: usize, vectors: &[f32]) -> Result<Self> {
+        if dimension == 0 || !vectors.len().is_multiple_of(dimension) {
+            return Err(CasperError::InvalidArgument(format!(
+                "matrix of {} values is not a multiple of dimension {}",
+                vectors.len(),
+                dimension
+            )));
+        }
+        Self::from_vectors(&vectors.chunks_exact(dimension).collect::<Vec<_>>())
+    }
+
+    /// How `self` drifted from `baseline`
+    pub fn compare(&self, baseline: &DistributionStats) -> Result<DriftReport> {
+        if self.dimension != baseline.dimension {
+            return Err(CasperError::InvalidDimension { expected: baseline.dimension, actual: self.dimension });
+        }
+
+        let mean_delta = vector::sub(&self.mean, &baseline.mean)?;
+        let norms = vector::norm(&self.mean) * vector::norm(&baseline.mean);
+        let variance_ratio = self
+            .variance
+            .iter()
+            .zip(&baseline.variance)
+            .map(|(current, base)| match (*base, *current) {
+                (0.0, 0.0) => 1.0,
+                (0.0, _) => f32::INFINITY,
+                (base, current) => current / base,
+            })
+            .collect();
+
+        Ok(DriftReport {
+            centroid_shift: vector::norm(&mean_delta),
+            centroid_cosine: if norms == 0.0 { 0.0 } else { vector::dot(&self.mean, &baseline.mean) / norms },
+            mean_norm_delta: self.mean_norm - baseline.mean_norm,
+            mean_delta,
+            variance_ratio,
+        })
+    }
+}
+
+/// Running mean and variance (Welford), in f64 to keep large scans stable
+#[derive(Default)]
+struct Accumulator {
+    count: usize,
+    norm_sum: f64,
+    mean: Vec<f64>,
+    m2: Vec<f64>,
+}
+
+impl Accumulator {
+    fn push(&mut self, v: &[f32]) -> Result<()> {
+        if self.count == 0 {
+            self.mean = vec![0.0; v.len()];
+            self.m2 = vec![0.0; v.len()];
+        } else if v.len() != self.mean.len() {
+            return Err(CasperError::InvalidDimension { expected: self.mean.len(), actual: v.len() });
+        }
+
+        self.count += 1;
+        self.norm_sum += vector::norm(v) as f64;
+        let n = self.count as f64;
+        for ((mean, m2), &x) in self.mean.iter_mut().zip(&mut self.m2).zip(v) {
+            let delta = x as f64 - *mean;
+            *mean += delta / n;
+            *m2 += delta * (x as f64 - *mean);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<DistributionStats> {
+        if self.count == 0 {
+            return Err(CasperError::InvalidArgument("no vectors to compute statistics over".to_string()));
+        }
+        let n = self.count as f64;
+        Ok(DistributionStats {
+            count: self.count,
+            dimension: self.mean.len(),
+            mean_norm: (self.norm_sum / n) as f32,
+            mean: self.mean.iter().map(|&m| m as f32).collect(),
+            variance: self.m2.iter().map(|&m2| (m2 / n) as f32).collect(),
+        })
+    }
+}
+
+impl CasperClient {
+    /// Distribution statistics of every vector stored in a collection.
+    ///
+    /// Vectors are fetched one by one (see [`CasperClient::find_duplicates`]
+    /// for the cost) and folded into running statistics, so memory use does
+    /// not grow with the collection.
+    pub async fn collection_stats(&self, collection_name: &str) -> Result<DistributionStats> {
+        let mut acc = Accumulator::default();
+        let mut error = None;
+        self.for_each_stored(collection_name, |_, v| {
+            if let Err(e) = acc.push(&v) {
+                error.get_or_insert(e);
+            }
+        })
+        .await?;
+
+        match error {
+            Some(e) => Err(e),
+            None => acc.finish(),
+        }
+    }
+
+    /// How a collection drifted from `baseline`, e.g. statistics of the
+    /// reference matrix or an earlier [`CasperClient::collection_stats`]
+    pub async fn collection_drift(&self, collection_name: &str, baseline: &DistributionStats) -> Result<DriftReport> {
+        self.collection_stats(collection_name).await?.compare(baseline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_and_drift() {
+        let baseline = DistributionStats::from_matrix(2, &[1.0, 0.0, 3.0, 0.0]).unwrap();
+        assert_eq!(baseline.count, 2);
+        assert_eq!(baseline.mean, vec![2.0, 0.0]);
+        assert_eq!(baseline.variance, vec![1.0, 0.0]);
+        assert_eq!(baseline.mean_norm, 2.0);
+
+        let current = DistributionStats::from_vectors(&[vec![2.0, 1.0], vec![2.0, 3.0]]).unwrap();
+        let report = current.compare(&baseline).unwrap();
+        assert_eq!(report.mean_delta, vec![0.0, 2.0]);
+        assert_eq!(report.centroid_shift, 2.0);
+        assert_eq!(report.variance_ratio, vec![0.0, f32::INFINITY]);
+        assert_eq!(report.max_mean_shift(), Some((1, 2.0)));
+
+        assert!(DistributionStats::from_matrix(3, &[1.0, 2.0]).is_err());
+        assert!(current.compare(&DistributionStats::from_vectors(&[vec![1.0]]).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{BatchInsertOperation, BatchResult, BatchUpdateRequest, InsertRequest};
+
+/// Reason reported for inserts refused by [`DuplicatePolicy::Error`]
+const DUPLICATE_REASON: &str = "ID already exists";
+
+/// What an insert does when its ID is already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Refuse the insert
+    #[default]
+    Error,
+    /// Replace the stored vector and payload, as an upsert
+    Overwrite,
+    /// Keep the stored vector and drop the insert
+    Skip,
+}
+
+impl CasperClient {
+    /// Insert a vector, handling an existing ID as `policy` says; returns
+    /// `false` when the insert was skipped.
+    ///
+    /// [`DuplicatePolicy::Overwrite`] is an [upsert](CasperClient::upsert_vector).
+    /// The other policies check the ID with [`CasperClient::contains_ids`]
+    /// first, which is not atomic: an ID inserted concurrently by another
+    /// writer is handled as the server handles plain inserts.
+    pub async fn insert_vector_with(
+        &self,
+        collection_name: &str,
+        request: InsertRequest,
+        policy: DuplicatePolicy,
+    ) -> Result<bool> {
+        if policy == DuplicatePolicy::Overwrite {
+            self.upsert_vector(collection_name, request).await?;
+            return Ok(true);
+        }
+        if self.contains_ids(collection_name, &[request.id]).await?[0] {
+            return match policy {
+                DuplicatePolicy::Skip => Ok(false),
+                _ => Err(CasperError::ItemRejected { id: request.id, reason: DUPLICATE_REASON.to_string() }),
+            };
+        }
+        self.insert_vector(collection_name, request).await?;
+        Ok(true)
+    }
+
+    /// Insert vectors in one batch update, handling existing IDs as `policy`
+    /// says, see [`CasperClient::insert_vector_with`].
+    ///
+    /// Existing IDs are listed in [`BatchResult::failed`] under
+    /// [`DuplicatePolicy::Error`] and in [`BatchResult::skipped`] under
+    /// [`DuplicatePolicy::Skip`]; the other vectors are inserted either way.
+    pub async fn batch_insert(
+        &self,
+        collection_name: &str,
+        inserts: Vec<BatchInsertOperation>,
+        policy: DuplicatePolicy,
+    ) -> Result<BatchResult> {
+        if policy == DuplicatePolicy::Overwrite {
+            let request = BatchUpdateRequest { insert: inserts, delete: Vec::new(), upsert: true };
+            return self.batch_update_results(collection_name, request).await;
+        }
+
+        let ids: Vec<u32> = inserts.iter().map(|op| op.id).collect();
+        let exists = self.contains_ids(collection_name, &ids).await?;
+        let mut result = BatchResult::default();
+        let mut insert = Vec::with_capacity(inserts.len());
+        for (op, exists) in inserts.into_iter().zip(exists) {
+            match (exists, policy) {
+                (false, _) => insert.push(op),
+                (true, DuplicatePolicy::Skip) => result.skipped.push(op.id),
+                (true, _) => result.failed.push((op.id, DUPLICATE_REASON.to_string())),
+            }
+        }
+        if !insert.is_empty() {
+            let request = BatchUpdateRequest { insert, delete: Vec::new(), upsert: false };
+            result.merge(self.batch_update_results(collection_name, request).await?);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_duplicate_policies() {
+        let scripted = Scripted::new([
+            (200, r#"{"exists":[true,false]}"#),
+            (200, ""),
+            (200, r#"{"exists":[true]}"#),
+            (200, r#"{"api_version":"1.2"}"#),
+            (200, ""),
+        ]);
+        let client = scripted.client();
+        let op = |id| BatchInsertOperation { id, vector: vec![0.5, 1.0], payload: None };
+
+        let result = client.batch_insert("docs", vec![op(1), op(2)], DuplicatePolicy::Skip).await.unwrap();
+        assert_eq!((result.skipped, result.succeeded), (vec![1], vec![2]));
+
+        let request = InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None };
+        let refused = client.insert_vector_with("docs", request.clone(), DuplicatePolicy::Error).await;
+        assert!(matches!(refused, Err(CasperError::ItemRejected { id: 1, .. })));
+        assert!(client.insert_vector_with("docs", request, DuplicatePolicy::Overwrite).await.unwrap());
+        assert_eq!(scripted.last_query("upsert").as_deref(), Some("true"));
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+use serde_json::ser::Formatter;
+use std::io;
+
+/// How floats are written in JSON request bodies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Shortest representation that round-trips exactly (serde_json default)
+    #[default]
+    Shortest,
+    /// Round to this many significant digits, written in plain or scientific
+    /// notation, whichever is shorter
+    Significant(u8),
+    /// Round to this many significant digits, always in scientific notation
+    Scientific(u8),
+}
+
+/// Encoding requested for search responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchEncoding {
+    /// Compact binary `(id, score)` records
+    #[default]
+    Binary,
+    /// JSON, for servers configured to answer searches in JSON only
+    Json,
+}
+
+impl SearchEncoding {
+    /// Value of the `output` query parameter
+    pub(crate) fn output(self) -> &'static str {
+        match self {
+            SearchEncoding::Binary => "bin",
+            SearchEncoding::Json => "json",
+        }
+    }
+
+    /// Value of the `Accept` header
+    pub(crate) fn accept(self) -> &'static str {
+        match self {
+            SearchEncoding::Binary => "application/octet-stream, application/json;q=0.5",
+            SearchEncoding::Json => "application/json",
+        }
+    }
+}
+
+impl FloatFormat {
+    /// Format a finite value
+    fn format(self, value: f64) -> Option<String> {
+        let digits = match self {
+            FloatFormat::Shortest => return None,
+            FloatFormat::Significant(d) | FloatFormat::Scientific(d) => d.clamp(1, 17) as usize,
+        };
+
+        let scientific = trim_scientific(&format!("{:.*e}", digits - 1, value));
+        if matches!(self, FloatFormat::Scientific(_)) {
+            return Some(scientific);
+        }
+
+        // Re-parsing the rounded value gives a float whose shortest plain
+        // representation carries exactly the kept digits.
+        let plain = scientific.parse::<f64>().map(|v| v.to_string()).unwrap_or_default();
+        Some(if !plain.is_empty() && plain.len() <= scientific.len() {
+            plain
+        } else {
+            scientific
+        })
+    }
+}
+
+/// Drop trailing zeros of the mantissa: `1.50000e-3` -> `1.5e-3`
+fn trim_scientific(s: &str) -> String {
+    match s.split_once('e') {
+        Some((mantissa, exponent)) if mantissa.contains('.') => {
+            let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+            if exponent == "0" {
+                mantissa.to_string()
+            } else {
+                format!("{}e{}", mantissa, exponent)
+            }
+        }
+        Some((mantissa, "0")) => mantissa.to_string(),
+        _ => s.to_string(),
+    }
+}
+
+/// serde_json formatter applying a [`FloatFormat`] to every float
+struct PrecisionFormatter(FloatFormat);
+
+impl PrecisionFormatter {
+    fn write_float<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f64) -> io::Result<Option<()>> {
+        if !value.is_finite() {
+            return writer.write_all(b"null").map(Some);
+        }
+        match self.0.format(value) {
+            Some(s) => writer.write_all(s.as_bytes()).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Formatter for PrecisionFormatter {
+    fn write_f32<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        match self.write_float(writer, value as f64)? {
+            Some(()) => Ok(()),
+            None => serde_json::ser::CompactFormatter.write_f32(writer, value),
+        }
+    }
+
+    fn write_f64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        match self.write_float(writer, value)? {
+            Some(()) => Ok(()),
+            None => serde_json::ser::CompactFormatter.write_f64(writer, value),
+        }
+    }
+}
+
+/// Serialize `value` as JSON, writing floats according to `format`
+pub(crate) fn to_json_vec<T: Serialize + ?Sized>(value: &T, format: FloatFormat) -> serde_json::Result<Vec<u8>> {
+    if format == FloatFormat::Shortest {
+        return serde_json::to_vec(value);
+    }
+
+    let mut buf = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buf, PrecisionFormatter(format));
+    value.serialize(&mut serializer)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced_precision() {
+        let values = vec![0.1234567f32, -1.0, 1.5e-7, 12345.678];
+
+        let json = to_json_vec(&values, FloatFormat::Significant(4)).unwrap();
+        assert_eq!(String::from_utf8(json).unwrap(), "[0.1235,-1,1.5e-7,12350]");
+
+        let json = to_json_vec(&values, FloatFormat::Scientific(3)).unwrap();
+        assert_eq!(String::from_utf8(json).unwrap(), "[1.23e-1,-1,1.5e-7,1.23e4]");
+
+        let json = to_json_vec(&values, FloatFormat::Shortest).unwrap();
+        assert_eq!(json, serde_json::to_vec(&values).unwrap());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::{CasperError, Result};
+use url::Url;
+
+/// Default port of the gRPC matrix service
+pub const DEFAULT_GRPC_PORT: u16 = 50051;
+
+/// Where a Casper server listens: the HTTP API and the gRPC matrix service.
+///
+/// Both construction styles end up here:
+///
+/// ```
+/// use casper_client::Endpoints;
+///
+/// // Host plus ports
+/// let a = Endpoints::from_host("http://localhost", 8080, 50051);
+/// // Full URLs
+/// let b = Endpoints::new("http://localhost:8080", "http://localhost:50051");
+/// // HTTP base URL only, gRPC on the same host at the default port
+/// let c = Endpoints::from_url("http://localhost:8080").unwrap();
+/// assert_eq!(a, b);
+/// assert_eq!(b, c);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// HTTP API base URL, e.g. `http://localhost:8080`
+    pub http: String,
+    /// gRPC address, e.g. `http://localhost:50051`
+    pub grpc: String,
+}
+
+impl Endpoints {
+    /// Endpoints from full URLs
+    pub fn new(http: &str, grpc: &str) -> Self {
+        Self { http: http.trim_end_matches('/').to_string(), grpc: grpc.trim_end_matches('/').to_string() }
+    }
+
+    /// Endpoints of `host` (e.g. `"http://127.0.0.1"`) at the given ports
+    pub fn from_host(host: &str, http_port: u16, grpc_port: u16) -> Self {
+        let host = host.trim_end_matches('/');
+        Self { http: format!("{}:{}", host, http_port), grpc: format!("{}:{}", host, grpc_port) }
+    }
+
+    /// Endpoints from the HTTP base URL, with the gRPC service on the same
+    /// host at [`DEFAULT_GRPC_PORT`]
+    pub fn from_url(http: &str) -> Result<Self> {
+        let mut grpc = Url::parse(http)?;
+        grpc.set_port(Some(DEFAULT_GRPC_PORT))
+            .map_err(|_| CasperError::Url(url::ParseError::EmptyHost))?;
+        grpc.set_path("");
+        grpc.set_query(None);
+        Ok(Self::new(http, grpc.as_str()))
+    }
+
+    /// Parsed HTTP base URL
+    pub(crate) fn http_url(&self) -> Result<Url> {
+        Ok(Url::parse(&self.http)?)
+    }
+}
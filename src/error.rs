@@ -1,3 +1,4 @@
+use crate::version::ApiVersion;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, CasperError>;
@@ -10,6 +11,9 @@ pub enum CasperError {
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
     
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    
     #[error("URL parsing error: {0}")]
     Url(#[from] url::ParseError),
     
@@ -21,10 +25,19 @@ pub enum CasperError {
     
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Invalid export: {0}")]
+    InvalidExport(String),
     
     #[error("Collection not found: {0}")]
     CollectionNotFound(String),
     
+    #[error("Vector not found: {0}")]
+    VectorNotFound(u32),
+    
     #[error("Index creation in progress")]
     IndexCreationInProgress,
     
@@ -43,11 +56,79 @@ pub enum CasperError {
     #[error("Collection is not mutable")]
     CollectionNotMutable,
     
+    #[error("Operation on ID {id} rejected: {reason}")]
+    ItemRejected { id: u32, reason: String },
+    
     #[error("Index already exists")]
     IndexAlreadyExists,
     
-    #[error("gRPC error: {0}")]
-    Grpc(String),
+    #[error("gRPC connection failed: {0}")]
+    GrpcConnection(String),
+
+    #[error("gRPC endpoint {addr} unavailable: {message}; {remediation}")]
+    GrpcUnavailable {
+        addr: String,
+        message: String,
+        /// What to check or configure to get uploads through
+        remediation: &'static str,
+    },
+
+    #[error("Request of {size} bytes exceeds the server limit of {limit} bytes; {remediation}")]
+    RequestTooLarge {
+        size: usize,
+        limit: usize,
+        /// How to split the request
+        remediation: &'static str,
+    },
+
+    #[error("Matrix upload header rejected: {code} - {message}")]
+    UploadHeaderRejected { code: tonic::Code, message: String },
+
+    #[error("Matrix upload chunk {chunk_index} rejected: {code} - {message}")]
+    UploadChunkRejected {
+        chunk_index: u32,
+        code: tonic::Code,
+        message: String,
+    },
+
+    #[error("Matrix upload validation failed: {code} - {message}")]
+    UploadValidation { code: tonic::Code, message: String },
+
+    #[error("Matrix upload aborted: {0}")]
+    UploadAborted(String),
+
+    #[error("Matrix {name} mismatch on {field}: expected {expected}, got {actual}")]
+    MatrixMismatch {
+        name: String,
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("{feature} requires server API {required}, only {available} is available")]
+    ApiVersionUnsupported {
+        feature: &'static str,
+        required: ApiVersion,
+        available: ApiVersion,
+    },
+
+    #[error("Incompatible API version: client speaks {client}, server {server}")]
+    IncompatibleApiVersion { client: ApiVersion, server: ApiVersion },
+
+    #[error("Operation deadline of {0:?} exceeded")]
+    DeadlineExceeded(std::time::Duration),
+
+    #[error("Job {id} did not succeed: {message}")]
+    JobFailed { id: String, message: String },
+
+    #[error("Preflight {check} check failed: {message}")]
+    PreflightFailed { check: &'static str, message: String },
+
+    #[error("HTTP middleware error: {0}")]
+    Middleware(crate::service::BoxError),
+
+    #[error("gRPC error: {code} - {message}")]
+    Grpc { code: tonic::Code, message: String },
     
     #[error("Unknown error: {0}")]
     Unknown(String),
@@ -55,6 +136,9 @@ pub enum CasperError {
 
 impl CasperError {
     pub fn from_status(status: u16, message: String) -> Self {
+        if status == 423 || message.to_ascii_lowercase().contains("index creation in progress") {
+            return CasperError::IndexCreationInProgress;
+        }
         match status {
             400 => CasperError::Client { status, message },
             404 => CasperError::CollectionNotFound(message),
@@ -64,4 +148,79 @@ impl CasperError {
             _ => CasperError::Unknown(format!("HTTP {}: {}", status, message)),
         }
     }
+
+    /// Whether the error means the server could not be reached at all
+    /// (connection failure, timeout, or a gateway reporting it unavailable),
+    /// as opposed to the server rejecting the request.
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            CasperError::Http(e) => e.is_connect() || e.is_timeout(),
+            CasperError::Io(e) => e.kind() == std::io::ErrorKind::TimedOut || self.is_connect_failure(),
+            CasperError::Server { status, .. } => matches!(status, 502..=504),
+            CasperError::GrpcConnection(_) | CasperError::GrpcUnavailable { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether no connection to the server could be made, so the request
+    /// certainly was not applied. Unlike [`CasperError::is_unreachable`],
+    /// timeouts and gateway errors do not count: the server may have acted on
+    /// the request before the answer was lost.
+    ///
+    /// I/O errors of a custom [`HttpTransport`](crate::HttpTransport) count
+    /// when their kind says the connection was refused or the host could not
+    /// be reached.
+    pub fn is_connect_failure(&self) -> bool {
+        use std::io::ErrorKind;
+        match self {
+            CasperError::Http(e) => e.is_connect(),
+            CasperError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::NotConnected
+                    | ErrorKind::AddrNotAvailable
+                    | ErrorKind::HostUnreachable
+                    | ErrorKind::NetworkUnreachable
+            ),
+            _ => false,
+        }
+    }
+
+    /// Classify a gRPC status returned by a matrix upload stream.
+    ///
+    /// - `last_chunk`: index of the last data chunk handed to the transport,
+    ///   `None` if only the header had been sent
+    /// - `stream_complete`: whether every chunk had been sent before the failure
+    ///
+    /// A server may point at the offending chunk explicitly through the
+    /// `x-chunk-index` metadata entry, which takes precedence over `last_chunk`.
+    pub fn from_upload_status(
+        status: tonic::Status,
+        last_chunk: Option<u32>,
+        stream_complete: bool,
+    ) -> Self {
+        let code = status.code();
+        let message = status.message().to_string();
+
+        let reported_chunk = status
+            .metadata()
+            .get("x-chunk-index")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u32>().ok());
+
+        match code {
+            tonic::Code::Unavailable => CasperError::GrpcConnection(message),
+            tonic::Code::InvalidArgument
+            | tonic::Code::FailedPrecondition
+            | tonic::Code::OutOfRange
+            | tonic::Code::AlreadyExists => match reported_chunk.or(last_chunk) {
+                Some(chunk_index) if reported_chunk.is_some() || !stream_complete => {
+                    CasperError::UploadChunkRejected { chunk_index, code, message }
+                }
+                Some(_) => CasperError::UploadValidation { code, message },
+                None => CasperError::UploadHeaderRejected { code, message },
+            },
+            _ => CasperError::Grpc { code, message },
+        }
+    }
 }
@@ -0,0 +1,442 @@
+//! Collection backups: a directory of chunk files described by a versioned
+//! JSON manifest, written by [`CasperClient::export_collection`] and
+//! restored by [`CasperClient::import_collection`].
+
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::models::{
+    BatchInsertOperation, BatchResult, BatchUpdateRequest, CreateCollectionRequest, IndexInfo, Labels,
+};
+use crate::memory::JSON_FLOAT_BYTES;
+use crate::progress::{Phase, ProgressCounter};
+use crate::upload::{fnv1a_64, fnv1a_64_extend};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio_stream::StreamExt;
+use tokio_util::io::ReaderStream;
+
+/// Manifest format written by this crate; imports accept it and older ones
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// File name of the manifest within an export directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Vectors per chunk file, fewer under a memory budget
+const EXPORT_CHUNK_VECTORS: usize = 10_000;
+
+/// How [`CasperClient::export_collection_with`] reads the collection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportMode {
+    /// Scan the collection as it changes; writes made during the export
+    /// may be partially included
+    #[default]
+    Live,
+    /// Scan a server snapshot taken as the export starts, so backups of
+    /// collections being written are consistent. Fails with
+    /// [`CasperError::OperationNotAllowed`] on servers without snapshots.
+    PointInTime,
+}
+
+/// `POST collection/<name>/snapshot` response
+#[derive(Debug, Deserialize)]
+struct SnapshotResponse {
+    /// Sequence marker the snapshot is taken at
+    snapshot: u64,
+}
+
+/// Description of an exported collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Manifest format, see [`MANIFEST_VERSION`]
+    pub format_version: u32,
+    /// Version of the crate that wrote the export
+    pub crate_version: String,
+    pub collection: String,
+    pub dimension: usize,
+    pub max_size: u32,
+    /// Vectors across all chunk files
+    pub count: u64,
+    /// Index of the collection at export time, for reference; imports do
+    /// not rebuild it
+    pub index: Option<IndexInfo>,
+    #[serde(default)]
+    pub labels: Labels,
+    /// Server sequence marker the export is consistent as of, `None` for
+    /// [`ExportMode::Live`] exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<u64>,
+    /// Compression of the chunk files, `"zstd"` for exports of clients
+    /// with [`compression`](crate::CasperClientBuilder) enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    pub chunks: Vec<ExportChunk>,
+}
+
+/// One chunk file, a [`BatchUpdateRequest`] JSON document of inserts,
+/// compressed as the manifest says
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportChunk {
+    /// File name within the export directory
+    pub file: String,
+    pub count: u64,
+    /// 64-bit FNV-1a hash of the file, in hex
+    pub checksum: String,
+}
+
+impl ExportManifest {
+    /// Read and [`validate`](Self::validate) the manifest of the export in `dir`
+    pub async fn read(dir: impl AsRef<Path>) -> Result<Self> {
+        let bytes = tokio::fs::read(dir.as_ref().join(MANIFEST_FILE)).await?;
+        let manifest: Self = serde_json::from_slice(&bytes)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Check that this crate can restore the export
+    pub fn validate(&self) -> Result<()> {
+        if self.format_version == 0 || self.format_version > MANIFEST_VERSION {
+            return Err(CasperError::InvalidExport(format!(
+                "export manifest version {} is not supported (written by {}, this crate reads up to {})",
+                self.format_version, self.crate_version, MANIFEST_VERSION
+            )));
+        }
+        let chunked: u64 = self.chunks.iter().map(|chunk| chunk.count).sum();
+        if chunked != self.count {
+            return Err(CasperError::InvalidExport(format!(
+                "export manifest counts {} vectors but its chunks hold {}",
+                self.count, chunked
+            )));
+        }
+        match self.compression.as_deref() {
+            None => {}
+            Some("zstd") if cfg!(feature = "zstd") => {}
+            Some(other) => {
+                return Err(CasperError::InvalidExport(format!(
+                    "export is compressed with '{}', which this build cannot read (zstd needs the `zstd` feature)",
+                    other
+                )));
+            }
+        }
+        // Chunk files must stay inside the export directory
+        let nested = self.chunks.iter().find(|chunk| Path::new(&chunk.file).file_name() != Some(chunk.file.as_ref()));
+        if let Some(chunk) = nested {
+            return Err(CasperError::InvalidExport(format!(
+                "export chunk file '{}' is not a plain file name",
+                chunk.file
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write `bytes`, the encoded chunk of `count` vectors, as the next chunk
+    /// file in `dir`
+    async fn write_chunk(
+        &mut self,
+        dir: &Path,
+        count: u64,
+        bytes: Vec<u8>,
+        progress: Option<&ProgressCounter>,
+    ) -> Result<()> {
+        let file = match &self.compression {
+            Some(_) => format!("chunk-{:05}.json.zst", self.chunks.len()),
+            None => format!("chunk-{:05}.json", self.chunks.len()),
+        };
+        tokio::fs::write(dir.join(&file), &bytes).await?;
+
+        self.count += count;
+        self.chunks.push(ExportChunk { file, count, checksum: checksum(&bytes) });
+        if let Some(progress) = progress {
+            progress.advance(count, bytes.len() as u64);
+        }
+        Ok(())
+    }
+}
+
+/// Checksum of a chunk file as recorded in the manifest
+fn checksum(bytes: &[u8]) -> String {
+    format!("{:016x}", fnv1a_64(bytes.iter().copied()))
+}
+
+/// Checksum of the file at `path`, read a buffer at a time
+async fn file_checksum(path: &Path) -> Result<String> {
+    let mut hash = fnv1a_64([]);
+    let mut reader = ReaderStream::new(tokio::fs::File::open(path).await?);
+    while let Some(bytes) = reader.next().await {
+        hash = fnv1a_64_extend(hash, bytes?);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+impl CasperClient {
+    /// Export every vector of a collection into `dir`, as chunk files of
+    /// 10000 vectors (fewer under the client's
+    /// [`memory_budget`](crate::CasperClientBuilder::memory_budget)) and a
+    /// [`MANIFEST_FILE`] describing them.
+    ///
+    /// Vectors and their payloads are read with
+    /// [`CasperClient::scan_records`]. The manifest is written last, so an
+    /// interrupted export cannot be imported.
+    pub async fn export_collection(&self, collection_name: &str, dir: impl AsRef<Path>) -> Result<ExportManifest> {
+        self.export_collection_with(collection_name, dir, ExportMode::Live).await
+    }
+
+    /// [`CasperClient::export_collection`] reading the collection as `mode`
+    /// says.
+    ///
+    /// A [`ExportMode::PointInTime`] snapshot is released once the vectors
+    /// are read, and recorded in [`ExportManifest::snapshot`].
+    pub async fn export_collection_with(
+        &self,
+        collection_name: &str,
+        dir: impl AsRef<Path>,
+        mode: ExportMode,
+    ) -> Result<ExportManifest> {
+        let dir = dir.as_ref();
+        let info = self.get_collection(collection_name).await?;
+        tokio::fs::create_dir_all(dir).await?;
+        let snapshot = match mode {
+            ExportMode::Live => None,
+            ExportMode::PointInTime => Some(self.create_snapshot(collection_name).await?),
+        };
+
+        let mut manifest = ExportManifest {
+            format_version: MANIFEST_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            collection: collection_name.to_string(),
+            dimension: info.dimension,
+            max_size: info.max_size,
+            count: 0,
+            index: info.index,
+            labels: info.labels,
+            snapshot,
+            compression: self.export_compression(),
+            chunks: Vec::new(),
+        };
+        let progress = ProgressCounter::new(
+            self.progress.as_ref(),
+            "export_collection",
+            collection_name,
+            Phase::Exporting,
+            Some(info.size as u64),
+        );
+
+        let written = self.write_export_chunks(&mut manifest, dir, progress.as_deref()).await;
+        if let Some(snapshot) = snapshot {
+            self.release_snapshot(collection_name, snapshot).await;
+        }
+        written?;
+
+        tokio::fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?).await?;
+        Ok(manifest)
+    }
+
+    /// Scan the collection of `manifest` as of its snapshot into chunk files
+    async fn write_export_chunks(
+        &self,
+        manifest: &mut ExportManifest,
+        dir: &Path,
+        progress: Option<&ProgressCounter>,
+    ) -> Result<()> {
+        let collection_name = manifest.collection.clone();
+        // A chunk is held both as operations and as the encoded file
+        let chunk_vectors = self.fit_budget(EXPORT_CHUNK_VECTORS, manifest.dimension * JSON_FLOAT_BYTES, 2);
+        let mut records = self.scan_snapshot(&collection_name, manifest.snapshot);
+        let mut insert = Vec::with_capacity(chunk_vectors);
+        while let Some(record) = records.next().await {
+            let record = record?;
+            insert.push(BatchInsertOperation { id: record.id, vector: record.vector, payload: record.payload });
+            if insert.len() == chunk_vectors {
+                let count = insert.len() as u64;
+                let bytes = self.encode_export_chunk(std::mem::take(&mut insert))?;
+                manifest.write_chunk(dir, count, bytes, progress).await?;
+            }
+        }
+        if !insert.is_empty() {
+            let count = insert.len() as u64;
+            let bytes = self.encode_export_chunk(insert)?;
+            manifest.write_chunk(dir, count, bytes, progress).await?;
+        }
+        Ok(())
+    }
+
+    /// Compression this client writes export chunks with
+    fn export_compression(&self) -> Option<String> {
+        #[cfg(feature = "zstd")]
+        if self.compression.is_some() {
+            return Some(crate::compression::ZSTD_ENCODING.to_string());
+        }
+        None
+    }
+
+    /// Contents of the chunk file holding `insert`
+    fn encode_export_chunk(&self, insert: Vec<BatchInsertOperation>) -> Result<Vec<u8>> {
+        // Plain serde_json keeps floats exact whatever the client's float format
+        let bytes = serde_json::to_vec(&BatchUpdateRequest { insert, delete: Vec::new(), upsert: false })?;
+        #[cfg(feature = "zstd")]
+        if let Some(compressor) = &self.compression {
+            return compressor.compress(&bytes);
+        }
+        Ok(bytes)
+    }
+
+    /// Ask the server for a snapshot of the collection to scan
+    async fn create_snapshot(&self, collection_name: &str) -> Result<u64> {
+        let url = self.collection_url(collection_name, "/snapshot")?;
+        let response = self.http(Scope::Read, Method::POST, url).dispatch(self).await?;
+        if matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "point-in-time export of '{}': the server does not support snapshots",
+                collection_name
+            )));
+        }
+        let response: SnapshotResponse = self.handle_response(response).await?;
+        Ok(response.snapshot)
+    }
+
+    /// Release a snapshot; failures are only logged, as the server expires
+    /// snapshots on its own
+    async fn release_snapshot(&self, collection_name: &str, snapshot: u64) {
+        let released = async {
+            let url = self.collection_url(collection_name, &format!("/snapshot/{}", snapshot))?;
+            let response = self.http(Scope::Read, Method::DELETE, url).dispatch(self).await?;
+            self.handle_text_response(response).await
+        };
+        if let Err(e) = released.await {
+            tracing::warn!(collection = collection_name, snapshot, error = %e, "failed to release export snapshot");
+        }
+    }
+
+    /// Restore the export in `dir` into `collection_name`, creating the
+    /// collection with the exported dimension, size and labels if missing.
+    ///
+    /// The manifest is validated first and every chunk is checked against
+    /// its checksum before being sent. Chunks are streamed from their files
+    /// with [`CasperClient::batch_update_from_reader`], so the result lists
+    /// the IDs the server reported on; compressed chunks are decompressed in
+    /// memory first. A failure stops the import with the earlier chunks
+    /// applied.
+    pub async fn import_collection(&self, dir: impl AsRef<Path>, collection_name: &str) -> Result<BatchResult> {
+        let dir = dir.as_ref();
+        let manifest = ExportManifest::read(dir).await?;
+        match self.get_collection(collection_name).await {
+            Ok(info) if info.dimension != manifest.dimension => {
+                return Err(CasperError::InvalidDimension { expected: info.dimension, actual: manifest.dimension });
+            }
+            Ok(_) => {}
+            Err(CasperError::CollectionNotFound(_)) => {
+                let request = CreateCollectionRequest {
+                    dim: manifest.dimension,
+                    max_size: manifest.max_size,
+                    labels: manifest.labels.clone(),
+                };
+                self.create_collection(collection_name, request).await?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        let progress = ProgressCounter::new(
+            self.progress.as_ref(),
+            "import_collection",
+            collection_name,
+            Phase::Importing,
+            Some(manifest.count),
+        );
+        let mut result = BatchResult::default();
+        for chunk in &manifest.chunks {
+            let path = dir.join(&chunk.file);
+            let actual = file_checksum(&path).await?;
+            if actual != chunk.checksum {
+                return Err(CasperError::InvalidExport(format!(
+                    "export chunk {} is corrupt: checksum {} does not match the manifest's {}",
+                    chunk.file, actual, chunk.checksum
+                )));
+            }
+
+            let len = tokio::fs::metadata(&path).await?.len();
+            let applied = match manifest.compression.as_deref() {
+                #[cfg(feature = "zstd")]
+                Some(_) => {
+                    let bytes = crate::compression::decompress(&tokio::fs::read(&path).await?)?;
+                    self.batch_update_from_reader(collection_name, std::io::Cursor::new(bytes)).await?
+                }
+                _ => self.batch_update_from_reader(collection_name, tokio::fs::File::open(&path).await?).await?,
+            };
+            result.merge(applied);
+            if let Some(progress) = &progress {
+                progress.advance(chunk.count, len);
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    const DOCS: &str = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":2,"index":null}"#;
+
+    #[tokio::test]
+    async fn test_export_and_import() {
+        let dir = std::env::temp_dir().join(format!("casper-export-{}", std::process::id()));
+        let scripted = Scripted::new([
+            (200, DOCS),
+            (200, r#"{"vectors":[{"id":1,"vector":[0.1,0.2],"payload":{"tag":"a"}},{"id":2,"vector":[0.3,0.4]}]}"#),
+            (404, "collection not found"),
+            (200, ""),
+            (200, ""),
+        ]);
+        let client = scripted.client();
+
+        let manifest = client.export_collection("docs", &dir).await.unwrap();
+        assert_eq!((manifest.count, manifest.chunks.len()), (2, 1));
+        assert_eq!(ExportManifest::read(&dir).await.unwrap().chunks, manifest.chunks);
+        let chunk: BatchUpdateRequest =
+            serde_json::from_slice(&tokio::fs::read(dir.join("chunk-00000.json")).await.unwrap()).unwrap();
+        assert_eq!(chunk.insert[0].payload, Some(serde_json::json!({"tag": "a"})));
+
+        client.import_collection(&dir, "restored").await.unwrap();
+        assert_eq!(
+            scripted.requests()[2..],
+            ["GET /collection/restored", "POST /collection/restored", "POST /collection/restored/update"]
+        );
+
+        // A tampered chunk is refused before anything is sent
+        tokio::fs::write(dir.join("chunk-00000.json"), br#"{"insert":[],"delete":[]}"#).await.unwrap();
+        let scripted = Scripted::new([(200, DOCS)]);
+        assert!(scripted.client().import_collection(&dir, "docs").await.is_err());
+        assert_eq!(scripted.requests(), ["GET /collection/docs"]);
+
+        let newer = ExportManifest { format_version: MANIFEST_VERSION + 1, ..manifest };
+        assert!(matches!(newer.validate(), Err(CasperError::InvalidExport(_))));
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_point_in_time_export() {
+        let dir = std::env::temp_dir().join(format!("casper-snapshot-export-{}", std::process::id()));
+        let scripted = Scripted::new([
+            (200, DOCS),
+            (200, r#"{"snapshot":17}"#),
+            (200, r#"{"vectors":[{"id":1,"vector":[0.1,0.2]}]}"#),
+            (200, ""),
+            (200, DOCS),
+            (501, "not implemented"),
+        ]);
+        let client = scripted.client();
+
+        let manifest = client.export_collection_with("docs", &dir, ExportMode::PointInTime).await.unwrap();
+        assert_eq!((manifest.snapshot, manifest.count), (Some(17), 1));
+        assert_eq!(
+            scripted.requests()[1..],
+            ["POST /collection/docs/snapshot", "GET /collection/docs/scan", "DELETE /collection/docs/snapshot/17"]
+        );
+
+        let unsupported = client.export_collection_with("docs", &dir, ExportMode::PointInTime).await;
+        assert!(matches!(unsupported, Err(CasperError::OperationNotAllowed(_))));
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
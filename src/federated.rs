@@ -0,0 +1,80 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::models::{CollectionSearchResult, SearchRequest};
+use crate::parallel;
+use crate::scores::ScoreScale;
+
+/// Collections searched at once by [`CasperClient::search_multi`]
+const SEARCH_PARALLELISM: usize = 16;
+
+impl CasperClient {
+    /// Run one search on several collections concurrently, e.g. a tenant
+    /// sharded across collections, returning the best `limit` results of
+    /// all of them.
+    ///
+    /// `limit` replaces the request's own [`SearchRequest::limit`]. Results
+    /// are ranked by [`ScoreScale::Similarity`] under each collection's
+    /// index metric, so collections of different metrics merge correctly,
+    /// and reported in the client's score scale; ties keep the order of
+    /// `collections`. The collections need an index. Fails on the first
+    /// failed search.
+    pub async fn search_multi(
+        &self,
+        collections: &[&str],
+        request: SearchRequest,
+        limit: usize,
+    ) -> Result<Vec<CollectionSearchResult>> {
+        let request = SearchRequest { limit: Some(limit), ..request };
+        // Scores are rescaled here, by the metric fetched alongside
+        let raw = CasperClient { score_scale: ScoreScale::Raw, ..self.clone() };
+        let searches = parallel::fan_out(collections.iter().map(|name| name.to_string()), SEARCH_PARALLELISM, |name| {
+            let client = raw.clone();
+            let request = request.clone();
+            async move {
+                let metric = client.collection_metric(&name).await?;
+                let results = client.query(&name, request).await?;
+                Ok((name, metric, results))
+            }
+        });
+        let per_collection: Vec<_> = searches.collect().await?.into_iter().collect::<Result<_>>()?;
+
+        let mut ranked = Vec::new();
+        for (collection, metric, results) in per_collection {
+            for result in results {
+                let similarity = ScoreScale::Similarity.convert(&metric, result.score)?;
+                let score = self.score_scale.convert(&metric, result.score)?;
+                let result = CollectionSearchResult { collection: collection.clone(), id: result.id, score };
+                ranked.push((similarity, result));
+            }
+        }
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(ranked.into_iter().take(limit).map(|(_, result)| result).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_search_multi() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"tenant-a","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"cosine","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"[[1,0.9],[2,0.5]]"#),
+            (200, r#"{"name":"tenant-b","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"[[1,0.0],[3,3.0]]"#),
+        ]);
+        let request = SearchRequest { vector: vec![1.0, 0.0], ..Default::default() };
+        let results = scripted.client().search_multi(&["tenant-a", "tenant-b"], request, 3).await.unwrap();
+
+        // An L2 distance of 0 beats a cosine of 0.9, raw scores stay raw
+        let ranked: Vec<(&str, u32, f32)> = results.iter().map(|r| (r.collection.as_str(), r.id, r.score)).collect();
+        assert_eq!(ranked, [("tenant-b", 1, 0.0), ("tenant-a", 1, 0.9), ("tenant-a", 2, 0.5)]);
+        assert_eq!(scripted.requests().len(), 4);
+    }
+}
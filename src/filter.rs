@@ -0,0 +1,309 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ops::{Bound, Range, RangeBounds};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds since the unix epoch (negative before it)
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_secs()).unwrap_or(i64::MAX),
+        Err(before) => i64::try_from(before.duration().as_secs()).map_or(i64::MIN, |secs| -secs),
+    }
+}
+
+/// Comparison operator of a filter condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Exists,
+}
+
+/// A single `field <op> value` condition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    pub field: String,
+    pub op: FilterOp,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub value: Value,
+}
+
+/// Boolean filter expression over payload fields.
+///
+/// Serializes to the server's filter JSON:
+/// conditions as `{"field", "op", "value"}`, combinators as `{"and": [..]}`,
+/// `{"or": [..]}` and `{"not": {..}}`.
+///
+/// ```
+/// use casper_client::Filter;
+///
+/// let filter = Filter::field("category").eq("news")
+///     .and(Filter::field("ts").gte(123));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "FilterRepr", from = "FilterRepr")]
+pub enum Filter {
+    Condition(Condition),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+/// Wire representation of [`Filter`]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FilterRepr {
+    And { and: Vec<Filter> },
+    Or { or: Vec<Filter> },
+    Not { not: Box<Filter> },
+    Condition(Condition),
+}
+
+impl From<Filter> for FilterRepr {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Condition(c) => FilterRepr::Condition(c),
+            Filter::And(and) => FilterRepr::And { and },
+            Filter::Or(or) => FilterRepr::Or { or },
+            Filter::Not(not) => FilterRepr::Not { not },
+        }
+    }
+}
+
+impl From<FilterRepr> for Filter {
+    fn from(repr: FilterRepr) -> Self {
+        match repr {
+            FilterRepr::Condition(c) => Filter::Condition(c),
+            FilterRepr::And { and } => Filter::And(and),
+            FilterRepr::Or { or } => Filter::Or(or),
+            FilterRepr::Not { not } => Filter::Not(not),
+        }
+    }
+}
+
+/// A payload field to build conditions on, see [`Filter::field`]
+#[derive(Debug, Clone)]
+pub struct FieldRef {
+    name: String,
+}
+
+impl FieldRef {
+    fn condition(self, op: FilterOp, value: Value) -> Filter {
+        Filter::Condition(Condition { field: self.name, op, value })
+    }
+
+    pub fn eq(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Eq, value.into())
+    }
+
+    pub fn ne(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Ne, value.into())
+    }
+
+    pub fn gt(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Gt, value.into())
+    }
+
+    pub fn gte(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Gte, value.into())
+    }
+
+    pub fn lt(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Lt, value.into())
+    }
+
+    pub fn lte(self, value: impl Into<Value>) -> Filter {
+        self.condition(FilterOp::Lte, value.into())
+    }
+
+    /// Field equals any of `values`
+    pub fn one_of<I, V>(self, values: I) -> Filter
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        self.condition(FilterOp::In, Value::Array(values))
+    }
+
+    /// Field is present in the payload
+    pub fn exists(self) -> Filter {
+        self.condition(FilterOp::Exists, Value::Null)
+    }
+
+    /// Timestamp field (unix seconds) at or after `time`
+    pub fn after(self, time: SystemTime) -> Filter {
+        self.gte(unix_secs(time))
+    }
+
+    /// Timestamp field (unix seconds) strictly before `time`
+    pub fn before(self, time: SystemTime) -> Filter {
+        self.lt(unix_secs(time))
+    }
+
+    /// Timestamp field (unix seconds) within `range` (start inclusive)
+    pub fn within(self, range: Range<SystemTime>) -> Filter {
+        let name = self.name.clone();
+        self.after(range.start).and(Filter::field(name).before(range.end))
+    }
+
+    /// Timestamp field (unix seconds) no older than `age` from now
+    pub fn newer_than(self, age: Duration) -> Filter {
+        match SystemTime::now().checked_sub(age) {
+            Some(time) => self.after(time),
+            // Older than any representable time: every timestamp qualifies
+            None => self.gte(i64::MIN),
+        }
+    }
+}
+
+impl Filter {
+    /// Start a condition on payload field `name`
+    pub fn field(name: impl Into<String>) -> FieldRef {
+        FieldRef { name: name.into() }
+    }
+
+    /// All of `filters` must match
+    pub fn must(filters: impl IntoIterator<Item = Filter>) -> Filter {
+        Filter::And(filters.into_iter().collect())
+    }
+
+    /// At least one of `filters` must match
+    pub fn should(filters: impl IntoIterator<Item = Filter>) -> Filter {
+        Filter::Or(filters.into_iter().collect())
+    }
+
+    /// Payload field `name` within `range`, e.g. `Filter::range("price", 10..=20)`
+    /// or `Filter::range("ts", 1_700_000_000..)`; an unbounded range only
+    /// requires the field to exist
+    pub fn range<T, R>(name: impl Into<String>, range: R) -> Filter
+    where
+        T: Into<Value> + Clone,
+        R: RangeBounds<T>,
+    {
+        let name = name.into();
+        let start = match range.start_bound() {
+            Bound::Included(v) => Some(Filter::field(name.clone()).gte(v.clone())),
+            Bound::Excluded(v) => Some(Filter::field(name.clone()).gt(v.clone())),
+            Bound::Unbounded => None,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(v) => Some(Filter::field(name.clone()).lte(v.clone())),
+            Bound::Excluded(v) => Some(Filter::field(name.clone()).lt(v.clone())),
+            Bound::Unbounded => None,
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => start.and(end),
+            (Some(bound), None) | (None, Some(bound)) => bound,
+            (None, None) => Filter::field(name).exists(),
+        }
+    }
+
+    /// Both `self` and `other` must match
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::And(mut all) => {
+                all.push(other);
+                Filter::And(all)
+            }
+            first => Filter::And(vec![first, other]),
+        }
+    }
+
+    /// Either `self` or `other` must match
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Or(mut any) => {
+                any.push(other);
+                Filter::Or(any)
+            }
+            first => Filter::Or(vec![first, other]),
+        }
+    }
+
+    /// Negation of `self`
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+
+    /// Filter as a JSON value
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter_serialization() {
+        let filter = Filter::field("category")
+            .eq("news")
+            .and(Filter::field("ts").gte(123))
+            .and(Filter::field("lang").one_of(["en", "de"]).or(Filter::field("draft").exists().not()));
+
+        let expected = json!({
+            "and": [
+                {"field": "category", "op": "eq", "value": "news"},
+                {"field": "ts", "op": "gte", "value": 123},
+                {"or": [
+                    {"field": "lang", "op": "in", "value": ["en", "de"]},
+                    {"not": {"field": "draft", "op": "exists"}}
+                ]}
+            ]
+        });
+        assert_eq!(filter.to_json(), expected);
+
+        let parsed: Filter = serde_json::from_value(expected).unwrap();
+        assert_eq!(parsed, filter);
+    }
+
+    #[test]
+    fn test_must_should_range() {
+        let filter = Filter::must([
+            Filter::field("category").eq("news"),
+            Filter::should([Filter::range("price", 10..=20), Filter::range("stock", 5..)]),
+        ]);
+        assert_eq!(
+            filter.to_json(),
+            json!({"and": [
+                {"field": "category", "op": "eq", "value": "news"},
+                {"or": [
+                    {"and": [
+                        {"field": "price", "op": "gte", "value": 10},
+                        {"field": "price", "op": "lte", "value": 20}
+                    ]},
+                    {"field": "stock", "op": "gte", "value": 5}
+                ]}
+            ]})
+        );
+        assert_eq!(Filter::range::<i64, _>("ts", ..).to_json(), json!({"field": "ts", "op": "exists"}));
+    }
+
+    #[test]
+    fn test_time_range() {
+        let start = UNIX_EPOCH + Duration::from_secs(100);
+        let end = UNIX_EPOCH + Duration::from_secs(200);
+        let filter = Filter::field("ts").within(start..end);
+
+        assert_eq!(
+            filter.to_json(),
+            json!({"and": [
+                {"field": "ts", "op": "gte", "value": 100},
+                {"field": "ts", "op": "lt", "value": 200}
+            ]})
+        );
+
+        let forever = Filter::field("ts").newer_than(Duration::MAX);
+        assert_eq!(forever.to_json(), json!({"field": "ts", "op": "gte", "value": i64::MIN}));
+    }
+}
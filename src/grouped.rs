@@ -0,0 +1,169 @@
+use crate::client::{CasperClient, Dispatch, DEFAULT_SEARCH_LIMIT};
+use crate::encoding::SearchEncoding;
+use crate::error::{CasperError, Result};
+use crate::models::{GroupedSearchResponse, SearchGroup, SearchRequest};
+use crate::records::Record;
+use crate::scores::ScoreScale;
+use crate::version::ApiVersion;
+
+/// Results fetched per wanted hit when grouping client-side, so that groups
+/// sharing the top results still fill up
+const GROUP_OVERFETCH: usize = 4;
+
+impl CasperClient {
+    /// Search with results bucketed by the payload field `group_by`, e.g.
+    /// the best 3 chunks of each of the 10 best documents.
+    ///
+    /// [`SearchRequest::limit`] is the number of groups (default 10), each
+    /// holding up to `group_size` hits, best first; groups are ordered by
+    /// their best score. Results without the field are left out.
+    ///
+    /// Servers without grouping (API 1.0, or answering 404, 405 or 501) get
+    /// `limit * group_size * 4` results fetched and grouped client-side,
+    /// which costs a concurrent payload fetch per result (see
+    /// [`CasperClient::get_records`]) and may return fewer or smaller groups
+    /// than the server would. Hit and best scores are on the client's
+    /// [`ScoreScale`] either way.
+    pub async fn search_grouped(
+        &self,
+        collection_name: &str,
+        request: SearchRequest,
+        group_by: &str,
+        group_size: usize,
+    ) -> Result<GroupedSearchResponse> {
+        if group_size == 0 {
+            return Err(CasperError::InvalidArgument("search group size must be positive".to_string()));
+        }
+
+        if self.supports_api(ApiVersion::V1_1) {
+            let url = self.collection_url(collection_name, "/search/grouped")?;
+            let params = [("group_by", group_by.to_string()), ("group_size", group_size.to_string())];
+            let query = self.prepare_query(collection_name, request.clone()).await?;
+            let response = self
+                .search_request(url, SearchEncoding::Json, &params, query)?
+                .dispatch(self)
+                .await?;
+            if !matches!(response.status().as_u16(), 404 | 405 | 501) {
+                let mut grouped = self.handle_response(response).await?;
+                self.rescale_groups(collection_name, &mut grouped).await?;
+                return Ok(grouped);
+            }
+        }
+        let mut grouped = self.group_locally(collection_name, request, group_by, group_size).await?;
+        self.rescale_groups(collection_name, &mut grouped).await?;
+        Ok(grouped)
+    }
+
+    /// Rescale the raw hit and best scores of `grouped` to the client's
+    /// score scale
+    async fn rescale_groups(&self, collection_name: &str, grouped: &mut GroupedSearchResponse) -> Result<()> {
+        if self.score_scale == ScoreScale::Raw {
+            return Ok(());
+        }
+        let metric = self.collection_metric(collection_name).await?;
+        for group in &mut grouped.groups {
+            self.score_scale.apply(&metric, &mut group.hits)?;
+            group.best_score = self.score_scale.convert(&metric, group.best_score)?;
+        }
+        Ok(())
+    }
+
+    /// [`CasperClient::search_grouped`] over a plain search and payload
+    /// fetches, with raw scores
+    async fn group_locally(
+        &self,
+        collection_name: &str,
+        request: SearchRequest,
+        group_by: &str,
+        group_size: usize,
+    ) -> Result<GroupedSearchResponse> {
+        let groups_wanted = request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let limit = groups_wanted.saturating_mul(group_size).saturating_mul(GROUP_OVERFETCH);
+        let raw = CasperClient { score_scale: ScoreScale::Raw, ..self.clone() };
+        let results = raw.query(collection_name, SearchRequest { limit: Some(limit), ..request }).await?;
+        let ids: Vec<u32> = results.iter().map(|result| result.id).collect();
+        let stored: Vec<Option<Record<Option<serde_json::Value>>>> = self.get_records(collection_name, &ids).await?;
+
+        let mut groups: Vec<SearchGroup> = Vec::new();
+        for (result, stored) in results.into_iter().zip(stored) {
+            let Some(key) = stored.as_ref().and_then(|stored| stored.payload.as_ref()?.get(group_by)) else {
+                continue;
+            };
+            match groups.iter().position(|group| &group.key == key) {
+                Some(idx) if groups[idx].hits.len() < group_size => groups[idx].hits.push(result),
+                Some(_) => {}
+                None if groups.len() < groups_wanted => {
+                    groups.push(SearchGroup { key: key.clone(), best_score: result.score, hits: vec![result] });
+                }
+                None => {}
+            }
+        }
+        Ok(GroupedSearchResponse { groups })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_search_grouped() {
+        let scripted = Scripted::new([(
+            200,
+            r#"{"groups":[{"key":"a","best_score":0.9,"hits":[{"id":1,"score":0.9},{"id":3,"score":0.7}]}]}"#,
+        )]);
+        let request = SearchRequest { vector: vec![1.0], limit: Some(2), ..Default::default() };
+        let grouped = scripted.client().search_grouped("docs", request, "doc", 2).await.unwrap();
+
+        assert_eq!((grouped.groups.len(), grouped.groups[0].hits.len()), (1, 2));
+        assert_eq!(scripted.requests(), ["POST /collection/docs/search/grouped"]);
+        assert_eq!(scripted.last_query("group_by").as_deref(), Some("doc"));
+        assert_eq!(scripted.last_query("group_size").as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_locally() {
+        let scripted = Scripted::new([
+            (404, "not found"),
+            (200, r#"[[1,0.9],[2,0.8],[3,0.7],[4,0.6]]"#),
+            (200, r#"{"id":1,"vector":[1.0],"payload":{"doc":"a"}}"#),
+            (200, r#"{"id":2,"vector":[1.0],"payload":{"doc":"b"}}"#),
+            (200, r#"{"id":3,"vector":[1.0],"payload":{"doc":"a"}}"#),
+            (404, "vector not found"),
+        ]);
+        let request = SearchRequest { vector: vec![1.0], limit: Some(2), ..Default::default() };
+        let grouped = scripted.client().search_grouped("docs", request, "doc", 2).await.unwrap();
+
+        assert_eq!(grouped.groups.len(), 2);
+        assert_eq!((grouped.groups[0].key.as_str(), grouped.groups[0].best_score), (Some("a"), 0.9));
+        let hits: Vec<u32> = grouped.groups[0].hits.iter().map(|hit| hit.id).collect();
+        assert_eq!(hits, [1, 3]);
+        assert_eq!(scripted.requests()[1], "POST /collection/docs/search");
+    }
+
+    #[tokio::test]
+    async fn test_search_grouped_on_client_scale() {
+        let scripted = Scripted::new([
+            (200, r#"{"groups":[{"key":"a","best_score":1.0,"hits":[{"id":1,"score":1.0}]}]}"#),
+            (200, r#"{"name":"docs","dimension":1,"mutable":true,"has_index":true,"max_size":10,"size":1,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (405, "method not allowed"),
+            (200, r#"[[1,1.0]]"#),
+            (200, r#"{"id":1,"vector":[1.0],"payload":{"doc":"a"}}"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+        let request = SearchRequest { vector: vec![1.0], limit: Some(1), ..Default::default() };
+
+        for _ in 0..2 {
+            let grouped = client.search_grouped("docs", request.clone(), "doc", 1).await.unwrap();
+            assert_eq!((grouped.groups[0].best_score, grouped.groups[0].hits[0].score), (0.5, 0.5));
+        }
+        assert_eq!(scripted.requests().len(), 5);
+    }
+}
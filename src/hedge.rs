@@ -0,0 +1,210 @@
+use crate::error::Result;
+use crate::probe::Prober;
+use crate::retry;
+use crate::transport::HttpTransport;
+use reqwest::{Request, Response};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Number of recent latencies the hedging delay is computed from
+const LATENCY_WINDOW: usize = 256;
+/// Samples needed before the percentile replaces the initial delay
+const MIN_SAMPLES: usize = 20;
+
+/// When and where to send a duplicate of a slow idempotent read.
+///
+/// Searches and GETs that have not answered within the configured latency
+/// percentile of recent reads are re-sent to the next replica (round robin,
+/// or the primary server when none are configured); the first successful
+/// answer wins and the other request is dropped. With
+/// [probing](crate::CasperClientBuilder::probing) enabled, hedges go to the
+/// fastest reachable replica instead.
+///
+/// ```
+/// # fn main() -> casper_client::Result<()> {
+/// use casper_client::HedgePolicy;
+///
+/// let policy = HedgePolicy::new(0.95).replica("http://replica-1:8080")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HedgePolicy {
+    percentile: f64,
+    initial_delay: Duration,
+    min_delay: Duration,
+    replicas: Vec<Url>,
+}
+
+impl HedgePolicy {
+    /// Hedge reads slower than the `percentile` (0..1) of recent latencies
+    pub fn new(percentile: f64) -> Self {
+        Self {
+            percentile: percentile.clamp(0.0, 1.0),
+            initial_delay: Duration::from_millis(100),
+            min_delay: Duration::from_millis(5),
+            replicas: Vec::new(),
+        }
+    }
+
+    /// Delay used until enough latencies have been observed (default 100ms)
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Never hedge sooner than `delay` (default 5ms)
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// Add a replica base URL (e.g. `"http://replica-1:8080"`) to send hedges to
+    pub fn replica(mut self, base_url: &str) -> Result<Self> {
+        self.replicas.push(Url::parse(base_url)?);
+        Ok(self)
+    }
+
+    pub(crate) fn replicas(&self) -> &[Url] {
+        &self.replicas
+    }
+}
+
+/// Hedging state shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct Hedger {
+    policy: HedgePolicy,
+    latencies: Mutex<VecDeque<Duration>>,
+    next_replica: AtomicUsize,
+    /// Replica latencies to pick the fastest replica by
+    prober: Option<Arc<Prober>>,
+}
+
+impl Hedger {
+    pub(crate) fn new(policy: HedgePolicy, prober: Option<Arc<Prober>>) -> Self {
+        Self {
+            policy,
+            latencies: Mutex::new(VecDeque::with_capacity(LATENCY_WINDOW)),
+            next_replica: AtomicUsize::new(0),
+            prober,
+        }
+    }
+
+    /// Whether `request` is an idempotent read that may be sent twice
+    pub(crate) fn applies_to(request: &Request) -> bool {
+        retry::is_idempotent_read(request)
+    }
+
+    /// Send `request`, hedging it if it is slow
+    pub(crate) async fn execute(&self, transport: &dyn HttpTransport, request: Request) -> Result<Response> {
+        let Some(mut hedge) = request.try_clone() else {
+            return transport.send(request).await;
+        };
+
+        let start = Instant::now();
+        let primary = transport.send(request);
+        tokio::pin!(primary);
+
+        tokio::select! {
+            result = &mut primary => {
+                self.record(start.elapsed());
+                return result;
+            }
+            _ = tokio::time::sleep(self.delay()) => {}
+        }
+
+        *hedge.url_mut() = self.replica_url(hedge.url());
+        let secondary = transport.send(hedge);
+        tokio::pin!(secondary);
+
+        // First success wins; on a failure, wait for the other request
+        let (first, other) = tokio::select! {
+            result = &mut primary => (result, Either::Secondary),
+            result = &mut secondary => (result, Either::Primary),
+        };
+        let result = match first {
+            Ok(response) => Ok(response),
+            Err(_) => match other {
+                Either::Primary => primary.await,
+                Either::Secondary => secondary.await,
+            },
+        };
+        self.record(start.elapsed());
+        result
+    }
+
+    /// Current hedging delay
+    fn delay(&self) -> Duration {
+        let latencies = self.latencies.lock().expect("latency window poisoned");
+        if latencies.len() < MIN_SAMPLES {
+            return self.policy.initial_delay.max(self.policy.min_delay);
+        }
+
+        let mut sorted: Vec<Duration> = latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let index = ((sorted.len() - 1) as f64 * self.policy.percentile).round() as usize;
+        sorted[index].max(self.policy.min_delay)
+    }
+
+    fn record(&self, latency: Duration) {
+        let mut latencies = self.latencies.lock().expect("latency window poisoned");
+        if latencies.len() == LATENCY_WINDOW {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    /// `url` re-targeted at the next of the fastest probed replicas, else
+    /// the next replica, or unchanged without replicas
+    fn replica_url(&self, url: &Url) -> Url {
+        let replicas = &self.policy.replicas;
+        if replicas.is_empty() {
+            return url.clone();
+        }
+
+        let fastest = self.prober.as_ref().map(|prober| prober.fastest(replicas)).unwrap_or_default();
+        let turn = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        let idx = if fastest.is_empty() { turn % replicas.len() } else { fastest[turn % fastest.len()] };
+        let replica = &replicas[idx];
+        let mut hedged = url.clone();
+        let _ = hedged.set_scheme(replica.scheme());
+        let _ = hedged.set_host(replica.host_str());
+        let _ = hedged.set_port(replica.port());
+        hedged
+    }
+}
+
+/// Which request is still outstanding
+enum Either {
+    Primary,
+    Secondary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_and_replicas() {
+        let policy = HedgePolicy::new(0.9)
+            .initial_delay(Duration::from_millis(40))
+            .replica("http://replica:9090")
+            .unwrap();
+        let hedger = Hedger::new(policy, None);
+        assert_eq!(hedger.delay(), Duration::from_millis(40));
+
+        for ms in 1..=100 {
+            hedger.record(Duration::from_millis(ms));
+        }
+        assert_eq!(hedger.delay(), Duration::from_millis(90));
+
+        let url = Url::parse("http://primary:8080/collection/docs/search?limit=5").unwrap();
+        assert_eq!(
+            hedger.replica_url(&url).as_str(),
+            "http://replica:9090/collection/docs/search?limit=5"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch, DEFAULT_SEARCH_LIMIT};
+use crate::error::{CasperError, Result};
+use crate::filter::Filter;
+use crate::models::{FusionStrategy, HybridSearchRequest, SearchRequest, SearchResponse, SearchResult, SparseVector};
+use crate::scores::ScoreScale;
+use crate::version::ApiVersion;
+use reqwest::Method;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Results fetched per list and wanted result when fusing client-side, so
+/// results ranked low in one list can still make the fused top
+const FUSION_OVERFETCH: usize = 2;
+
+/// `POST collection/<name>/search/sparse` body
+#[derive(Debug, Serialize)]
+struct SparseSearchBody<'a> {
+    sparse: &'a SparseVector,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<&'a Filter>,
+}
+
+impl CasperClient {
+    /// Search by a dense and a sparse query (e.g. an embedding and BM25 or
+    /// SPLADE term weights), fusing both result lists, best first.
+    ///
+    /// The server fuses when it supports hybrid search. Otherwise (API 1.0,
+    /// or answering 404, 405 or 501) the dense and sparse searches are sent
+    /// concurrently, fetching twice the limit each, and fused client-side;
+    /// servers without sparse search then yield
+    /// [`CasperError::OperationNotAllowed`].
+    pub async fn hybrid_search(&self, collection_name: &str, request: HybridSearchRequest) -> Result<SearchResponse> {
+        validate_sparse(&request.sparse)?;
+        validate_fusion(request.fusion)?;
+        let limit = request.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let dense = SearchRequest { vector: request.dense, filter: request.filter, ..Default::default() };
+        let dense = self.prepare_query(collection_name, dense).await?;
+        let request = HybridSearchRequest { dense: dense.vector, filter: dense.filter, ..request };
+
+        if self.supports_api(ApiVersion::V1_1) {
+            let url = self.collection_url(collection_name, "/search/hybrid")?;
+            let response = self
+                .http(Scope::Read, Method::POST, url)
+                .query(&[("limit", limit.to_string())])
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json")
+                .body(self.json_body(&request)?)
+                .dispatch(self)
+                .await?;
+            if !matches!(response.status().as_u16(), 404 | 405 | 501) {
+                return self.handle_search_response(response).await;
+            }
+        }
+
+        let fetched = limit.saturating_mul(FUSION_OVERFETCH);
+        let sparse = self.sparse_search(collection_name, &request.sparse, request.filter.as_ref(), fetched);
+        // The dense vector is prepared already, and its scores are rescaled
+        // here for weighted sums
+        let dense_client = CasperClient { score_scale: ScoreScale::Raw, ..self.stored_space() };
+        let dense_request = SearchRequest {
+            vector: request.dense.clone(),
+            limit: Some(fetched),
+            filter: request.filter.clone(),
+            ..Default::default()
+        };
+        let dense = dense_client.query(collection_name, dense_request);
+        let (sparse, mut dense) = tokio::try_join!(sparse, dense)?;
+        if let FusionStrategy::WeightedSum { .. } = request.fusion {
+            let metric = self.collection_metric(collection_name).await?;
+            ScoreScale::Similarity.apply(&metric, &mut dense)?;
+        }
+        Ok(fuse(&dense, &sparse, request.fusion, limit))
+    }
+
+    async fn sparse_search(
+        &self,
+        collection_name: &str,
+        sparse: &SparseVector,
+        filter: Option<&Filter>,
+        limit: usize,
+    ) -> Result<SearchResponse> {
+        let url = self.collection_url(collection_name, "/search/sparse")?;
+        let response = self
+            .http(Scope::Read, Method::POST, url)
+            .query(&[("limit", limit.to_string())])
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(self.json_body(&SparseSearchBody { sparse, filter })?)
+            .dispatch(self)
+            .await?;
+        if matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "collection '{}': the server supports neither hybrid nor sparse search",
+                collection_name
+            )));
+        }
+        self.handle_search_response(response).await
+    }
+}
+
+fn validate_sparse(sparse: &SparseVector) -> Result<()> {
+    if sparse.indices.len() != sparse.values.len() {
+        return Err(CasperError::InvalidArgument(format!(
+            "sparse vector has {} indices but {} values",
+            sparse.indices.len(),
+            sparse.values.len()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_fusion(fusion: FusionStrategy) -> Result<()> {
+    match fusion {
+        FusionStrategy::Rrf { k } if k.is_nan() || k <= 0.0 => {
+            Err(CasperError::InvalidArgument(format!("RRF fusion needs a positive k, got {}", k)))
+        }
+        FusionStrategy::WeightedSum { dense_weight } if !(0.0..=1.0).contains(&dense_weight) => Err(
+            CasperError::InvalidArgument(format!("fusion dense weight must be within [0, 1], got {}", dense_weight)),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Fuse two result lists, each best first, into the best `limit` results
+fn fuse(dense: &[SearchResult], sparse: &[SearchResult], fusion: FusionStrategy, limit: usize) -> SearchResponse {
+    let mut fused: HashMap<u32, f32> = HashMap::new();
+    for (results, is_dense) in [(dense, true), (sparse, false)] {
+        let scored: Vec<(u32, f32)> = match fusion {
+            FusionStrategy::Rrf { k } => {
+                results.iter().enumerate().map(|(rank, result)| (result.id, 1.0 / (k + rank as f32 + 1.0))).collect()
+            }
+            FusionStrategy::WeightedSum { dense_weight } => {
+                let weight = if is_dense { dense_weight } else { 1.0 - dense_weight };
+                min_max_scaled(results).into_iter().map(|(id, score)| (id, weight * score)).collect()
+            }
+        };
+        for (id, score) in scored {
+            *fused.entry(id).or_default() += score;
+        }
+    }
+
+    let mut fused: SearchResponse = fused.into_iter().map(|(id, score)| SearchResult { id, score }).collect();
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+    fused.truncate(limit);
+    fused
+}
+
+/// Scores scaled to `[0, 1]` within their list; all 1 if they are equal
+fn min_max_scaled(results: &[SearchResult]) -> Vec<(u32, f32)> {
+    let min = results.iter().map(|result| result.score).fold(f32::INFINITY, f32::min);
+    let max = results.iter().map(|result| result.score).fold(f32::NEG_INFINITY, f32::max);
+    results
+        .iter()
+        .map(|result| {
+            let scaled = if max > min { (result.score - min) / (max - min) } else { 1.0 };
+            (result.id, scaled)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_hybrid_search_fused_locally() {
+        let scripted = Scripted::new([
+            (404, "not found"),
+            (200, r#"[[7,12.5],[3,4.0]]"#),
+            (200, r#"[[3,0.9],[5,0.8]]"#),
+        ]);
+        let request = HybridSearchRequest {
+            dense: vec![1.0, 0.0],
+            sparse: SparseVector::from_entries([(17, 0.5), (902, 1.5)]),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let results = scripted.client().hybrid_search("docs", request).await.unwrap();
+        // 3 is second in the sparse and first in the dense list
+        assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), [3, 7]);
+        assert_eq!(
+            scripted.requests(),
+            [
+                "POST /collection/docs/search/hybrid",
+                "POST /collection/docs/search/sparse",
+                "POST /collection/docs/search",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_weighted_sum_fusion_by_similarity() {
+        let scripted = Scripted::new([
+            (404, "not found"),
+            (200, r#"[[3,0.9],[5,0.8]]"#),
+            (200, r#"[[7,0.0],[3,4.0]]"#),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":3,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+        ]);
+        let request = HybridSearchRequest {
+            dense: vec![1.0, 0.0],
+            sparse: SparseVector::from_entries([(17, 0.5)]),
+            limit: Some(2),
+            fusion: FusionStrategy::WeightedSum { dense_weight: 0.75 },
+            ..Default::default()
+        };
+        let client = scripted.client();
+        // 7 is at distance 0, so the best dense result despite its lower score
+        let results = client.hybrid_search("docs", request.clone()).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), [7, 3]);
+
+        for fusion in [FusionStrategy::WeightedSum { dense_weight: 1.5 }, FusionStrategy::Rrf { k: 0.0 }] {
+            let invalid = client.hybrid_search("docs", HybridSearchRequest { fusion, ..request.clone() }).await;
+            assert!(matches!(invalid, Err(CasperError::InvalidArgument(_))));
+        }
+        assert_eq!(scripted.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_weighted_sum_fusion() {
+        let dense = [SearchResult { id: 1, score: 0.9 }, SearchResult { id: 2, score: 0.1 }];
+        let sparse = [SearchResult { id: 2, score: 30.0 }, SearchResult { id: 3, score: 10.0 }];
+        let fused = fuse(&dense, &sparse, FusionStrategy::WeightedSum { dense_weight: 0.25 }, 3);
+        let ranked: Vec<(u32, f32)> = fused.iter().map(|r| (r.id, r.score)).collect();
+        assert_eq!(ranked, [(2, 0.75), (1, 0.25), (3, 0.0)]);
+    }
+}
@@ -0,0 +1,373 @@
+//! Mapping of application keys (strings or `u64`s) to the `u32` vector IDs
+//! the server stores, for data whose natural keys do not fit a `u32`.
+//!
+//! An [`IdMap`] assigns IDs on first use and persists every assignment
+//! through an [`IdStore`] before it is used, so the same key maps to the
+//! same ID across restarts. [`CollectionHandle::with_ids`] then inserts,
+//! deletes and searches a collection by key.
+//!
+//! ```no_run
+//! # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+//! use casper_client::idmap::{FileIdStore, IdMap};
+//! use std::sync::Arc;
+//!
+//! let ids = Arc::new(IdMap::open(FileIdStore::new("docs-ids.jsonl")).await?);
+//! let docs = client.collection("docs").with_ids(ids);
+//! docs.insert("intro.md", vec![0.1, 0.2], None).await?;
+//! for result in docs.search(vec![0.1, 0.2], 5).await? {
+//!     println!("{:?}: {}", result.key, result.score);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::collection::CollectionHandle;
+use crate::error::{CasperError, Result};
+use crate::models::{InsertRequest, SearchRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Future returned by [`IdStore`] methods
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Application key of a vector
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExternalId {
+    Int(u64),
+    Str(String),
+}
+
+impl From<u64> for ExternalId {
+    fn from(key: u64) -> Self {
+        Self::Int(key)
+    }
+}
+
+impl From<String> for ExternalId {
+    fn from(key: String) -> Self {
+        Self::Str(key)
+    }
+}
+
+impl From<&str> for ExternalId {
+    fn from(key: &str) -> Self {
+        Self::Str(key.to_string())
+    }
+}
+
+impl fmt::Display for ExternalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(key) => write!(f, "{}", key),
+            Self::Str(key) => f.write_str(key),
+        }
+    }
+}
+
+/// Persistence of the assignments of an [`IdMap`].
+///
+/// [`FileIdStore`] keeps them in a local file and [`MemoryIdStore`] nowhere;
+/// implement this for a database or key-value store shared by several
+/// processes. Changes must be durable once the returned future completes.
+pub trait IdStore: fmt::Debug + Send + Sync + 'static {
+    /// Every assignment, read once when the map is opened
+    fn load(&self) -> StoreFuture<'_, Vec<(ExternalId, u32)>>;
+
+    /// Record new assignments
+    fn insert(&self, assigned: Vec<(ExternalId, u32)>) -> StoreFuture<'_, ()>;
+
+    /// Forget the assignment of `key`
+    fn remove(&self, key: ExternalId) -> StoreFuture<'_, ()>;
+}
+
+/// Store keeping assignments for the lifetime of the process only
+#[derive(Debug, Default)]
+pub struct MemoryIdStore;
+
+impl IdStore for MemoryIdStore {
+    fn load(&self) -> StoreFuture<'_, Vec<(ExternalId, u32)>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+
+    fn insert(&self, _assigned: Vec<(ExternalId, u32)>) -> StoreFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn remove(&self, _key: ExternalId) -> StoreFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// One line of a [`FileIdStore`]; `id` is `None` for removals
+#[derive(Debug, Serialize, Deserialize)]
+struct IdRecord {
+    key: ExternalId,
+    id: Option<u32>,
+}
+
+/// Store appending assignments and removals to a JSON lines file, replayed
+/// on load
+#[derive(Debug)]
+pub struct FileIdStore {
+    path: PathBuf,
+}
+
+impl FileIdStore {
+    /// Store backed by `path`, created on the first assignment
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    async fn append(&self, records: &[IdRecord]) -> Result<()> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(&lines).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+}
+
+impl IdStore for FileIdStore {
+    fn load(&self) -> StoreFuture<'_, Vec<(ExternalId, u32)>> {
+        Box::pin(async move {
+            let text = match tokio::fs::read_to_string(&self.path).await {
+                Ok(text) => text,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(e) => return Err(e.into()),
+            };
+            let mut assigned = HashMap::new();
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                let record: IdRecord = serde_json::from_str(line)?;
+                match record.id {
+                    Some(id) => assigned.insert(record.key, id),
+                    None => assigned.remove(&record.key),
+                };
+            }
+            Ok(assigned.into_iter().collect())
+        })
+    }
+
+    fn insert(&self, assigned: Vec<(ExternalId, u32)>) -> StoreFuture<'_, ()> {
+        let records: Vec<IdRecord> = assigned.into_iter().map(|(key, id)| IdRecord { key, id: Some(id) }).collect();
+        Box::pin(async move { self.append(&records).await })
+    }
+
+    fn remove(&self, key: ExternalId) -> StoreFuture<'_, ()> {
+        Box::pin(async move { self.append(&[IdRecord { key, id: None }]).await })
+    }
+}
+
+#[derive(Debug)]
+struct Assignments {
+    ids: HashMap<ExternalId, u32>,
+    keys: HashMap<u32, ExternalId>,
+    next: u32,
+}
+
+/// Bidirectional map between application keys and server IDs, see the
+/// [module documentation](self).
+///
+/// IDs are assigned in increasing order, from the map's offset or after
+/// the highest ID loaded from the store, whichever is larger. IDs of removed
+/// keys are not reassigned while the map is open.
+#[derive(Debug)]
+pub struct IdMap {
+    store: Box<dyn IdStore>,
+    assignments: Mutex<Assignments>,
+}
+
+impl IdMap {
+    /// Map loaded from `store`, assigning IDs from 0
+    pub async fn open(store: impl IdStore) -> Result<Self> {
+        Self::open_with_offset(store, 0).await
+    }
+
+    /// Map loaded from `store`, assigning IDs from `first_id`, e.g. to keep
+    /// mapped vectors clear of IDs the application assigns itself
+    pub async fn open_with_offset(store: impl IdStore, first_id: u32) -> Result<Self> {
+        let loaded = store.load().await?;
+        let next = loaded.iter().map(|(_, id)| id.saturating_add(1)).max().unwrap_or(0).max(first_id);
+        let keys = loaded.iter().map(|(key, id)| (*id, key.clone())).collect();
+        let assignments = Assignments { ids: loaded.into_iter().collect(), keys, next };
+        Ok(Self { store: Box::new(store), assignments: Mutex::new(assignments) })
+    }
+
+    /// ID of `key`, if assigned
+    pub async fn id(&self, key: &ExternalId) -> Option<u32> {
+        self.assignments.lock().await.ids.get(key).copied()
+    }
+
+    /// Key the ID `id` is assigned to
+    pub async fn key(&self, id: u32) -> Option<ExternalId> {
+        self.assignments.lock().await.keys.get(&id).cloned()
+    }
+
+    /// Number of assigned keys
+    pub async fn len(&self) -> usize {
+        self.assignments.lock().await.ids.len()
+    }
+
+    /// Whether no key is assigned
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// ID of `key`, assigning and persisting the next free one if needed
+    pub async fn assign(&self, key: impl Into<ExternalId>) -> Result<u32> {
+        let ids = self.assign_all([key.into()]).await?;
+        Ok(ids[0])
+    }
+
+    /// IDs of `keys` in order, assigning new ones with a single store write
+    pub async fn assign_all(&self, keys: impl IntoIterator<Item = ExternalId>) -> Result<Vec<u32>> {
+        let mut assignments = self.assignments.lock().await;
+        let mut next = assignments.next;
+        let mut ids = Vec::new();
+        let mut assigned: Vec<(ExternalId, u32)> = Vec::new();
+        for key in keys {
+            let known = assignments.ids.get(&key).copied();
+            let id = match known.or_else(|| assigned.iter().find(|(new, _)| *new == key).map(|(_, id)| *id)) {
+                Some(id) => id,
+                None => {
+                    let id = next;
+                    next = next.checked_add(1).ok_or_else(|| {
+                        CasperError::InvalidArgument("ID map has assigned every u32 ID".to_string())
+                    })?;
+                    assigned.push((key, id));
+                    id
+                }
+            };
+            ids.push(id);
+        }
+        if assigned.is_empty() {
+            return Ok(ids);
+        }
+
+        // Persisted before use, so a crash cannot leave unmapped vectors
+        self.store.insert(assigned.clone()).await?;
+        assignments.next = next;
+        for (key, id) in assigned {
+            assignments.keys.insert(id, key.clone());
+            assignments.ids.insert(key, id);
+        }
+        Ok(ids)
+    }
+
+    /// Forget `key`, returning the ID it had
+    pub async fn remove(&self, key: &ExternalId) -> Result<Option<u32>> {
+        let mut assignments = self.assignments.lock().await;
+        if !assignments.ids.contains_key(key) {
+            return Ok(None);
+        }
+        self.store.remove(key.clone()).await?;
+        let id = assignments.ids.remove(key);
+        if let Some(id) = id {
+            assignments.keys.remove(&id);
+        }
+        Ok(id)
+    }
+}
+
+/// Search result of a [`KeyedCollection`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyedResult {
+    /// Key of the result, `None` for vectors inserted without the map
+    pub key: Option<ExternalId>,
+    pub id: u32,
+    pub score: f32,
+}
+
+/// Collection addressed by application keys, see [`CollectionHandle::with_ids`]
+#[derive(Debug, Clone)]
+pub struct KeyedCollection {
+    collection: CollectionHandle,
+    ids: Arc<IdMap>,
+}
+
+impl CollectionHandle {
+    /// This collection addressed by the keys of `ids`
+    pub fn with_ids(&self, ids: Arc<IdMap>) -> KeyedCollection {
+        KeyedCollection { collection: self.clone(), ids }
+    }
+}
+
+impl KeyedCollection {
+    /// ID map of the collection
+    pub fn ids(&self) -> &Arc<IdMap> {
+        &self.ids
+    }
+
+    /// Insert a vector under `key`, returning the server ID it got
+    pub async fn insert(
+        &self,
+        key: impl Into<ExternalId>,
+        vector: Vec<f32>,
+        payload: Option<serde_json::Value>,
+    ) -> Result<u32> {
+        let id = self.ids.assign(key).await?;
+        self.collection.insert(InsertRequest { id, vector, payload }).await?;
+        Ok(id)
+    }
+
+    /// Delete the vector of `key` and forget its ID; `false` if the key was
+    /// never assigned
+    pub async fn delete(&self, key: impl Into<ExternalId>) -> Result<bool> {
+        let key = key.into();
+        let Some(id) = self.ids.id(&key).await else {
+            return Ok(false);
+        };
+        self.collection.delete(id).await?;
+        self.ids.remove(&key).await?;
+        Ok(true)
+    }
+
+    /// The `limit` vectors most similar to `vector`, with their keys
+    pub async fn search(&self, vector: Vec<f32>, limit: usize) -> Result<Vec<KeyedResult>> {
+        let request = SearchRequest { vector, limit: Some(limit), ..Default::default() };
+        let results = self.collection.client.query(&self.collection.name, request).await?;
+        let mut keyed = Vec::with_capacity(results.len());
+        for result in results {
+            keyed.push(KeyedResult { key: self.ids.key(result.id).await, id: result.id, score: result.score });
+        }
+        Ok(keyed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_keyed_collection() {
+        let path = std::env::temp_dir().join(format!("casper-ids-{}.jsonl", std::process::id()));
+        let ids = Arc::new(IdMap::open_with_offset(FileIdStore::new(&path), 100).await.unwrap());
+        let scripted = Scripted::new([(200, ""), (200, ""), (200, r#"[{"id":101,"score":0.9},{"id":7,"score":0.5}]"#)]);
+        let docs = scripted.client().collection("docs").with_ids(ids.clone());
+
+        assert_eq!(docs.insert("a.md", vec![0.5, 1.0], None).await.unwrap(), 100);
+        assert_eq!(docs.insert(42u64, vec![1.0, 0.5], None).await.unwrap(), 101);
+        let results = docs.search(vec![1.0, 0.5], 2).await.unwrap();
+        assert_eq!(results[0].key, Some(ExternalId::Int(42)));
+        assert_eq!(results[1].key, None);
+
+        // Assignments survive a restart
+        ids.remove(&ExternalId::from("a.md")).await.unwrap();
+        let reopened = IdMap::open(FileIdStore::new(&path)).await.unwrap();
+        assert_eq!((reopened.len().await, reopened.id(&42u64.into()).await), (1, Some(101)));
+        assert_eq!(reopened.assign("b.md").await.unwrap(), 102);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
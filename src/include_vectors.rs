@@ -0,0 +1,120 @@
+use crate::client::{CasperClient, Dispatch};
+use crate::encoding::SearchEncoding;
+use crate::error::Result;
+use crate::models::{SearchRequest, SearchResultWithVector};
+use crate::scores::ScoreScale;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+/// Result of a search asked to include vectors; servers that ignore
+/// `include_vectors` leave them out
+#[derive(Deserialize)]
+struct MaybeWithVector {
+    id: u32,
+    score: f32,
+    #[serde(default)]
+    vector: Option<Vec<f32>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MaybeWithVectors {
+    List(Vec<MaybeWithVector>),
+    Wrapped { results: Vec<MaybeWithVector> },
+}
+
+impl CasperClient {
+    /// Search and return the matched vectors alongside their scores, e.g.
+    /// for reranking, instead of fetching each result afterwards.
+    ///
+    /// Vectors are returned as stored, so reduced if the collection has a
+    /// [`crate::DimReducer`]. Servers that do not include vectors get the
+    /// missing ones fetched concurrently, see [`CasperClient::get_records`];
+    /// results deleted before being fetched are dropped. Scores are on the
+    /// client's [`ScoreScale`], as by [`CasperClient::query`].
+    pub async fn search_with_vectors(
+        &self,
+        collection_name: &str,
+        request: SearchRequest,
+    ) -> Result<Vec<SearchResultWithVector>> {
+        let url = self.collection_url(collection_name, "/search")?;
+        let params = [("include_vectors", "true".to_string())];
+        let request = self.prepare_query(collection_name, request).await?;
+        let cutoffs = self.cutoffs(collection_name, &request).await?;
+        let response = self
+            .search_request(url, SearchEncoding::Json, &params, request)?
+            .dispatch(self)
+            .await?;
+        let (MaybeWithVectors::List(results) | MaybeWithVectors::Wrapped { results }) =
+            self.handle_response(response).await?;
+
+        let results: Vec<_> = results.into_iter().filter(|result| cutoffs.keeps(result.score)).collect();
+        let missing: Vec<u32> =
+            results.iter().filter(|result| result.vector.is_none()).map(|result| result.id).collect();
+        let mut fetched = self.get_records::<IgnoredAny>(collection_name, &missing).await?.into_iter();
+        let metric = match self.score_scale {
+            ScoreScale::Raw => None,
+            _ => Some(self.collection_metric(collection_name).await?),
+        };
+
+        let mut with_vectors = Vec::with_capacity(results.len());
+        for result in results {
+            let vector = match result.vector {
+                Some(vector) => Some(vector),
+                None => fetched.next().flatten().map(|record| record.vector),
+            };
+            let score = match &metric {
+                Some(metric) => self.score_scale.convert(metric, result.score)?,
+                None => result.score,
+            };
+            if let Some(vector) = vector {
+                with_vectors.push(SearchResultWithVector { id: result.id, score, vector });
+            }
+        }
+        Ok(with_vectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_search_with_vectors() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":0.9,"vector":[0.5,0.5]},{"id":2,"score":0.8},{"id":3,"score":0.7}]"#),
+            (200, r#"{"id":2,"vector":[1.0,0.0]}"#),
+            (404, "vector not found"),
+        ]);
+        let request = SearchRequest { vector: vec![0.5, 0.5], ..Default::default() };
+        let results = scripted.client().search_with_vectors("docs", request).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].vector.clone(), results[1].vector.clone()), (vec![0.5, 0.5], vec![1.0, 0.0]));
+        assert_eq!(scripted.requests(), [
+            "POST /collection/docs/search",
+            "GET /collection/docs/vector/2",
+            "GET /collection/docs/vector/3"
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_search_with_vectors_on_client_scale() {
+        let scripted = Scripted::new([
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"cosine","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (200, r#"[{"id":1,"score":0.5,"vector":[0.5,0.5]},{"id":2,"score":-0.5,"vector":[1.0,0.0]}]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        // The threshold is raw, the reported scores are similarities
+        let request = SearchRequest { vector: vec![0.5, 0.5], score_threshold: Some(0.0), ..Default::default() };
+        let results = client.search_with_vectors("docs", request).await.unwrap();
+        assert_eq!(results.iter().map(|r| (r.id, r.score)).collect::<Vec<_>>(), [(1, 0.75)]);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::job::Job;
+use crate::outbox::WriteOp;
+use crate::progress::{Phase, ProgressCounter};
+use std::time::Instant;
+
+impl CasperClient {
+    /// Send a write, waiting out an index build if configured with
+    /// [`CasperClientBuilder::wait_for_index`](crate::CasperClientBuilder::wait_for_index).
+    ///
+    /// A write rejected with [`CasperError::IndexCreationInProgress`] is
+    /// retried once the collection reports its index, or when the deadline
+    /// is reached, after which the error is returned. Upserts are translated
+    /// for the server once, not per attempt.
+    pub(crate) async fn send_write(&self, collection_name: &str, op: WriteOp) -> Result<String> {
+        let op = self.translate_upsert(collection_name, op).await?;
+        let Some(max_wait) = self.index_wait else {
+            return self.send_write_once(collection_name, op).await;
+        };
+
+        let deadline = Instant::now() + max_wait;
+        loop {
+            match self.send_write_once(collection_name, op.clone()).await {
+                Err(CasperError::IndexCreationInProgress) if Instant::now() < deadline => {
+                    self.wait_for_index(collection_name, deadline).await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Follow the collection's index build as a [`Job`] until it reports an
+    /// index or `deadline` passes, reporting progress once per poll
+    async fn wait_for_index(&self, collection_name: &str, deadline: Instant) -> Result<()> {
+        let progress =
+            ProgressCounter::new(self.progress.as_ref(), "wait_for_index", collection_name, Phase::WaitingForIndex, Some(1));
+        let on_poll = || {
+            if let Some(progress) = &progress {
+                progress.advance(0, 0);
+            }
+        };
+        let built = Job::index_build(self, collection_name).wait_until(Some(deadline), on_poll).await?;
+        if let (Some(progress), Some(_)) = (&progress, built) {
+            progress.advance(1, 0);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::InsertRequest;
+    use crate::testing::Scripted;
+    use crate::CasperClient;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_write_waits_for_index() {
+        let transport = Scripted::new([
+            (423, r#"{"error":"index creation in progress"}"#),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":0,"index":null}"#),
+            (200, ""),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(transport.clone())
+            .wait_for_index(Duration::from_secs(5))
+            .build()
+            .unwrap();
+
+        client.insert_vector("docs", InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None }).await.unwrap();
+        assert_eq!(
+            transport.requests(),
+            vec!["POST /collection/docs/insert", "GET /collection/docs", "POST /collection/docs/insert"]
+        );
+    }
+}
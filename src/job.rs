@@ -0,0 +1,258 @@
+//! Long-running server jobs: index builds, backups, compactions and
+//! replications run in the background on servers that report them as jobs.
+//!
+//! A [`Job`] polls `GET job/<id>` with backoff and cancels with
+//! `POST job/<id>/cancel`. Index builds on servers without jobs are
+//! followed through the collection info instead, which is also how writes
+//! wait out index builds (see
+//! [`CasperClientBuilder::wait_for_index`](crate::CasperClientBuilder::wait_for_index)).
+
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::version::ApiVersion;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// First delay between job status polls, doubled up to [`MAX_POLL`]
+const INITIAL_POLL: Duration = Duration::from_millis(100);
+const MAX_POLL: Duration = Duration::from_secs(2);
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    IndexBuild,
+    Backup,
+    Compaction,
+    Replication,
+    /// A kind this client does not know yet
+    #[serde(other)]
+    Other,
+}
+
+/// Where a job is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    /// A status this client does not know yet, polled like a running job
+    #[serde(other)]
+    Unknown,
+}
+
+impl JobStatus {
+    /// Whether the job stopped, successfully or not
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// `GET job/<id>` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    /// Empty for index builds the server does not track as jobs
+    pub id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Completed fraction in `[0, 1]`, if the server estimates it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f32>,
+    /// Collection the job works on, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    /// Why a failed job failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Handle of a long-running operation, e.g. returned by
+/// [`CasperClient::create_hnsw_index`]; clones of it refer to the same job
+#[derive(Debug, Clone)]
+pub struct Job {
+    client: CasperClient,
+    target: JobTarget,
+}
+
+#[derive(Debug, Clone)]
+enum JobTarget {
+    /// A job the server tracks under this ID
+    Server(String),
+    /// An index build of this collection, on a server without jobs
+    IndexBuild(String),
+}
+
+impl Job {
+    /// Index build of `collection_name`, followed through its collection info
+    pub(crate) fn index_build(client: &CasperClient, collection_name: &str) -> Self {
+        Self { client: client.clone(), target: JobTarget::IndexBuild(collection_name.to_string()) }
+    }
+
+    /// Job of an index build the server answered with `body`: the job it
+    /// reported, else one followed through the collection info
+    pub(crate) fn index_build_started(client: &CasperClient, collection_name: &str, body: &str) -> Self {
+        match serde_json::from_str::<JobInfo>(body) {
+            Ok(info) => Self { client: client.clone(), target: JobTarget::Server(info.id) },
+            Err(_) => Self::index_build(client, collection_name),
+        }
+    }
+
+    /// Server-side job ID, `None` for index builds the server does not
+    /// track as jobs
+    pub fn id(&self) -> Option<&str> {
+        match &self.target {
+            JobTarget::Server(id) => Some(id),
+            JobTarget::IndexBuild(_) => None,
+        }
+    }
+
+    /// Current state of the job
+    pub async fn status(&self) -> Result<JobInfo> {
+        let id = match &self.target {
+            JobTarget::Server(id) => id,
+            JobTarget::IndexBuild(collection_name) => {
+                let info = self.client.get_collection(collection_name).await?;
+                return Ok(JobInfo {
+                    id: String::new(),
+                    kind: JobKind::IndexBuild,
+                    status: if info.has_index { JobStatus::Succeeded } else { JobStatus::Running },
+                    progress: None,
+                    collection: Some(collection_name.clone()),
+                    error: None,
+                });
+            }
+        };
+        let url = self.client.segments_url(&["job", id])?;
+        let response = self.client.http(Scope::Read, Method::GET, url).dispatch(&self.client).await?;
+        self.client.handle_response(response).await
+    }
+
+    /// Poll until the job finishes, returning its final state.
+    ///
+    /// Failed and cancelled jobs yield [`CasperError::JobFailed`]. Bound
+    /// the wait with `tokio::time::timeout` if needed; dropping the future
+    /// does not cancel the job.
+    pub async fn wait(&self) -> Result<JobInfo> {
+        let finished = self.wait_until(None, || {}).await?;
+        Ok(finished.expect("waits without a deadline only end when the job does"))
+    }
+
+    /// [`Job::wait`], giving up with `None` once `deadline` passes; calls
+    /// `on_poll` before every poll
+    pub(crate) async fn wait_until(
+        &self,
+        deadline: Option<Instant>,
+        mut on_poll: impl FnMut(),
+    ) -> Result<Option<JobInfo>> {
+        let mut delay = INITIAL_POLL;
+        loop {
+            on_poll();
+            let info = self.status().await?;
+            match info.status {
+                JobStatus::Succeeded => return Ok(Some(info)),
+                JobStatus::Failed | JobStatus::Cancelled => {
+                    let message = info.error.unwrap_or_else(|| format!("job {:?}", info.status).to_lowercase());
+                    return Err(CasperError::JobFailed { id: info.id, message });
+                }
+                JobStatus::Pending | JobStatus::Running | JobStatus::Unknown => {}
+            }
+            let now = Instant::now();
+            let sleep = match deadline {
+                Some(deadline) if now >= deadline => return Ok(None),
+                Some(deadline) => delay.min(deadline - now),
+                None => delay,
+            };
+            tokio::time::sleep(sleep).await;
+            delay = (delay * 2).min(MAX_POLL);
+        }
+    }
+
+    /// Ask the server to stop the job; it reports
+    /// [`JobStatus::Cancelled`] once stopped. Index builds the server does
+    /// not track as jobs cannot be cancelled.
+    pub async fn cancel(&self) -> Result<()> {
+        self.client.check_writable("cancel_job")?;
+        let id = match &self.target {
+            JobTarget::Server(id) => id,
+            JobTarget::IndexBuild(collection_name) => {
+                return Err(CasperError::OperationNotAllowed(format!(
+                    "the server does not track the index build of '{}' as a cancellable job",
+                    collection_name
+                )));
+            }
+        };
+        let url = self.client.segments_url(&["job", id, "cancel"])?;
+        let response = self.client.http(Scope::Admin, Method::POST, url).dispatch(&self.client).await?;
+        self.client.handle_text_response(response).await?;
+        Ok(())
+    }
+}
+
+impl CasperClient {
+    /// Handle of the server job `id`, e.g. one started by another process
+    pub fn job(&self, id: &str) -> Result<Job> {
+        self.require_api(ApiVersion::V1_1, "jobs")?;
+        Ok(Job { client: self.clone(), target: JobTarget::Server(id.to_string()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_job_wait() {
+        let scripted = Scripted::new([
+            (200, r#"{"id":"j1","kind":"compaction","status":"running","progress":0.5}"#),
+            (200, r#"{"id":"j1","kind":"compaction","status":"succeeded"}"#),
+            (200, r#"{"id":"j2","kind":"defragment","status":"failed","error":"disk full"}"#),
+        ]);
+        let client = scripted.client();
+
+        let done = client.job("j1").unwrap().wait().await.unwrap();
+        assert_eq!((done.kind, done.status), (JobKind::Compaction, JobStatus::Succeeded));
+        let failed = client.job("j2").unwrap().wait().await;
+        assert!(matches!(failed, Err(CasperError::JobFailed { message, .. }) if message == "disk full"));
+        assert_eq!(scripted.requests(), ["GET /job/j1", "GET /job/j1", "GET /job/j2"]);
+    }
+
+    #[tokio::test]
+    async fn test_index_build_jobs() {
+        let scripted = Scripted::new([
+            (202, r#"{"id":"a/b","kind":"index_build","status":"queued"}"#),
+            (200, r#"{"id":"a/b","kind":"index_build","status":"queued"}"#),
+            (200, r#"{"id":"a/b","kind":"index_build","status":"succeeded"}"#),
+            (200, ""),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":0,
+                "index":null}"#),
+        ]);
+        let client = scripted.client();
+        let hnsw = crate::models::HNSWIndexConfig {
+            metric: "cosine".to_string(),
+            quantization: "f32".to_string(),
+            m: 16,
+            m0: 32,
+            ef_construction: 200,
+            pq_name: None,
+        };
+        let request = crate::models::CreateHNSWIndexRequest { hnsw, normalization: None };
+
+        let job = client.create_hnsw_index("docs", request.clone()).await.unwrap();
+        assert_eq!(job.id(), Some("a/b"));
+        assert_eq!(job.wait().await.unwrap().status, JobStatus::Succeeded);
+        assert_eq!(scripted.requests()[1], "GET /job/a%2Fb");
+
+        // Servers without jobs answer with an empty body
+        let job = client.create_hnsw_index("docs", request).await.unwrap();
+        assert_eq!(job.id(), None);
+        assert_eq!(job.wait().await.unwrap().collection.as_deref(), Some("docs"));
+        assert!(matches!(job.cancel().await, Err(CasperError::OperationNotAllowed(_))));
+        assert_eq!(scripted.requests()[4], "GET /collection/docs");
+    }
+}
@@ -0,0 +1,91 @@
+//! Distance kernels that use 8-lane SIMD when the `simd` feature is
+//! enabled, and scalar loops otherwise.
+
+#[cfg(feature = "simd")]
+use wide::f32x8;
+
+/// Lanes per SIMD register
+#[cfg(feature = "simd")]
+const LANES: usize = 8;
+
+#[cfg(feature = "simd")]
+fn lanes(v: &[f32]) -> (impl Iterator<Item = f32x8> + '_, &[f32]) {
+    let chunks = v.chunks_exact(LANES);
+    let rest = chunks.remainder();
+    (chunks.map(|c| f32x8::from([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])), rest)
+}
+
+/// Dot product over the common prefix of `a` and `b`
+pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    #[cfg(feature = "simd")]
+    {
+        let ((a_lanes, a_rest), (b_lanes, b_rest)) = (lanes(a), lanes(b));
+        let sum = a_lanes.zip(b_lanes).fold(f32x8::ZERO, |acc, (x, y)| x.mul_add(y, acc));
+        sum.reduce_add() + a_rest.iter().zip(b_rest).map(|(x, y)| x * y).sum::<f32>()
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+}
+
+/// Squared Euclidean distance over the common prefix of `a` and `b`
+pub(crate) fn squared_l2(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    #[cfg(feature = "simd")]
+    {
+        let ((a_lanes, a_rest), (b_lanes, b_rest)) = (lanes(a), lanes(b));
+        let sum = a_lanes.zip(b_lanes).fold(f32x8::ZERO, |acc, (x, y)| {
+            let d = x - y;
+            d.mul_add(d, acc)
+        });
+        sum.reduce_add() + a_rest.iter().zip(b_rest).map(|(x, y)| (x - y) * (x - y)).sum::<f32>()
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+    }
+}
+
+/// `v *= factor` in place
+pub(crate) fn scale_in_place(v: &mut [f32], factor: f32) {
+    #[cfg(feature = "simd")]
+    {
+        let factor_lanes = f32x8::splat(factor);
+        let mut chunks = v.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            let scaled = (f32x8::from(&*chunk) * factor_lanes).to_array();
+            chunk.copy_from_slice(&scaled);
+        }
+        chunks.into_remainder().iter_mut().for_each(|x| *x *= factor);
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        v.iter_mut().for_each(|x| *x *= factor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        // Lengths around the lane width exercise the remainder handling
+        for len in [0, 1, 7, 8, 9, 17, 1536] {
+            let a: Vec<f32> = (0..len).map(|i| (i as f32 * 0.37).sin()).collect();
+            let b: Vec<f32> = (0..len).map(|i| (i as f32 * 0.11).cos()).collect();
+            let dot_ref: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+            let l2_ref: f32 = a.iter().zip(&b).map(|(x, y)| (x - y) * (x - y)).sum();
+            assert!((dot(&a, &b) - dot_ref).abs() < 1e-3, "dot of length {}", len);
+            assert!((squared_l2(&a, &b) - l2_ref).abs() < 1e-3, "l2 of length {}", len);
+
+            let mut scaled = a.clone();
+            scale_in_place(&mut scaled, 2.0);
+            assert!(scaled.iter().zip(&a).all(|(s, x)| *s == 2.0 * x));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{CollectionInfo, Labels};
+use std::str::FromStr;
+
+/// One requirement of a [`LabelSelector`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelRequirement {
+    /// `key=value`
+    Equals(String, String),
+    /// `key!=value` (also matches when the label is absent)
+    NotEquals(String, String),
+    /// `key`
+    Exists(String),
+    /// `!key`
+    NotExists(String),
+}
+
+impl LabelRequirement {
+    fn matches(&self, labels: &Labels) -> bool {
+        match self {
+            LabelRequirement::Equals(key, value) => labels.get(key) == Some(value),
+            LabelRequirement::NotEquals(key, value) => labels.get(key) != Some(value),
+            LabelRequirement::Exists(key) => labels.contains_key(key),
+            LabelRequirement::NotExists(key) => !labels.contains_key(key),
+        }
+    }
+}
+
+/// Comma-separated label requirements, all of which must hold.
+///
+/// ```
+/// use casper_client::labels::LabelSelector;
+///
+/// let selector: LabelSelector = "team=search,env!=prod,!deprecated".parse().unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelSelector {
+    pub requirements: Vec<LabelRequirement>,
+}
+
+impl LabelSelector {
+    /// Whether `labels` satisfy every requirement
+    pub fn matches(&self, labels: &Labels) -> bool {
+        self.requirements.iter().all(|r| r.matches(labels))
+    }
+}
+
+impl FromStr for LabelSelector {
+    type Err = CasperError;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        let invalid = |term: &str| {
+            CasperError::InvalidArgument(format!("invalid label selector term '{}'", term))
+        };
+
+        let requirements = selector
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(|term| {
+                let requirement = if let Some((key, value)) = term.split_once("!=") {
+                    LabelRequirement::NotEquals(key.trim().to_string(), value.trim().to_string())
+                } else if let Some((key, value)) = term.split_once('=') {
+                    LabelRequirement::Equals(key.trim().to_string(), value.trim().to_string())
+                } else if let Some(key) = term.strip_prefix('!') {
+                    LabelRequirement::NotExists(key.trim().to_string())
+                } else {
+                    LabelRequirement::Exists(term.to_string())
+                };
+
+                let key = match &requirement {
+                    LabelRequirement::Equals(key, _)
+                    | LabelRequirement::NotEquals(key, _)
+                    | LabelRequirement::Exists(key)
+                    | LabelRequirement::NotExists(key) => key,
+                };
+                if key.is_empty() || key.contains(['=', '!']) {
+                    return Err(invalid(term));
+                }
+                Ok(requirement)
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { requirements })
+    }
+}
+
+impl CasperClient {
+    /// List collections whose labels match `selector`, e.g. `"team=search,!deprecated"`.
+    ///
+    /// See [`LabelSelector`] for the syntax; an empty selector matches all
+    /// collections.
+    pub async fn list_collections_by_label(&self, selector: &str) -> Result<Vec<CollectionInfo>> {
+        let selector: LabelSelector = selector.parse()?;
+        let list = self.list_collections().await?;
+        Ok(list
+            .collections
+            .into_iter()
+            .filter(|info| selector.matches(&info.labels))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector() {
+        let selector: LabelSelector = "team=search, env!=prod, !deprecated, owner".parse().unwrap();
+        assert_eq!(selector.requirements.len(), 4);
+
+        let labels = |pairs: &[(&str, &str)]| -> Labels {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        assert!(selector.matches(&labels(&[("team", "search"), ("owner", "ann")])));
+        assert!(!selector.matches(&labels(&[("team", "search"), ("owner", "ann"), ("env", "prod")])));
+        assert!(!selector.matches(&labels(&[("team", "search"), ("owner", "ann"), ("deprecated", "")])));
+        assert!(!selector.matches(&labels(&[("team", "ads"), ("owner", "ann")])));
+
+        assert!("=x".parse::<LabelSelector>().is_err());
+        assert!("".parse::<LabelSelector>().unwrap().matches(&Labels::new()));
+    }
+}
@@ -1,10 +1,126 @@
+// Lets `#[derive(CasperRecord)]` name `::casper_client` inside this crate too
+extern crate self as casper_client;
+
+pub mod audit;
+pub mod auth;
+pub mod binary;
+mod batch_search;
+mod budget;
+pub mod builder;
+pub mod bulk;
+pub mod cache;
+pub mod cleanup;
 pub mod client;
+pub mod cluster;
+mod coalesce;
+pub mod collection;
+#[cfg(feature = "zstd")]
+pub mod compression;
+pub mod dedup;
+mod dimensions;
+pub mod duplicates;
+pub mod drift;
+pub mod encoding;
+pub mod endpoints;
 pub mod error;
+pub mod export;
+mod federated;
+pub mod filter;
+mod grouped;
+pub mod hedge;
+pub mod idmap;
+mod hybrid;
+mod include_vectors;
+mod index_wait;
+pub mod job;
+mod kernels;
+pub mod labels;
+pub mod limits;
+pub mod matrix;
+mod memory;
 pub mod models;
+pub mod namespace;
+mod normalize;
+pub mod outbox;
+pub mod paginate;
+pub mod preflight;
+pub mod presets;
+pub mod probe;
+pub mod progress;
+mod parallel;
+mod protect;
+mod purge;
+pub mod quantize;
+mod range;
+pub mod records;
+pub mod reduce;
+pub mod retry;
+mod scan;
+pub mod scores;
+pub mod service;
+pub mod session;
+pub mod signing;
+pub mod sizing;
+#[cfg(test)]
+mod testing;
+mod similar;
+pub mod slow;
+mod streaming;
+mod rng;
+pub mod timeouts;
+pub mod transport;
+mod truncate;
+pub mod upload;
+mod upsert;
+pub mod vector;
+pub mod version;
 
-pub use client::CasperClient;
+pub use audit::AuditEvent;
+pub use auth::Scope;
+pub use builder::CasperClientBuilder;
+pub use bulk::{BulkReport, DeadLetter};
+pub use cache::SearchCachePolicy;
+pub use cleanup::{MatrixPurge, MatrixPurgeReport, PqCascade, ResourceReport};
+pub use client::{CasperClient, DEFAULT_SEARCH_LIMIT};
+pub use collection::{CollectionHandle, IndexRequest, SearchBuilder};
+#[cfg(feature = "zstd")]
+pub use compression::Compression;
+pub use dedup::{DuplicatePair, DuplicateReport};
+pub use drift::{DistributionStats, DriftReport};
+pub use duplicates::DuplicatePolicy;
+pub use encoding::{FloatFormat, SearchEncoding};
+pub use endpoints::Endpoints;
 pub use error::{CasperError, Result};
+pub use export::{ExportChunk, ExportManifest, ExportMode};
+pub use filter::Filter;
+pub use hedge::HedgePolicy;
+pub use idmap::{ExternalId, IdMap, IdStore, KeyedCollection};
+pub use job::{Job, JobInfo, JobKind, JobStatus};
+pub use labels::LabelSelector;
+pub use limits::RequestLimits;
+pub use matrix::{Matrix, PqCodebook};
 pub use models::*;
+pub use outbox::{Outbox, OutboxEntry, OutboxReplay, WriteOp};
+pub use paginate::{Page, PageFuture, Paginated};
+pub use preflight::Preflight;
+pub use presets::CollectionPreset;
+pub use probe::{EndpointLatency, ProbePolicy};
+pub use progress::{Phase, Progress};
+pub use quantize::ScalarQuantizer;
+pub use casper_vdb_derive::CasperRecord;
+pub use records::{CasperRecord, Record};
+pub use reduce::DimReducer;
+pub use retry::RetryPolicy;
+pub use scores::ScoreScale;
+pub use service::{CasperService, HttpService};
+pub use session::Session;
+pub use signing::RequestSigner;
+pub use sizing::{HnswRecommendation, MemoryEstimate};
+pub use slow::SlowCall;
+pub use timeouts::Timeouts;
+pub use transport::HttpTransport;
+pub use upload::{MatrixDigest, UploadHandle};
+pub use version::ApiVersion;
 
 /// gRPC client types generated from `proto/matrix_service.proto`.
 pub mod grpc {
@@ -23,5 +139,9 @@ mod tests {
     async fn test_client_creation() {
         let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
         assert_eq!(client.base_url(), "http://localhost:8080/");
+
+        let from_url = CasperClient::from_url("http://localhost:8080").unwrap();
+        assert_eq!(from_url.endpoints(), client.endpoints());
+        assert_eq!(from_url.grpc_addr(), "http://localhost:50051");
     }
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+//! Local checks of request sizes against the server's limits.
+//!
+//! Servers answer oversized HTTP bodies with a bare 413 and reset gRPC
+//! streams carrying oversized messages; with [`RequestLimits`] configured,
+//! such requests fail before being sent with
+//! [`CasperError::RequestTooLarge`], saying how to split them.
+
+use crate::error::{CasperError, Result};
+use reqwest::Request;
+
+/// Largest HTTP body and gRPC message the server accepts (default
+/// unbounded). Servers running the default gRPC configuration take messages
+/// of up to 4 MiB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestLimits {
+    max_body_bytes: Option<usize>,
+    max_grpc_message_bytes: Option<usize>,
+}
+
+impl RequestLimits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest HTTP request body, as sent (after compression)
+    pub fn max_body_bytes(mut self, bytes: usize) -> Self {
+        self.max_body_bytes = Some(bytes);
+        self
+    }
+
+    /// Largest gRPC message, i.e. matrix upload chunk
+    pub fn max_grpc_message_bytes(mut self, bytes: usize) -> Self {
+        self.max_grpc_message_bytes = Some(bytes);
+        self
+    }
+
+    /// Reject `request` if its body is over the limit; streamed bodies are
+    /// not checked
+    pub(crate) fn check_body(&self, request: &Request) -> Result<()> {
+        let size = request.body().and_then(|body| body.as_bytes()).map_or(0, <[u8]>::len);
+        check(size, self.max_body_bytes, "split it into smaller requests, e.g. with insert_vectors_chunked")
+    }
+
+    /// Reject gRPC messages of up to `size` bytes if over the limit
+    pub(crate) fn check_grpc_message(&self, size: usize) -> Result<()> {
+        check(size, self.max_grpc_message_bytes, "upload in smaller chunks by lowering chunk_floats")
+    }
+}
+
+fn check(size: usize, limit: Option<usize>, remediation: &'static str) -> Result<()> {
+    match limit {
+        Some(limit) if size > limit => Err(CasperError::RequestTooLarge { size, limit, remediation }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BatchInsertOperation, BatchUpdateRequest};
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_oversized_requests() {
+        let scripted = Scripted::new([(200, "")]);
+        let client = crate::CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .request_limits(RequestLimits::new().max_body_bytes(4096).max_grpc_message_bytes(1024))
+            .build()
+            .unwrap();
+        let batch = |count: u32| BatchUpdateRequest {
+            insert: (0..count).map(|id| BatchInsertOperation { id, vector: vec![0.25; 8], payload: None }).collect(),
+            delete: Vec::new(),
+            upsert: false,
+        };
+
+        client.batch_update_results("docs", batch(10)).await.unwrap();
+        let oversized = client.batch_update_results("docs", batch(1000)).await;
+        assert!(matches!(oversized, Err(CasperError::RequestTooLarge { limit: 4096, .. })));
+        assert_eq!(scripted.requests().len(), 1);
+
+        let upload = client.upload_matrix("m", 4, vec![0.0; 4096], 1024).await;
+        assert!(matches!(upload, Err(CasperError::RequestTooLarge { limit: 1024, .. })));
+    }
+}
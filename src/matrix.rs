@@ -0,0 +1,150 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{CreatePqRequest, MatrixInfo, UploadMatrixResult};
+use crate::upload::{self, MatrixUpload};
+use crate::version::ApiVersion;
+use std::ops::Deref;
+
+/// Handle to a server-side matrix that knows its name and dimension, so rows
+/// are checked locally before anything is sent.
+///
+/// Returned by [`CasperClient::get_matrix_info`] and
+/// [`CasperClient::upload_matrix`]. It dereferences to the [`MatrixInfo`] it
+/// was created with; [`Matrix::info`] fetches the current one.
+#[derive(Debug, Clone)]
+pub struct Matrix {
+    client: CasperClient,
+    info: MatrixInfo,
+}
+
+/// A matrix used as one codebook of a PQ, see [`CreatePqRequest::from_codebooks`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PqCodebook {
+    pub name: String,
+    pub dim: usize,
+}
+
+impl Matrix {
+    pub(crate) fn new(client: CasperClient, info: MatrixInfo) -> Self {
+        Self { client, info }
+    }
+
+    /// Handle to a matrix `client` just uploaded
+    pub(crate) fn uploaded(client: CasperClient, name: &str, dim: usize, result: &UploadMatrixResult) -> Self {
+        let info = MatrixInfo {
+            name: name.to_string(),
+            dim,
+            len: result.total_vectors as usize,
+            enabled: true,
+            checksum: None,
+        };
+        Self { client, info }
+    }
+
+    /// Matrix name, without the client's namespace
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    /// Row dimension
+    pub fn dim(&self) -> usize {
+        self.info.dim
+    }
+
+    /// Current server-side info
+    pub async fn info(&self) -> Result<MatrixInfo> {
+        Ok(self.client.get_matrix_info(&self.info.name).await?.into_info())
+    }
+
+    /// Info the handle was created with
+    pub fn into_info(self) -> MatrixInfo {
+        self.info
+    }
+
+    /// Number of rows currently stored
+    pub async fn rows(&self) -> Result<usize> {
+        Ok(self.info().await?.len)
+    }
+
+    /// Append rows (flat, row-wise) to the matrix over gRPC.
+    ///
+    /// Fails before uploading if `vectors` is not a whole number of rows.
+    /// Requires API [`ApiVersion::V1_2`]: older servers would ignore the
+    /// append flag and replace the matrix, so they are refused up front.
+    pub async fn append(&self, vectors: Vec<f32>, chunk_floats: usize) -> Result<UploadMatrixResult> {
+        let (name, dim) = (self.name(), self.dim());
+        if dim == 0 || !vectors.len().is_multiple_of(dim) {
+            return Err(CasperError::InvalidArgument(format!(
+                "{} values do not make whole rows of matrix '{}' (dimension {})",
+                vectors.len(),
+                name,
+                dim
+            )));
+        }
+        self.client.check_writable("append_matrix")?;
+        self.client.ensure_api(ApiVersion::V1_2, "matrix append").await?;
+        let chunk_floats = self.client.upload_chunk_floats(chunk_floats, 1);
+        let upload = MatrixUpload::new(&self.client.qualify(name), dim, vectors, chunk_floats)?.appending();
+        upload::spawn(self.client.grpc_target(), upload, self.client.audit.clone()).await
+    }
+
+    /// Delete the matrix
+    pub async fn delete(self) -> Result<()> {
+        self.client.delete_matrix(&self.info.name).await
+    }
+
+    /// This matrix as a PQ codebook
+    pub fn as_pq_codebook(&self) -> PqCodebook {
+        PqCodebook { name: self.info.name.clone(), dim: self.info.dim }
+    }
+}
+
+impl Deref for Matrix {
+    type Target = MatrixInfo;
+
+    fn deref(&self) -> &MatrixInfo {
+        &self.info
+    }
+}
+
+impl CreatePqRequest {
+    /// PQ over `codebooks`, in order; its dimension is the sum of theirs
+    pub fn from_codebooks(codebooks: &[PqCodebook]) -> Result<Self> {
+        if codebooks.is_empty() {
+            return Err(CasperError::InvalidArgument("a PQ needs at least one codebook".to_string()));
+        }
+        Ok(Self {
+            dim: codebooks.iter().map(|codebook| codebook.dim).sum(),
+            codebooks: codebooks.iter().map(|codebook| codebook.name.clone()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_pq_from_codebooks() {
+        let codebook = |name: &str, dim| PqCodebook { name: name.to_string(), dim };
+        let request = CreatePqRequest::from_codebooks(&[codebook("cb0", 4), codebook("cb1", 2)]).unwrap();
+        assert_eq!(request.dim, 6);
+        assert_eq!(request.codebooks, vec!["cb0", "cb1"]);
+        assert!(CreatePqRequest::from_codebooks(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_append_needs_server_support() {
+        // A 1.1 server would ignore the append flag and replace the rows
+        let scripted = Scripted::new([(200, r#"{"name":"m","dim":2,"len":3,"enabled":true}"#)])
+            .header(crate::version::API_VERSION_HEADER, "1.1");
+        let matrix = scripted.client().get_matrix_info("m").await.unwrap();
+        assert_eq!((matrix.name(), matrix.len), ("m", 3));
+
+        let err = matrix.append(vec![0.0; 4], 4).await.unwrap_err();
+        assert!(matches!(err, CasperError::ApiVersionUnsupported { feature: "matrix append", .. }));
+        assert!(matches!(matrix.append(vec![0.0; 3], 4).await, Err(CasperError::InvalidArgument(_))));
+        assert_eq!(scripted.requests(), ["GET /matrix/m"]);
+    }
+}
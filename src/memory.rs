@@ -0,0 +1,48 @@
+//! Sizing of bulk operation buffers under the client's memory budget, see
+//! [`crate::CasperClientBuilder::memory_budget`].
+
+use crate::client::CasperClient;
+
+/// Matrix upload chunks buffered per stream: the producer channel's
+/// capacity, the chunk being produced and the one being encoded
+const UPLOAD_CHUNKS_IN_FLIGHT: usize = 6;
+
+/// Upper bound of the JSON text of one float
+pub(crate) const JSON_FLOAT_BYTES: usize = 16;
+
+impl CasperClient {
+    /// The largest item count up to `requested` for which `in_flight`
+    /// buffers of that many `item_bytes`-sized items fit in the memory
+    /// budget; at least 1, and `requested` without a budget
+    pub(crate) fn fit_budget(&self, requested: usize, item_bytes: usize, in_flight: usize) -> usize {
+        let Some(budget) = self.memory_budget else {
+            return requested;
+        };
+        let per_item = item_bytes.max(1).saturating_mul(in_flight.max(1));
+        requested.min(budget / per_item).max(1)
+    }
+
+    /// Floats per chunk of matrix uploads running `streams` at once; chunks
+    /// are still raised to at least one row by the upload
+    pub(crate) fn upload_chunk_floats(&self, chunk_floats: usize, streams: usize) -> usize {
+        self.fit_budget(chunk_floats, std::mem::size_of::<f32>(), UPLOAD_CHUNKS_IN_FLIGHT.saturating_mul(streams))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CasperClient;
+
+    #[test]
+    fn test_fit_budget() {
+        let unbounded = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        assert_eq!(unbounded.upload_chunk_floats(1 << 20, 4), 1 << 20);
+
+        let bounded = CasperClient::builder("http://localhost", 8080, 50051).memory_budget(24 << 20).build().unwrap();
+        // 24 MiB over 6 buffered chunks of 4-byte floats
+        assert_eq!(bounded.upload_chunk_floats(usize::MAX, 1), 1 << 20);
+        assert_eq!(bounded.upload_chunk_floats(usize::MAX, 4), 1 << 18);
+        assert_eq!(bounded.upload_chunk_floats(1_000, 1), 1_000);
+        assert_eq!(bounded.fit_budget(256, usize::MAX, 2), 1);
+    }
+}
@@ -1,16 +1,48 @@
+use crate::filter::Filter;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// Vector insertion request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InsertRequest {
     pub id: u32,
     pub vector: Vec<f32>,
+    /// JSON payload stored alongside the vector, e.g. a document ID, tags
+    /// or a timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
 }
 
 /// Vector insertion body (for JSON payload)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InsertVectorBody {
     pub vector: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Partial vector update: overwrite selected components of a stored vector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateComponentsRequest {
+    pub id: u32,
+    /// Component indices to overwrite
+    pub indices: Vec<usize>,
+    /// New values, one per index
+    pub values: Vec<f32>,
+}
+
+/// Partial vector update body (for JSON payload)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateComponentsBody {
+    pub indices: Vec<usize>,
+    pub values: Vec<f32>,
+}
+
+/// Replacement of the payload stored with a vector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePayloadRequest {
+    pub id: u32,
+    pub payload: serde_json::Value,
 }
 
 /// Vector deletion request
@@ -20,16 +52,165 @@ pub struct DeleteRequest {
 }
 
 /// Search request
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchRequest {
     pub vector: Vec<f32>,
     pub limit: Option<usize>,
+    /// Restrict results to vectors whose payload matches the filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+    /// Boost recent vectors by decaying scores with payload age
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<RecencyDecay>,
+    /// Server-side time budget in milliseconds: a server running out of time
+    /// answers with the best results found so far. Servers without budget
+    /// support ignore it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time_ms: Option<u64>,
+    /// HNSW search breadth for this query, overriding the server default:
+    /// higher values trade latency for recall
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef: Option<usize>,
+    /// IVF lists probed by this query, overriding
+    /// [`IvfPqIndexConfig::nprobe`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nprobe: Option<usize>,
+    /// Also return deleted vectors that were not purged yet; servers
+    /// without soft deletes ignore it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_deleted: bool,
+    /// Drop results scoring worse than this raw score: below it for inner
+    /// products and cosines, above it for L2 distances. Sent to the server
+    /// and applied again to the decoded results, for servers that ignore it;
+    /// see [`crate::ScoreScale`] and [`crate::SearchBuilder::min_score`] for
+    /// thresholds across metrics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_threshold: Option<f32>,
+    /// Drop results farther than this distance, as reported by
+    /// [`crate::ScoreScale::Distance`] for the collection's metric; applied
+    /// to the decoded results. Both cutoffs need the collection to have an
+    /// index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_distance: Option<f32>,
+}
+
+/// Search for every vector within `max_distance` of `vector`, see
+/// [`crate::CasperClient::stream_search_range`].
+///
+/// Distances are scores on [`crate::ScoreScale::Distance`]: the L2 distance, or
+/// `1 - s` for cosine and inner-product scores `s`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeSearchRequest {
+    pub vector: Vec<f32>,
+    pub max_distance: f32,
+    /// Restrict results to vectors whose payload matches the filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+}
+
+/// Sparse vector: the non-zero `values` at dimensions `indices`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SparseVector {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+impl SparseVector {
+    /// Sparse vector from its non-zero `(index, value)` entries
+    pub fn from_entries(entries: impl IntoIterator<Item = (u32, f32)>) -> Self {
+        let (indices, values) = entries.into_iter().unzip();
+        Self { indices, values }
+    }
+}
+
+/// How [`crate::CasperClient::hybrid_search`] combines dense and sparse
+/// results
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum FusionStrategy {
+    /// Reciprocal rank fusion: each result scores `1 / (k + rank)` per list
+    /// it appears in, ranks starting at 1; the usual `k` is 60
+    Rrf { k: f32 },
+    /// `dense_weight * dense + (1 - dense_weight) * sparse`, with the scores
+    /// of each list min-max scaled to `[0, 1]`; dense scores are first made
+    /// similarities ([`crate::ScoreScale::Similarity`]) by the collection's
+    /// metric, so L2 distances rank the right way round
+    WeightedSum { dense_weight: f32 },
+}
+
+impl Default for FusionStrategy {
+    fn default() -> Self {
+        FusionStrategy::Rrf { k: 60.0 }
+    }
+}
+
+/// Search by a dense and a sparse query at once
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HybridSearchRequest {
+    pub dense: Vec<f32>,
+    pub sparse: SparseVector,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub fusion: FusionStrategy,
+    /// Restrict results to vectors whose payload matches the filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
 }
 
 /// Search vector body (for JSON payload)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchVectorBody {
     pub vector: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<RecencyDecay>,
+}
+
+/// Shape of a recency decay curve.
+///
+/// Every shape keeps the full score at age 0 and halves it at an age of
+/// `half_life_secs`; they differ in how the factor falls around that point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayFunction {
+    /// `0.5^(age / half_life)`: halves again with every further half-life
+    #[default]
+    Exponential,
+    /// `max(0, 1 - age / (2 * half_life))`: falls at a constant rate and
+    /// reaches 0 at twice the half-life
+    Linear,
+    /// `0.5^((age / half_life)^2)`: stays close to the full score for recent
+    /// vectors, then falls faster than exponential decay
+    Gaussian,
+}
+
+/// Recency-decay scoring: a result's score is multiplied by a factor that
+/// falls with its age along `function`, reaching 0.5 at `half_life_secs`.
+/// Age is measured between the timestamp payload `field` (unix seconds) and
+/// `origin`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecencyDecay {
+    /// Payload field holding the vector's timestamp (unix seconds)
+    pub field: String,
+    pub half_life_secs: f64,
+    /// Reference time in unix seconds; the server's current time if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<i64>,
+    #[serde(default)]
+    pub function: DecayFunction,
+}
+
+impl RecencyDecay {
+    /// Exponential decay on `field` with the given half-life
+    pub fn exponential(field: impl Into<String>, half_life: std::time::Duration) -> Self {
+        Self {
+            field: field.into(),
+            half_life_secs: half_life.as_secs_f64(),
+            origin: None,
+            function: DecayFunction::Exponential,
+        }
+    }
 }
 
 /// Search result item (tuple format: [id, score])
@@ -39,14 +220,143 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Search result of [`crate::CasperClient::search_multi`], with the
+/// collection it was found in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSearchResult {
+    pub collection: String,
+    pub id: u32,
+    pub score: f32,
+}
+
+/// Results of [`crate::CasperClient::search_grouped`], best group first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedSearchResponse {
+    pub groups: Vec<SearchGroup>,
+}
+
+/// Hits sharing a value of the grouping field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchGroup {
+    /// Value of the grouping field
+    pub key: serde_json::Value,
+    /// Best hits of the group, best first
+    pub hits: SearchResponse,
+    pub best_score: f32,
+}
+
+/// Search result with the matched vector, as stored, see
+/// [`crate::CasperClient::search_with_vectors`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResultWithVector {
+    pub id: u32,
+    pub score: f32,
+    pub vector: Vec<f32>,
+}
+
+/// Recommendation request body: example IDs to move towards and away from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendBody {
+    pub positive: Vec<u32>,
+    pub negative: Vec<u32>,
+}
+
+/// Existence check request body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainsIdsBody {
+    pub ids: Vec<u32>,
+}
+
+/// Existence check response: one flag per requested ID, in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainsIdsResponse {
+    pub exists: Vec<bool>,
+}
+
+/// Vector count response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountVectorsResponse {
+    pub count: usize,
+}
+
+/// Purge response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeResponse {
+    /// Deleted vectors physically removed
+    #[serde(default)]
+    pub purged: usize,
+}
+
 /// Search response (array of [id, score] tuples)
 pub type SearchResponse = Vec<SearchResult>;
 
-/// Collection creation request
+/// Server-side diagnostics of one search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchExplain {
+    /// Graph nodes (or list entries) visited
+    #[serde(default)]
+    pub nodes_visited: u64,
+    #[serde(default)]
+    pub distance_computations: u64,
+    /// Time spent in each search phase, in microseconds
+    #[serde(default)]
+    pub phase_micros: BTreeMap<String, u64>,
+}
+
+/// Search results with diagnostics, see [`crate::CasperClient::search_explain`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedSearch {
+    pub results: SearchResponse,
+    pub explain: SearchExplain,
+    /// Server-assigned query ID, see [`crate::CasperClient::get_query_profile`]
+    #[serde(default)]
+    pub query_id: Option<String>,
+}
+
+/// Search results with the server-assigned query ID, if any
+#[derive(Debug, Clone)]
+pub struct SearchOutcome {
+    pub results: SearchResponse,
+    pub query_id: Option<String>,
+}
+
+/// Profile of a past query, see [`crate::CasperClient::get_query_profile`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryProfile {
+    pub query_id: String,
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Total server-side time, in microseconds
+    pub duration_micros: u64,
+    #[serde(default)]
+    pub explain: SearchExplain,
+}
+
+/// Key/value labels attached to a collection (owner, environment, ...)
+pub type Labels = BTreeMap<String, String>;
+
+/// Collection creation request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateCollectionRequest {
     pub dim: usize,
     pub max_size: u32,
+    /// Labels to attach to the new collection
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: Labels,
+}
+
+/// Label body for collection creation and label updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionLabelsBody {
+    pub labels: Labels,
+}
+
+/// Mutability toggle body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetMutableBody {
+    pub mutable: bool,
 }
 
 /// Collection information
@@ -59,7 +369,14 @@ pub struct CollectionInfo {
     pub max_size: u32,
     /// Current number of vectors in the collection
     pub size: usize,
+    /// Deleted vectors still taking up space until purged, see
+    /// [`CasperClient::purge_deleted`](crate::CasperClient::purge_deleted);
+    /// 0 on servers that delete right away
+    #[serde(default)]
+    pub deleted: usize,
     pub index: Option<IndexInfo>,
+    #[serde(default)]
+    pub labels: Labels,
 }
 
 /// Index information
@@ -67,6 +384,9 @@ pub struct CollectionInfo {
 pub struct IndexInfo {
     /// HNSW index configuration (if present)
     pub hnsw: Option<HNSWIndexConfig>,
+    /// IVF-PQ index configuration (if present)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ivf_pq: Option<IvfPqIndexConfig>,
     /// Whether normalization is applied for this index
     pub normalization: bool,
 }
@@ -76,6 +396,10 @@ pub struct IndexInfo {
 pub struct BatchInsertOperation {
     pub id: u32,
     pub vector: Vec<f32>,
+    /// JSON payload stored alongside the vector, e.g. a document ID, tags
+    /// or a timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
 }
 
 /// Batch update request
@@ -83,6 +407,121 @@ pub struct BatchInsertOperation {
 pub struct BatchUpdateRequest {
     pub insert: Vec<BatchInsertOperation>,
     pub delete: Vec<u32>,
+    /// Overwrite inserted IDs that already exist instead of rejecting them,
+    /// see [`CasperClient::upsert_vector`](crate::CasperClient::upsert_vector)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub upsert: bool,
+}
+
+/// Insert of an i8-quantized vector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedInsertOperation {
+    pub id: u32,
+    pub vector: Vec<i8>,
+}
+
+/// Batch insert of i8-quantized vectors (sent with `quantization=i8`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedBatchInsertRequest {
+    pub insert: Vec<QuantizedInsertOperation>,
+}
+
+/// Insert of a packed binary vector (MSB-first, see [`crate::binary::pack_bits`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryInsertOperation {
+    pub id: u32,
+    pub bits: Vec<u8>,
+}
+
+/// Batch insert of packed binary vectors (sent with `format=binary`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryBatchInsertRequest {
+    pub insert: Vec<BinaryInsertOperation>,
+}
+
+/// Binary search body (for JSON payload)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinarySearchBody {
+    pub bits: Vec<u8>,
+}
+
+/// Hamming search result item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinarySearchResult {
+    pub id: u32,
+    /// Number of differing bits
+    pub distance: u32,
+}
+
+/// Status of a single operation in a batch update response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOperationStatus {
+    pub id: u32,
+    /// Failure reason; absent when the operation succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Batch update response, for servers reporting per-operation statuses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchUpdateResponse {
+    #[serde(default)]
+    pub results: Vec<BatchOperationStatus>,
+}
+
+/// Per-item outcome of a batch update
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchResult {
+    /// IDs of inserts and deletes that were applied
+    pub succeeded: Vec<u32>,
+    /// IDs that were rejected, with the server's reason
+    pub failed: Vec<(u32, String)>,
+    /// IDs of inserts left out because the ID exists, see
+    /// [`DuplicatePolicy::Skip`](crate::DuplicatePolicy::Skip)
+    pub skipped: Vec<u32>,
+    /// IDs queued in the outbox while the server could not be reached; they
+    /// are applied when it is replayed
+    pub pending: Vec<u32>,
+}
+
+impl BatchResult {
+    /// Build the result for `request_ids` from a server response.
+    ///
+    /// IDs the server does not mention are considered applied, so an empty
+    /// response means every operation succeeded.
+    pub fn from_response(request_ids: impl IntoIterator<Item = u32>, response: &BatchUpdateResponse) -> Self {
+        let failures: HashMap<u32, &String> = response
+            .results
+            .iter()
+            .filter_map(|status| Some((status.id, status.error.as_ref()?)))
+            .collect();
+        let mut result = BatchResult::default();
+        for id in request_ids {
+            match failures.get(&id) {
+                Some(reason) => result.failed.push((id, reason.to_string())),
+                None => result.succeeded.push(id),
+            }
+        }
+        result
+    }
+
+    /// Whether every operation was applied
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Failure reason for `id`, if it was rejected
+    pub fn failure(&self, id: u32) -> Option<&str> {
+        self.failed.iter().find(|(failed, _)| *failed == id).map(|(_, reason)| reason.as_str())
+    }
+
+    /// Add the outcomes of another batch, e.g. the next chunk of a bulk call
+    pub fn merge(&mut self, other: BatchResult) {
+        self.succeeded.extend(other.succeeded);
+        self.failed.extend(other.failed);
+        self.skipped.extend(other.skipped);
+        self.pending.extend(other.pending);
+    }
 }
 
 /// Index creation request for HNSW
@@ -113,6 +552,30 @@ pub struct HNSWIndexConfig {
     pub pq_name: Option<String>,
 }
 
+/// Index creation request for IVF with PQ-encoded residuals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateIvfPqIndexRequest {
+    /// IVF-PQ index configuration
+    pub ivf_pq: IvfPqIndexConfig,
+    /// Whether to apply vector normalization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<bool>,
+}
+
+/// IVF-PQ index configuration: vectors are assigned to the nearest of
+/// `nlist` coarse centroids, and their residuals are PQ-encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IvfPqIndexConfig {
+    /// Distance metric, e.g. "inner-product"
+    pub metric: String,
+    /// Number of coarse clusters
+    pub nlist: usize,
+    /// Default number of clusters probed per search (at most `nlist`)
+    pub nprobe: usize,
+    /// PQ encoding the residuals, see [`crate::CasperClient::create_pq`]
+    pub pq_name: String,
+}
+
 /// Collections list response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionsListResponse {
@@ -124,6 +587,9 @@ pub struct CollectionsListResponse {
 pub struct GetVectorResponse {
     pub id: u32,
     pub vector: Vec<f32>,
+    /// JSON payload stored with the vector, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
 }
 
 /// Matrix information (from /matrix APIs)
@@ -133,6 +599,10 @@ pub struct MatrixInfo {
     pub dim: usize,
     pub len: usize,
     pub enabled: bool,
+    /// Server-computed checksum of the matrix data, if the server reports one
+    /// (see [`crate::upload::MatrixDigest`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
 /// Result of gRPC matrix upload
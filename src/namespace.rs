@@ -0,0 +1,143 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{
+    CollectionInfo, CreateHNSWIndexRequest, CreateIvfPqIndexRequest, CreatePqRequest, MatrixInfo, PqInfo,
+};
+use std::sync::Arc;
+use url::Url;
+
+/// Separator between namespace and name in server-side names
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+impl CasperClient {
+    /// A copy of this client scoped to `namespace`.
+    ///
+    /// Collection, matrix and PQ names are transparently stored on the server
+    /// as `"<namespace>.<name>"` and stripped again in responses; listings
+    /// only show entries of the namespace. This gives simple tenant isolation
+    /// on a shared deployment.
+    pub fn with_namespace(&self, namespace: &str) -> CasperClient {
+        CasperClient { namespace: Some(Arc::from(namespace)), ..self.clone() }
+    }
+
+    /// Namespace the client is scoped to, if any
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Server-side name of a collection, matrix or PQ
+    pub(crate) fn qualify(&self, name: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Client-side name of a server-side name, `None` if outside the namespace
+    pub(crate) fn unqualify(&self, name: &str) -> Option<String> {
+        match &self.namespace {
+            Some(namespace) => name
+                .strip_prefix(namespace.as_ref())
+                .and_then(|rest| rest.strip_prefix(NAMESPACE_SEPARATOR))
+                .map(str::to_string),
+            None => Some(name.to_string()),
+        }
+    }
+
+    /// `collection/<name><path>` URL for a collection endpoint
+    pub(crate) fn collection_url(&self, collection_name: &str, path: &str) -> Result<Url> {
+        Ok(self
+            .base_url
+            .join(&format!("collection/{}{}", self.qualify(collection_name), path))?)
+    }
+
+    /// `matrix/<name>` URL
+    pub(crate) fn matrix_url(&self, name: &str) -> Result<Url> {
+        Ok(self.base_url.join(&format!("matrix/{}", self.qualify(name)))?)
+    }
+
+    /// `pq/<name>` URL
+    pub(crate) fn pq_url(&self, name: &str) -> Result<Url> {
+        Ok(self.base_url.join(&format!("pq/{}", self.qualify(name)))?)
+    }
+
+    /// URL of `segments` below the base URL, each percent-encoded as a
+    /// single path segment, for IDs that may contain `/`, `?` or `#`
+    pub(crate) fn segments_url(&self, segments: &[&str]) -> Result<Url> {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| CasperError::Url(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    pub(crate) fn qualify_index(&self, mut request: CreateHNSWIndexRequest) -> CreateHNSWIndexRequest {
+        request.hnsw.pq_name = request.hnsw.pq_name.map(|pq| self.qualify(&pq));
+        request
+    }
+
+    pub(crate) fn qualify_ivf_pq(&self, mut request: CreateIvfPqIndexRequest) -> CreateIvfPqIndexRequest {
+        request.ivf_pq.pq_name = self.qualify(&request.ivf_pq.pq_name);
+        request
+    }
+
+    pub(crate) fn qualify_pq(&self, mut request: CreatePqRequest) -> CreatePqRequest {
+        request.codebooks = request.codebooks.iter().map(|m| self.qualify(m)).collect();
+        request
+    }
+
+    /// Strip the namespace from collection info, `None` if outside it
+    pub(crate) fn localize_collection(&self, mut info: CollectionInfo) -> Option<CollectionInfo> {
+        info.name = self.unqualify(&info.name)?;
+        if let Some(hnsw) = info.index.as_mut().and_then(|index| index.hnsw.as_mut())
+            && let Some(pq) = &hnsw.pq_name
+        {
+            hnsw.pq_name = Some(self.unqualify(pq).unwrap_or_else(|| pq.clone()));
+        }
+        if let Some(ivf_pq) = info.index.as_mut().and_then(|index| index.ivf_pq.as_mut())
+            && let Some(pq) = self.unqualify(&ivf_pq.pq_name)
+        {
+            ivf_pq.pq_name = pq;
+        }
+        Some(info)
+    }
+
+    /// Strip the namespace from matrix info, `None` if outside it
+    pub(crate) fn localize_matrix(&self, mut info: MatrixInfo) -> Option<MatrixInfo> {
+        info.name = self.unqualify(&info.name)?;
+        Some(info)
+    }
+
+    /// Strip the namespace from PQ info, `None` if outside it
+    pub(crate) fn localize_pq(&self, mut info: PqInfo) -> Option<PqInfo> {
+        info.name = self.unqualify(&info.name)?;
+        info.codebooks = info
+            .codebooks
+            .iter()
+            .map(|m| self.unqualify(m).unwrap_or_else(|| m.clone()))
+            .collect();
+        Some(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualify_round_trip() {
+        let client = CasperClient::new("http://localhost", 8080, 50051).unwrap();
+        assert_eq!(client.qualify("docs"), "docs");
+
+        let tenant = client.with_namespace("tenant-a");
+        assert_eq!(tenant.qualify("docs"), "tenant-a.docs");
+        assert_eq!(tenant.unqualify("tenant-a.docs").as_deref(), Some("docs"));
+        assert_eq!(tenant.unqualify("tenant-ab.docs"), None);
+        assert_eq!(tenant.unqualify("docs"), None);
+        assert_eq!(
+            tenant.collection_url("docs", "/search").unwrap().as_str(),
+            "http://localhost:8080/collection/tenant-a.docs/search"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::client::CasperClient;
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Which collections have an index normalizing its vectors, learned from
+/// collection info and shared by all clones of a client.
+///
+/// Only consulted when auto-normalization is enabled, see
+/// [`crate::CasperClientBuilder::auto_normalize`].
+#[derive(Debug, Default)]
+pub(crate) struct Normalization {
+    enabled: bool,
+    indexes: RwLock<HashMap<String, bool>>,
+}
+
+impl Normalization {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self { enabled, ..Default::default() }
+    }
+
+    fn cached(&self, collection_name: &str) -> Option<bool> {
+        self.indexes.read().expect("normalization cache poisoned").get(collection_name).copied()
+    }
+
+    fn remember(&self, collection_name: &str, normalizes: bool) {
+        self.indexes
+            .write()
+            .expect("normalization cache poisoned")
+            .insert(collection_name.to_string(), normalizes);
+    }
+
+    /// Drop what is known about a collection whose index changed
+    pub(crate) fn forget(&self, collection_name: &str) {
+        self.indexes.write().expect("normalization cache poisoned").remove(collection_name);
+    }
+}
+
+impl CasperClient {
+    /// Whether vectors sent to a collection should be normalized client-side:
+    /// auto-normalization is enabled and the collection's index was created
+    /// with `normalization: true`.
+    ///
+    /// The collection info is fetched on first use and cached until the
+    /// client changes the collection's index.
+    pub(crate) async fn normalizes(&self, collection_name: &str) -> Result<bool> {
+        if !self.normalization.enabled {
+            return Ok(false);
+        }
+        if let Some(normalizes) = self.normalization.cached(collection_name) {
+            return Ok(normalizes);
+        }
+        let info = self.get_collection(collection_name).await?;
+        let normalizes = info.index.is_some_and(|index| index.normalization);
+        self.normalization.remember(collection_name, normalizes);
+        Ok(normalizes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_cache() {
+        let cache = Normalization::new(true);
+        assert_eq!(cache.cached("docs"), None);
+        cache.remember("docs", true);
+        assert_eq!(cache.cached("docs"), Some(true));
+        cache.forget("docs");
+        assert_eq!(cache.cached("docs"), None);
+    }
+}
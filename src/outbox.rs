@@ -0,0 +1,341 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::parallel;
+use crate::models::{
+    BatchUpdateRequest, BinaryBatchInsertRequest, DeleteRequest, InsertRequest, QuantizedBatchInsertRequest,
+    UpdateComponentsRequest, UpdatePayloadRequest,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// A write operation that can be queued in an [`Outbox`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WriteOp {
+    Insert(InsertRequest),
+    Upsert(InsertRequest),
+    UpdateComponents(UpdateComponentsRequest),
+    UpdatePayload(UpdatePayloadRequest),
+    Delete(DeleteRequest),
+    BatchUpdate(BatchUpdateRequest),
+    InsertQuantized(QuantizedBatchInsertRequest),
+    InsertBinary(BinaryBatchInsertRequest),
+}
+
+impl WriteOp {
+    /// Operation name as used in the outbox and audit events
+    pub fn name(&self) -> &'static str {
+        match self {
+            WriteOp::Insert(_) => "insert",
+            WriteOp::Upsert(_) => "upsert",
+            WriteOp::UpdateComponents(_) => "update_components",
+            WriteOp::UpdatePayload(_) => "update_payload",
+            WriteOp::Delete(_) => "delete",
+            WriteOp::BatchUpdate(_) => "batch_update",
+            WriteOp::InsertQuantized(_) => "insert_quantized",
+            WriteOp::InsertBinary(_) => "insert_binary",
+        }
+    }
+
+    /// Number of vectors the operation touches
+    pub fn item_count(&self) -> usize {
+        match self {
+            WriteOp::Insert(_)
+            | WriteOp::Upsert(_)
+            | WriteOp::UpdateComponents(_)
+            | WriteOp::UpdatePayload(_)
+            | WriteOp::Delete(_) => 1,
+            WriteOp::BatchUpdate(request) => request.insert.len() + request.delete.len(),
+            WriteOp::InsertQuantized(request) => request.insert.len(),
+            WriteOp::InsertBinary(request) => request.insert.len(),
+        }
+    }
+
+    /// Apply `f` to every full vector carried by the operation.
+    ///
+    /// Partial component and payload updates, quantized and binary inserts,
+    /// and deletes are left untouched.
+    /// Batches are processed in parallel when large enough, see
+    /// [`parallel::batch_try_for_each`].
+    pub(crate) fn map_vectors<F>(self, f: F) -> Result<WriteOp>
+    where
+        F: Fn(Vec<f32>) -> Result<Vec<f32>> + Sync + Send,
+    {
+        Ok(match self {
+            WriteOp::Insert(mut request) => {
+                request.vector = f(request.vector)?;
+                WriteOp::Insert(request)
+            }
+            WriteOp::Upsert(mut request) => {
+                request.vector = f(request.vector)?;
+                WriteOp::Upsert(request)
+            }
+            WriteOp::BatchUpdate(mut request) => {
+                parallel::batch_try_for_each(&mut request.insert, |op| {
+                    op.vector = f(std::mem::take(&mut op.vector))?;
+                    Ok(())
+                })?;
+                WriteOp::BatchUpdate(request)
+            }
+            op @ (WriteOp::UpdateComponents(_)
+            | WriteOp::UpdatePayload(_)
+            | WriteOp::Delete(_)
+            | WriteOp::InsertQuantized(_)
+            | WriteOp::InsertBinary(_)) => op,
+        })
+    }
+}
+
+/// A queued write
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Sequence number, increasing in enqueue order for as long as the
+    /// outbox is open; an outbox reopened empty starts again from 0
+    pub seq: u64,
+    pub collection: String,
+    #[serde(flatten)]
+    pub op: WriteOp,
+}
+
+/// Outcome of replaying an outbox
+#[derive(Debug, Default)]
+pub struct OutboxReplay {
+    /// Entries delivered to the server
+    pub delivered: usize,
+    /// Entries the server refused; they are removed from the outbox
+    pub rejected: Vec<(OutboxEntry, CasperError)>,
+    /// Entries still queued because the server became unreachable again
+    pub remaining: usize,
+}
+
+/// File-backed queue of writes made while the server could not be connected
+/// to.
+///
+/// Only writes that failed to connect are queued: after a timeout the server
+/// may already have applied the write, so that error is returned instead.
+///
+/// Entries are stored as JSON lines and replayed in order once the server is
+/// reachable again, either explicitly through [`CasperClient::flush_outbox`] or
+/// implicitly before the next write. An entry is only removed from the file
+/// after the server accepted it, so delivery is at-least-once: a crash between
+/// delivery and compaction replays the entry again.
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    entries: Mutex<VecDeque<OutboxEntry>>,
+    next_seq: AtomicU64,
+}
+
+impl Outbox {
+    /// Open (or create) an outbox stored at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(text) => text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<std::result::Result<VecDeque<_>, _>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let next_seq = entries.back().map_or(0, |entry: &OutboxEntry| entry.seq + 1);
+        Ok(Self { path, entries: Mutex::new(entries), next_seq: AtomicU64::new(next_seq) })
+    }
+
+    /// Path of the backing file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of queued writes
+    pub async fn len(&self) -> usize {
+        self.entries.lock().await.len()
+    }
+
+    /// Whether no writes are queued
+    pub async fn is_empty(&self) -> bool {
+        self.entries.lock().await.is_empty()
+    }
+
+    /// Snapshot of the queued writes, oldest first
+    pub async fn pending(&self) -> Vec<OutboxEntry> {
+        self.entries.lock().await.iter().cloned().collect()
+    }
+
+    /// Durably append a write to the queue
+    pub async fn push(&self, collection: &str, op: WriteOp) -> Result<()> {
+        let mut entries = self.entries.lock().await;
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let entry = OutboxEntry { seq, collection: collection.to_string(), op };
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+
+        entries.push_back(entry);
+        Ok(())
+    }
+
+    /// Replay queued writes in order through `client`.
+    ///
+    /// Stops at the first entry that fails because the server is unreachable.
+    pub(crate) async fn replay(&self, client: &CasperClient) -> Result<OutboxReplay> {
+        let mut entries = self.entries.lock().await;
+        let mut replay = OutboxReplay::default();
+
+        while let Some(entry) = entries.front() {
+            match client.send_write(&entry.collection, entry.op.clone()).await {
+                Ok(_) => replay.delivered += 1,
+                Err(e) if e.is_unreachable() => break,
+                Err(e) => replay.rejected.push((entry.clone(), e)),
+            }
+            entries.pop_front();
+        }
+        replay.remaining = entries.len();
+
+        if replay.delivered > 0 || !replay.rejected.is_empty() {
+            self.rewrite(&entries).await?;
+        }
+        Ok(replay)
+    }
+
+    /// Atomically replace the backing file with `entries`
+    async fn rewrite(&self, entries: &VecDeque<OutboxEntry>) -> Result<()> {
+        let mut buf = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut buf, entry)?;
+            buf.push(b'\n');
+        }
+
+        let tmp = self.path.with_extension("tmp");
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        file.write_all(&buf).await?;
+        file.sync_data().await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
+
+impl CasperClient {
+    /// Replay writes queued in the outbox, if one is configured
+    pub async fn flush_outbox(&self) -> Result<OutboxReplay> {
+        self.check_writable("flush_outbox")?;
+        match &self.outbox {
+            Some(outbox) => {
+                let pending = outbox.len().await;
+                self.audited("flush_outbox", "*", pending, outbox.replay(self)).await
+            }
+            None => Ok(OutboxReplay::default()),
+        }
+    }
+
+    /// Apply a write, queueing it in the outbox if no connection to the server
+    /// can be made.
+    ///
+    /// Queued writes are replayed first so that ordering is preserved. Returns
+    /// the response body, or `None` if the write ended up queued.
+    pub(crate) async fn write(&self, collection_name: &str, op: WriteOp) -> Result<Option<String>> {
+        let (operation, items) = (op.name(), op.item_count());
+        self.audited(operation, collection_name, items, self.write_unaudited(collection_name, op))
+            .await
+    }
+
+    async fn write_unaudited(&self, collection_name: &str, op: WriteOp) -> Result<Option<String>> {
+        self.check_writable(op.name())?;
+        let op = self.prepare_write(collection_name, op).await?;
+        let Some(outbox) = &self.outbox else {
+            return self.send_write(collection_name, op).await.map(Some);
+        };
+
+        if !outbox.is_empty().await && outbox.replay(self).await?.remaining > 0 {
+            outbox.push(collection_name, op).await?;
+            return Ok(None);
+        }
+
+        match self.send_write(collection_name, op.clone()).await {
+            Err(e) if e.is_connect_failure() => {
+                outbox.push(collection_name, op).await?;
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+    use std::io::ErrorKind;
+
+    #[tokio::test]
+    async fn test_outbox_persists_across_reopen() {
+        let path = std::env::temp_dir().join(format!("casper-outbox-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let outbox = Outbox::open(&path).unwrap();
+        outbox
+            .push("docs", WriteOp::Insert(InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None }))
+            .await
+            .unwrap();
+        outbox.push("docs", WriteOp::Delete(DeleteRequest { id: 2 })).await.unwrap();
+        drop(outbox);
+
+        let reopened = Outbox::open(&path).unwrap();
+        let pending = reopened.pending().await;
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[1].seq, 1);
+        assert!(matches!(pending[0].op, WriteOp::Insert(ref r) if r.id == 1));
+        assert!(matches!(pending[1].op, WriteOp::Delete(ref r) if r.id == 2));
+
+        // Draining the queue does not reset the sequence
+        let scripted = Scripted::new([(200, ""), (200, "")]);
+        assert_eq!(reopened.replay(&scripted.client()).await.unwrap().delivered, 2);
+        reopened.push("docs", WriteOp::Delete(DeleteRequest { id: 3 })).await.unwrap();
+        assert_eq!(reopened.pending().await[0].seq, 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_only_connect_failures_are_queued() {
+        let path = std::env::temp_dir().join(format!("casper-outbox-queue-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let scripted = Scripted::default()
+            .fail(ErrorKind::TimedOut)
+            .fail(ErrorKind::ConnectionRefused)
+            .fail(ErrorKind::ConnectionRefused);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted)
+            .outbox(Outbox::open(&path).unwrap())
+            .build()
+            .unwrap();
+        let outbox = client.outbox.clone().unwrap();
+
+        // The server may have applied a write that timed out
+        assert!(client.delete_vector("docs", DeleteRequest { id: 1 }).await.is_err());
+        assert!(outbox.is_empty().await);
+
+        client.delete_vector("docs", DeleteRequest { id: 2 }).await.unwrap();
+        assert!(matches!(outbox.pending().await[0].op, WriteOp::Delete(ref r) if r.id == 2));
+
+        // Queued behind it, a batch is reported as pending rather than applied
+        let batch = BatchUpdateRequest { insert: Vec::new(), delete: vec![3, 4], upsert: false };
+        let result = client.batch_update_results("docs", batch).await.unwrap();
+        assert_eq!((result.pending, result.succeeded.len()), (vec![3, 4], 0));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use crate::auth::Scope;
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::labels::LabelSelector;
+use crate::models::{CollectionInfo, CollectionsListResponse, MatrixInfo, PqInfo};
+use crate::client::Dispatch;
+use crate::version::ApiVersion;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::convert::identity;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio_stream::{Stream, StreamExt};
+
+/// Future of one page, returned by the fetcher of a [`Paginated`] stream
+pub type PageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<Page<T>>> + Send + 'a>>;
+
+/// One page of a listing or scroll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Where the next page starts (an offset or an ID, as the fetcher
+    /// defines it), `None` on the last page
+    pub next: Option<u64>,
+}
+
+impl<T> Page<T> {
+    /// The only page of a listing that is not paginated
+    pub fn last(items: Vec<T>) -> Self {
+        Self { items, next: None }
+    }
+}
+
+/// Items asked for per page by the `stream_*` listings
+const LIST_PAGE_SIZE: usize = 256;
+
+type Fetch<'a, T> = Box<dyn FnMut(Option<u64>) -> PageFuture<'a, T> + Send + 'a>;
+
+/// Stream of the items of a paginated listing, fetching a page at a time.
+///
+/// Every list and scroll API has a `stream_*` form returning one, so all of
+/// them are consumed the same way:
+///
+/// ```no_run
+/// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+/// use tokio_stream::StreamExt;
+///
+/// let mut collections = client.stream_collections();
+/// while let Some(info) = collections.next().await {
+///     println!("{}", info?.name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A failed page is yielded as an error and ends the stream.
+pub struct Paginated<'a, T> {
+    fetch: Fetch<'a, T>,
+    pending: Option<PageFuture<'a, T>>,
+    buffered: VecDeque<T>,
+    cursor: Option<u64>,
+    done: bool,
+}
+
+impl<'a, T> Paginated<'a, T> {
+    /// Stream the pages returned by `fetch`, called with `None` for the first
+    /// page and then with the `next` cursor of the previous page
+    pub fn new<F>(fetch: F) -> Self
+    where
+        F: FnMut(Option<u64>) -> PageFuture<'a, T> + Send + 'a,
+    {
+        Self { fetch: Box::new(fetch), pending: None, buffered: VecDeque::new(), cursor: None, done: false }
+    }
+
+    /// Stream the items of a listing fetched in one request
+    pub fn once<F>(list: F) -> Self
+    where
+        F: Future<Output = Result<Vec<T>>> + Send + 'a,
+        T: 'a,
+    {
+        let mut list = Some(list);
+        Self::new(move |_| {
+            let list = list.take().expect("single page fetched twice");
+            Box::pin(async move { Ok(Page::last(list.await?)) })
+        })
+    }
+
+    /// Collect all remaining items
+    pub async fn collect_all(mut self) -> Result<Vec<T>>
+    where
+        T: Unpin,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Unpin> Stream for Paginated<'_, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.buffered.pop_front() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if let Some(pending) = this.pending.as_mut() {
+                let page = ready!(pending.as_mut().poll(cx));
+                this.pending = None;
+                match page {
+                    Ok(page) => {
+                        this.buffered.extend(page.items);
+                        this.cursor = page.next;
+                        this.done = page.next.is_none();
+                    }
+                    Err(e) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                continue;
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            this.pending = Some((this.fetch)(this.cursor));
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Paginated<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginated")
+            .field("buffered", &self.buffered.len())
+            .field("cursor", &self.cursor)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl CasperClient {
+    /// [`CasperClient::list_collections`] as a [`Paginated`] stream
+    pub fn stream_collections(&self) -> Paginated<'_, CollectionInfo> {
+        Paginated::new(move |cursor| {
+            Box::pin(self.list_page("collections", cursor, collections, Self::localize_collection))
+        })
+    }
+
+    /// [`CasperClient::list_collections_by_label`] as a [`Paginated`] stream
+    pub fn stream_collections_by_label<'a>(&'a self, selector: &'a str) -> Paginated<'a, CollectionInfo> {
+        Paginated::new(move |cursor| {
+            Box::pin(async move {
+                let selector: LabelSelector = selector.parse()?;
+                let mut page = self.list_page("collections", cursor, collections, Self::localize_collection).await?;
+                page.items.retain(|info| selector.matches(&info.labels));
+                Ok(page)
+            })
+        })
+    }
+
+    /// [`CasperClient::list_matrices`] as a [`Paginated`] stream
+    pub fn stream_matrices(&self) -> Paginated<'_, MatrixInfo> {
+        Paginated::new(move |cursor| Box::pin(self.list_page("matrix/list", cursor, identity, Self::localize_matrix)))
+    }
+
+    /// [`CasperClient::list_pqs`] as a [`Paginated`] stream
+    pub fn stream_pqs(&self) -> Paginated<'_, PqInfo> {
+        Paginated::new(move |cursor| Box::pin(self.list_page("pq/list", cursor, identity, Self::localize_pq)))
+    }
+
+    /// Page of the listing at `path` starting at offset `cursor`.
+    ///
+    /// Listings page with `cursor` and `limit` from API 1.2. A server that
+    /// answers with more items than asked for, or advertises an older
+    /// version, lists everything at once.
+    async fn list_page<L, T>(
+        &self,
+        path: &str,
+        cursor: Option<u64>,
+        items: fn(L) -> Vec<T>,
+        localize: fn(&Self, T) -> Option<T>,
+    ) -> Result<Page<T>>
+    where
+        L: DeserializeOwned,
+    {
+        let url = self.base_url.join(path)?;
+        let offset = cursor.unwrap_or(0);
+        let mut request = self.http(Scope::Read, Method::GET, url);
+        if self.supports_api(ApiVersion::V1_2) {
+            request = request.query(&[("cursor", offset), ("limit", LIST_PAGE_SIZE as u64)]);
+        }
+        let response = request.dispatch(self).await?;
+        let listed = items(self.handle_response(response).await?);
+
+        let paged = listed.len() == LIST_PAGE_SIZE && self.supports_api(ApiVersion::V1_2);
+        let next = paged.then_some(offset + LIST_PAGE_SIZE as u64);
+        Ok(Page { items: listed.into_iter().filter_map(|item| localize(self, item)).collect(), next })
+    }
+}
+
+fn collections(list: CollectionsListResponse) -> Vec<CollectionInfo> {
+    list.collections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CasperError;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_paginated() {
+        // Pages of three numbers up to 7
+        let numbers = Paginated::new(|cursor| {
+            let start = cursor.unwrap_or(0);
+            Box::pin(async move {
+                let end = (start + 3).min(7);
+                Ok(Page { items: (start..end).collect(), next: (end < 7).then_some(end) })
+            })
+        });
+        assert_eq!(numbers.collect_all().await.unwrap(), (0..7).collect::<Vec<u64>>());
+
+        let mut failing = Paginated::<u64>::new(|_| {
+            Box::pin(async { Err(CasperError::InvalidResponse("boom".to_string())) })
+        });
+        assert!(failing.next().await.unwrap().is_err());
+        assert!(failing.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_pqs() {
+        let scripted = Scripted::new([(200, r#"[{"name":"a","dim":8,"codebooks":[],"enabled":true}]"#)]);
+        let client = scripted.client();
+        let mut pqs = client.stream_pqs();
+        assert_eq!(pqs.next().await.unwrap().unwrap().name, "a");
+        assert!(pqs.next().await.is_none());
+        assert_eq!(scripted.requests(), vec!["GET /pq/list"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_matrices_pages() {
+        let matrices = |range: std::ops::Range<usize>| {
+            let items: Vec<String> =
+                range.map(|i| format!(r#"{{"name":"m{i}","dim":2,"len":1,"enabled":true}}"#)).collect();
+            format!("[{}]", items.join(","))
+        };
+        let (first, second) = (matrices(0..LIST_PAGE_SIZE), matrices(LIST_PAGE_SIZE..300));
+        let scripted = Scripted::new([(200, first.as_str()), (200, second.as_str())]);
+        let client = scripted.client();
+        let listed = client.stream_matrices().collect_all().await.unwrap();
+        assert_eq!(listed.len(), 300);
+        assert_eq!(listed[299].name, "m299");
+        assert_eq!(scripted.last_query("cursor").as_deref(), Some("256"));
+        assert_eq!(scripted.requests().len(), 2);
+
+        // A server ignoring the parameters answers with everything at once
+        let all = matrices(0..300);
+        let scripted = Scripted::new([(200, all.as_str())]);
+        assert_eq!(scripted.client().stream_matrices().collect_all().await.unwrap().len(), 300);
+        assert_eq!(scripted.requests().len(), 1);
+    }
+}
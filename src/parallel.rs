@@ -0,0 +1,170 @@
+//! Map helpers that run on the rayon pool when the `rayon` feature is
+//! enabled, and sequentially otherwise.
+//!
+//! The `batch_*` helpers are for cheap per-item work such as preprocessing
+//! vectors: they stay on the calling thread for batches too small to be
+//! worth the hand-off. [`fan_out`] is their async counterpart for requests,
+//! running them as tokio tasks with bounded concurrency.
+
+use crate::error::{CasperError, Result};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::future::Future;
+use std::iter::Enumerate;
+use tokio::task::JoinSet;
+
+/// Requests a [`fan_out`] keeps in flight unless the caller picks a limit
+pub(crate) const FAN_OUT_LIMIT: usize = 32;
+
+/// Smallest batch the `batch_*` helpers spread across the pool
+#[cfg(feature = "rayon")]
+const MIN_PARALLEL_BATCH: usize = 256;
+
+/// `items.iter().map(f).collect()`, parallel with the `rayon` feature
+pub(crate) fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// [`map`] for cheap per-item work, parallel only for large batches
+pub(crate) fn batch_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    if items.len() >= MIN_PARALLEL_BATCH {
+        return items.par_iter().map(f).collect();
+    }
+    items.iter().map(f).collect()
+}
+
+/// `items.iter_mut().try_for_each(f)`, parallel for large batches with the
+/// `rayon` feature; which error is returned when several items fail is
+/// unspecified
+pub(crate) fn batch_try_for_each<T, F>(items: &mut [T], f: F) -> Result<()>
+where
+    T: Send,
+    F: Fn(&mut T) -> Result<()> + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    if items.len() >= MIN_PARALLEL_BATCH {
+        return items.par_iter_mut().try_for_each(f);
+    }
+    items.iter_mut().try_for_each(f)
+}
+
+/// Run `f` on every item as a tokio task, at most `limit` at a time.
+///
+/// Tasks are spawned lazily as earlier ones finish, so a long input never
+/// has more than `limit` tasks alive; dropping the [`FanOut`] cancels the
+/// ones still running.
+pub(crate) fn fan_out<I, F, Fut>(items: I, limit: usize, f: F) -> FanOut<I::IntoIter, F, Fut::Output>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    FanOut { items: items.into_iter().enumerate(), f, limit: limit.max(1), tasks: JoinSet::new() }
+}
+
+/// Tasks started by [`fan_out`]
+pub(crate) struct FanOut<I, F, R> {
+    items: Enumerate<I>,
+    f: F,
+    limit: usize,
+    tasks: JoinSet<(usize, R)>,
+}
+
+impl<I, F, Fut> FanOut<I, F, Fut::Output>
+where
+    I: Iterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    /// Next output to finish with the index of its item, `None` once every
+    /// item is done
+    pub(crate) async fn next(&mut self) -> Option<Result<(usize, Fut::Output)>> {
+        while self.tasks.len() < self.limit {
+            let Some((idx, item)) = self.items.next() else {
+                break;
+            };
+            let task = (self.f)(item);
+            self.tasks.spawn(async move { (idx, task.await) });
+        }
+        let joined = self.tasks.join_next().await?;
+        Some(joined.map_err(|e| CasperError::Unknown(format!("concurrent task failed: {}", e))))
+    }
+
+    /// Every output, in input order
+    pub(crate) async fn collect(mut self) -> Result<Vec<Fut::Output>> {
+        let mut outputs: Vec<Option<Fut::Output>> = Vec::with_capacity(self.items.size_hint().0);
+        while let Some(next) = self.next().await {
+            let (idx, output) = next?;
+            if outputs.len() <= idx {
+                outputs.resize_with(idx + 1, || None);
+            }
+            outputs[idx] = Some(output);
+        }
+        Ok(outputs.into_iter().map(|output| output.expect("every task produces an output")).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CasperError;
+
+    #[test]
+    fn test_batch_helpers() {
+        // Large enough to run on the pool with the `rayon` feature
+        let mut items: Vec<u32> = (0..1000).collect();
+        batch_try_for_each(&mut items, |x| {
+            *x *= 2;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(batch_map(&items, |x| x + 1)[999], 1999);
+
+        let failed = batch_try_for_each(&mut items, |x| match *x {
+            500 => Err(CasperError::ZeroNormVector),
+            _ => Ok(()),
+        });
+        assert!(matches!(failed, Err(CasperError::ZeroNormVector)));
+    }
+
+    #[tokio::test]
+    async fn test_fan_out() {
+        let running = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let outputs = fan_out(0..100u64, 4, |i| {
+            let running = running.clone();
+            async move {
+                let now = running.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                tokio::time::sleep(std::time::Duration::from_millis(10 - i % 10)).await;
+                running.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                (i, now)
+            }
+        })
+        .collect()
+        .await
+        .unwrap();
+
+        // Input order despite later items finishing first, never over the limit
+        assert!(outputs.iter().enumerate().all(|(idx, (i, _))| *i == idx as u64));
+        assert!(outputs.iter().all(|(_, running)| *running <= 4));
+    }
+}
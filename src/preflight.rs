@@ -0,0 +1,112 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use std::time::Duration;
+use url::Url;
+
+/// Checks run by [`CasperClient::connect`] before the client is handed out,
+/// so a misconfigured or unreachable server fails at construction instead of
+/// on the first real request.
+///
+/// The server is always asked for its API version, which checks that it is
+/// reachable and compatible (see [`CasperClient::negotiate_api_version`]).
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    resolve_dns: bool,
+    warm_grpc: bool,
+    timeout: Duration,
+}
+
+impl Default for Preflight {
+    fn default() -> Self {
+        Self { resolve_dns: true, warm_grpc: false, timeout: Duration::from_secs(10) }
+    }
+}
+
+impl Preflight {
+    /// Resolve the HTTP and gRPC hosts first, reporting a name that does not
+    /// resolve as such rather than as a connection error (default on)
+    pub fn resolve_dns(mut self, enabled: bool) -> Self {
+        self.resolve_dns = enabled;
+        self
+    }
+
+    /// Establish the gRPC channel used by matrix uploads (default off)
+    pub fn warm_grpc(mut self, enabled: bool) -> Self {
+        self.warm_grpc = enabled;
+        self
+    }
+
+    /// Time allowed for all checks together (default 10s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl CasperClient {
+    /// Create a client like [`CasperClient::new`], then run the default
+    /// [`Preflight`] checks against the server
+    pub async fn connect(host: &str, http_port: u16, grpc_port: u16) -> Result<Self> {
+        Self::builder(host, http_port, grpc_port).connect().await
+    }
+
+    /// Run `checks` against the server, e.g. again after a network change
+    pub async fn preflight(&self, checks: &Preflight) -> Result<()> {
+        let run = async {
+            if checks.resolve_dns {
+                resolve(&self.base_url).await?;
+                resolve(&Url::parse(self.grpc.addr())?).await?;
+            }
+            self.negotiate_api_version().await?;
+            if checks.warm_grpc {
+                self.grpc.warm(checks.timeout).await?;
+            }
+            Ok(())
+        };
+        tokio::time::timeout(checks.timeout, run).await.map_err(|_| CasperError::PreflightFailed {
+            check: "timeout",
+            message: format!("checks did not complete within {:?}", checks.timeout),
+        })?
+    }
+}
+
+/// Resolve the host of `url`
+async fn resolve(url: &Url) -> Result<()> {
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let resolved = tokio::net::lookup_host((host, port)).await.map_err(|e| CasperError::PreflightFailed {
+        check: "dns",
+        message: format!("cannot resolve '{}': {}", host, e),
+    })?;
+    if resolved.count() == 0 {
+        return Err(CasperError::PreflightFailed {
+            check: "dns",
+            message: format!("'{}' resolved to no addresses", host),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+    use std::io::ErrorKind;
+
+    #[tokio::test]
+    async fn test_resolve_ip_literal() {
+        assert!(resolve(&Url::parse("http://127.0.0.1:8080").unwrap()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_reports_unreachable_server() {
+        let checks = Preflight::default().resolve_dns(false);
+        let scripted = Scripted::new([]).fail(ErrorKind::ConnectionRefused);
+        let err = scripted.client().preflight(&checks).await.unwrap_err();
+        assert!(err.is_connect_failure(), "{err:?}");
+
+        let scripted = Scripted::new([(200, r#"{"api_version":"1.2"}"#)]);
+        assert!(scripted.client().preflight(&checks).await.is_ok());
+        assert_eq!(scripted.requests(), ["GET /version"]);
+    }
+}
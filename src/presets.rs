@@ -0,0 +1,112 @@
+//! Collection and HNSW index settings for common embedding models.
+//!
+//! The models below emit embeddings meant to be compared by cosine, so
+//! every preset normalizes vectors and searches by inner product. Link
+//! counts follow [`crate::sizing::recommend_hnsw_params`] for recall around
+//! 0.95: wider embeddings get more links per node.
+
+use crate::client::CasperClient;
+use crate::error::Result;
+use crate::job::Job;
+use crate::models::{CreateCollectionRequest, CreateHNSWIndexRequest, HNSWIndexConfig};
+
+/// Collection capacity of a preset, until [`CollectionPreset::max_size`]
+const DEFAULT_MAX_SIZE: u32 = 1_000_000;
+
+/// A collection and the HNSW index to build on it.
+///
+/// ```no_run
+/// # async fn example(client: &casper_client::CasperClient) -> casper_client::Result<()> {
+/// use casper_client::CollectionPreset;
+///
+/// let preset = CollectionPreset::text_embeddings_1536().max_size(5_000_000);
+/// client.create_from_preset("docs", preset).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CollectionPreset {
+    pub collection: CreateCollectionRequest,
+    pub index: CreateHNSWIndexRequest,
+}
+
+impl CollectionPreset {
+    fn new(dim: usize, m: usize, ef_construction: usize) -> Self {
+        Self {
+            collection: CreateCollectionRequest { dim, max_size: DEFAULT_MAX_SIZE, labels: Default::default() },
+            index: CreateHNSWIndexRequest {
+                hnsw: HNSWIndexConfig {
+                    metric: "inner-product".to_string(),
+                    quantization: "f32".to_string(),
+                    m,
+                    m0: 2 * m,
+                    ef_construction,
+                    pq_name: None,
+                },
+                normalization: Some(true),
+            },
+        }
+    }
+
+    /// OpenAI `text-embedding-3-small` and `text-embedding-ada-002`
+    pub fn text_embeddings_1536() -> Self {
+        Self::new(1536, 24, 256)
+    }
+
+    /// OpenAI `text-embedding-3-large`
+    pub fn text_embeddings_3072() -> Self {
+        Self::new(3072, 32, 256)
+    }
+
+    /// BERT-base sized sentence encoders, e.g. `all-mpnet-base-v2`
+    pub fn text_embeddings_768() -> Self {
+        Self::new(768, 24, 200)
+    }
+
+    /// Small sentence encoders, e.g. `all-MiniLM-L6-v2`
+    pub fn text_embeddings_384() -> Self {
+        Self::new(384, 16, 200)
+    }
+
+    /// CLIP ViT-B/32 and ViT-B/16 image and text embeddings
+    pub fn image_clip_512() -> Self {
+        Self::new(512, 16, 200)
+    }
+
+    /// CLIP ViT-L/14 image and text embeddings
+    pub fn image_clip_768() -> Self {
+        Self::new(768, 24, 200)
+    }
+
+    /// Collection capacity, 1,000,000 vectors by default
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.collection.max_size = max_size;
+        self
+    }
+}
+
+impl CasperClient {
+    /// Create a collection and its index from a preset, returning the
+    /// [`Job`] building the index. The index is not created if the
+    /// collection could not be.
+    pub async fn create_from_preset(&self, collection_name: &str, preset: CollectionPreset) -> Result<Job> {
+        self.create_collection(collection_name, preset.collection).await?;
+        self.create_hnsw_index(collection_name, preset.index).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_create_from_preset() {
+        let preset = CollectionPreset::image_clip_512().max_size(1000);
+        assert_eq!((preset.index.hnsw.m, preset.index.hnsw.m0), (16, 32));
+
+        let scripted = Scripted::new([(200, ""), (200, "")]);
+        scripted.client().create_from_preset("images", preset).await.unwrap();
+        assert_eq!(scripted.requests(), ["POST /collection/images", "POST /collection/images/index"]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Background latency probing of the server and its replicas.
+//!
+//! With [`CasperClientBuilder::probing`](crate::CasperClientBuilder::probing),
+//! a task shared by all clones of the client sends `GET /version` to the
+//! primary server and to every [hedging](crate::HedgePolicy) replica at a
+//! fixed interval. Hedges then take turns among the reachable replicas
+//! about as fast as the fastest one, rather than among all replicas, and
+//! [`CasperClient::endpoint_latencies`] exposes the measurements, e.g. for
+//! dashboards. The task stops once the last clone of the client is dropped.
+//!
+//! Probes are authenticated and signed like other requests, but skip
+//! middleware, retries and hedging, which would distort their latencies.
+
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::retry::RetryPolicy;
+use reqwest::Method;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task::JoinSet;
+use url::Url;
+
+/// Weight of the newest probe in [`EndpointLatency::average`]
+const AVERAGE_WEIGHT: f64 = 0.2;
+
+/// Replicas count as fast when their average latency is within this factor
+/// of the fastest one
+const FAST_SLACK: f64 = 1.5;
+
+/// How often to probe and how long to wait for an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbePolicy {
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl ProbePolicy {
+    /// Probe every `interval`, waiting up to 2s for each answer
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, timeout: Duration::from_secs(2) }
+    }
+
+    /// Count probes unanswered after `timeout` as failed (default 2s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Probe measurements of one endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointLatency {
+    /// Base URL of the server or replica
+    pub url: Url,
+    /// Latency of the last probe, `None` if it failed or none ran yet
+    pub last: Option<Duration>,
+    /// Exponentially weighted average latency of the successful probes
+    pub average: Option<Duration>,
+    /// Probes failed in a row; only 2xx answers count as success
+    pub consecutive_failures: u32,
+    /// When the last probe finished
+    pub probed_at: Option<SystemTime>,
+}
+
+impl EndpointLatency {
+    fn new(url: Url) -> Self {
+        Self { url, last: None, average: None, consecutive_failures: 0, probed_at: None }
+    }
+
+    fn record(&mut self, latency: Option<Duration>) {
+        self.last = latency;
+        self.probed_at = Some(SystemTime::now());
+        match latency {
+            Some(latency) => {
+                self.consecutive_failures = 0;
+                self.average = Some(match self.average {
+                    Some(average) => average.mul_f64(1.0 - AVERAGE_WEIGHT) + latency.mul_f64(AVERAGE_WEIGHT),
+                    None => latency,
+                });
+            }
+            None => self.consecutive_failures += 1,
+        }
+    }
+
+    /// Whether the endpoint answered its last probe
+    pub fn is_reachable(&self) -> bool {
+        self.last.is_some()
+    }
+}
+
+/// Probe state and task shared by all clones of a client
+#[derive(Debug)]
+pub(crate) struct Prober {
+    policy: ProbePolicy,
+    endpoints: Mutex<Vec<EndpointLatency>>,
+    started: AtomicBool,
+}
+
+impl Prober {
+    pub(crate) fn new(policy: ProbePolicy, urls: Vec<Url>) -> Self {
+        Self {
+            policy,
+            endpoints: Mutex::new(urls.into_iter().map(EndpointLatency::new).collect()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the probing task sending probes with `client`, unless running
+    /// or outside a Tokio runtime
+    pub(crate) fn start(self: &Arc<Self>, client: &CasperClient) {
+        if self.started.load(Ordering::Relaxed) || tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+        if self.started.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let prober: Weak<Self> = Arc::downgrade(self);
+        let client = client.probe_client();
+        let interval = self.policy.interval;
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                let Some(prober) = prober.upgrade() else {
+                    return;
+                };
+                prober.probe_all(&client).await;
+            }
+        });
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<EndpointLatency> {
+        self.endpoints.lock().expect("probe results poisoned").clone()
+    }
+
+    /// Probe every endpoint once, concurrently, with a
+    /// [`CasperClient::probe_client`]
+    async fn probe_all(self: &Arc<Self>, client: &CasperClient) {
+        let urls: Vec<Url> = self.snapshot().into_iter().map(|endpoint| endpoint.url).collect();
+        let mut probes = JoinSet::new();
+        for (idx, url) in urls.into_iter().enumerate() {
+            let prober = self.clone();
+            let client = client.clone();
+            probes.spawn(async move { (idx, prober.probe(&client, url).await) });
+        }
+        while let Some(Ok((idx, latency))) = probes.join_next().await {
+            self.endpoints.lock().expect("probe results poisoned")[idx].record(latency);
+        }
+    }
+
+    /// Latency of one probe of the server at `base_url`, `None` on failure
+    async fn probe(&self, client: &CasperClient, base_url: Url) -> Option<Duration> {
+        let url = base_url.join("version").ok()?;
+        let request = client.http(Scope::Read, Method::GET, url).timeout(self.policy.timeout);
+        let start = Instant::now();
+        let response = tokio::time::timeout(self.policy.timeout, request.dispatch(client)).await.ok()?.ok()?;
+        response.status().is_success().then(|| start.elapsed())
+    }
+
+    /// Indices in `candidates` of the reachable endpoints whose average
+    /// latency is within [`FAST_SLACK`] of the lowest, empty if none was
+    /// measured
+    pub(crate) fn fastest(&self, candidates: &[Url]) -> Vec<usize> {
+        let endpoints = self.endpoints.lock().expect("probe results poisoned");
+        let measured: Vec<(usize, Duration)> = candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, url)| {
+                let endpoint = endpoints.iter().find(|endpoint| &endpoint.url == url)?;
+                endpoint.is_reachable().then_some((idx, endpoint.average?))
+            })
+            .collect();
+        let Some(lowest) = measured.iter().map(|(_, average)| *average).min() else {
+            return Vec::new();
+        };
+        let cutoff = lowest.mul_f64(FAST_SLACK);
+        measured.into_iter().filter(|(_, average)| *average <= cutoff).map(|(idx, _)| idx).collect()
+    }
+}
+
+impl CasperClient {
+    /// Latest probe measurements of the server and its replicas, primary
+    /// first; empty unless [probing](crate::CasperClientBuilder::probing) is
+    /// enabled
+    pub fn endpoint_latencies(&self) -> Vec<EndpointLatency> {
+        self.prober.as_ref().map_or_else(Vec::new, |prober| prober.snapshot())
+    }
+
+    /// Probe every endpoint now rather than at the next interval, returning
+    /// the updated measurements
+    pub async fn probe_endpoints(&self) -> Vec<EndpointLatency> {
+        let Some(prober) = &self.prober else {
+            return Vec::new();
+        };
+        prober.probe_all(&self.probe_client()).await;
+        prober.snapshot()
+    }
+
+    /// Clone sending probes, without the middleware, retries and hedging
+    /// that would distort latencies; it holds no reference to the prober,
+    /// so the probing task does not keep it alive
+    fn probe_client(&self) -> CasperClient {
+        CasperClient {
+            prober: None,
+            hedger: None,
+            middleware: None,
+            session: None,
+            retry: Arc::new(RetryPolicy::none()),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+    use crate::HedgePolicy;
+
+    #[tokio::test]
+    async fn test_probe_endpoints() {
+        let scripted = Scripted::new([(200, r#"{"api_version":"1.2"}"#), (404, "")]);
+        let client = crate::CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .token("secret")
+            .hedging(HedgePolicy::new(0.9).replica("http://replica:9090").unwrap())
+            .probing(ProbePolicy::new(Duration::from_secs(3600)))
+            .build()
+            .unwrap();
+
+        // The background task probes right away, then every hour
+        let mut latencies = client.endpoint_latencies();
+        while latencies.iter().any(|endpoint| endpoint.probed_at.is_none()) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            latencies = client.endpoint_latencies();
+        }
+        assert_eq!(latencies.len(), 2);
+        assert!(latencies[0].is_reachable());
+        assert_eq!(latencies[1].url.as_str(), "http://replica:9090/");
+        assert!(!latencies[1].is_reachable());
+        assert_eq!(scripted.requests(), ["GET /version", "GET /version"]);
+        assert_eq!(scripted.last_header("authorization").as_deref(), Some("Bearer secret"));
+    }
+
+    #[test]
+    fn test_fastest_endpoint() {
+        let urls: Vec<Url> = ["http://a:1", "http://b:1", "http://c:1"].map(|url| Url::parse(url).unwrap()).to_vec();
+        let prober = Prober::new(ProbePolicy::new(Duration::from_secs(1)), urls.clone());
+        assert!(prober.fastest(&urls).is_empty());
+
+        let mut endpoints = prober.endpoints.lock().unwrap();
+        endpoints[0].record(Some(Duration::from_millis(35)));
+        endpoints[1].record(Some(Duration::from_millis(10)));
+        endpoints[1].record(None);
+        endpoints[2].record(Some(Duration::from_millis(20)));
+        drop(endpoints);
+        assert_eq!(prober.fastest(&urls), [2]);
+
+        // The average drops to 29ms, close enough to the fastest
+        prober.endpoints.lock().unwrap()[0].record(Some(Duration::from_millis(5)));
+        assert_eq!(prober.fastest(&urls), [0, 2]);
+    }
+}
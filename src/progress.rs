@@ -0,0 +1,146 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Stage a long-running operation is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Streaming matrix rows over gRPC
+    Uploading,
+    /// Streaming a batch update body
+    Writing,
+    /// Waiting for a collection's index build before retrying a write
+    WaitingForIndex,
+    /// Writing a collection's vectors to export files
+    Exporting,
+    /// Restoring an export into a collection
+    Importing,
+}
+
+/// Progress of one operation, passed to the hook registered with
+/// [`crate::CasperClientBuilder::progress_hook`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// Operation name, as in [`crate::AuditEvent::operation`]
+    pub operation: &'static str,
+    /// Collection or matrix the operation applies to
+    pub target: String,
+    pub phase: Phase,
+    /// Items (vectors, matrix rows) completed so far
+    pub done: u64,
+    /// Items in total, if known upfront
+    pub total: Option<u64>,
+    /// Payload bytes sent so far
+    pub bytes: u64,
+}
+
+impl Progress {
+    /// Completed share in `[0, 1]`, if the total is known
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total? {
+            0 => Some(1.0),
+            total => Some((self.done as f64 / total as f64).min(1.0)),
+        }
+    }
+}
+
+/// Callback receiving every [`Progress`] event
+#[derive(Clone)]
+pub(crate) struct ProgressHook(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Running counts of one operation, shared by the tasks working on it
+/// (e.g. the shards of a matrix upload) and reported on every change
+#[derive(Debug)]
+pub(crate) struct ProgressCounter {
+    hook: ProgressHook,
+    operation: &'static str,
+    target: String,
+    phase: Phase,
+    total: Option<u64>,
+    done: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl ProgressCounter {
+    /// Counter reporting to `hook`, `None` without a hook so untracked
+    /// operations skip the bookkeeping
+    pub(crate) fn new(
+        hook: Option<&ProgressHook>,
+        operation: &'static str,
+        target: &str,
+        phase: Phase,
+        total: Option<u64>,
+    ) -> Option<Arc<Self>> {
+        Some(Arc::new(Self {
+            hook: hook?.clone(),
+            operation,
+            target: target.to_string(),
+            phase,
+            total,
+            done: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+        }))
+    }
+
+    /// Count `items` and `bytes` more as done and report the new totals
+    pub(crate) fn advance(&self, items: u64, bytes: u64) {
+        let done = self.done.fetch_add(items, Ordering::Relaxed) + items;
+        let bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        (self.hook.0)(&Progress {
+            operation: self.operation,
+            target: self.target.clone(),
+            phase: self.phase,
+            done,
+            total: self.total,
+            bytes,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InsertRequest;
+    use crate::testing::Scripted;
+    use crate::CasperClient;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_index_wait_reports_progress() {
+        let transport = Scripted::new([
+            (423, r#"{"error":"index creation in progress"}"#),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":0,"index":null}"#),
+            (200, ""),
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(transport)
+            .wait_for_index(Duration::from_secs(5))
+            .progress_hook(move |progress| sink.lock().unwrap().push(progress.clone()))
+            .build()
+            .unwrap();
+
+        client.insert_vector("docs", InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None }).await.unwrap();
+        let events = events.lock().unwrap();
+        let done: Vec<_> = events.iter().map(|p| (p.phase, p.done, p.fraction())).collect();
+        assert_eq!(done, [(Phase::WaitingForIndex, 0, Some(0.0)), (Phase::WaitingForIndex, 1, Some(1.0))]);
+        assert_eq!(events[0].target, "docs");
+    }
+}
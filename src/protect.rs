@@ -0,0 +1,131 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+
+/// Names guarded against destructive calls, see
+/// [`crate::CasperClientBuilder::protect`].
+///
+/// Patterns match whole names; `*` matches any run of characters, so
+/// `"prod-*"` protects every name starting with `prod-` and `"*"` protects
+/// everything.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DeleteProtection {
+    patterns: Vec<String>,
+}
+
+impl DeleteProtection {
+    pub(crate) fn add(&mut self, pattern: &str) {
+        self.patterns.push(pattern.to_string());
+    }
+
+    pub(crate) fn is_protected(&self, name: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
+/// Match `name` against a pattern where `*` matches any (possibly empty) run
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` in the pattern: exact match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+impl CasperClient {
+    /// A copy of this client that bypasses delete protection.
+    ///
+    /// Use it for the one destructive call that is meant to go through:
+    /// `client.force().delete_collection("prod-docs").await`.
+    pub fn force(&self) -> CasperClient {
+        CasperClient { forced: true, ..self.clone() }
+    }
+
+    /// Whether the client was built with [`crate::CasperClientBuilder::read_only`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Refuse any mutating operation on a read-only client
+    pub(crate) fn check_writable(&self, operation: &str) -> Result<()> {
+        if self.read_only {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "{} rejected: client is read-only",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse destructive operations on protected names unless forced
+    pub(crate) fn check_destructive(&self, kind: &str, name: &str) -> Result<()> {
+        self.check_writable("delete")?;
+        if !self.forced && self.protection.is_protected(name) {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "{} '{}' is delete-protected; use CasperClient::force() to delete it",
+                kind, name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InsertRequest;
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_protected_patterns() {
+        let mut protection = DeleteProtection::default();
+        protection.add("prod-*");
+        protection.add("users");
+        protection.add("*-archive-*v1");
+
+        assert!(protection.is_protected("prod-docs"));
+        assert!(protection.is_protected("users"));
+        assert!(protection.is_protected("eu-archive-2024-v1"));
+        assert!(!protection.is_protected("users-test"));
+        assert!(!protection.is_protected("staging-docs"));
+        assert!(!protection.is_protected("eu-archive-2024-v2"));
+
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .protect("prod-*")
+            .build()
+            .unwrap();
+        assert!(client.check_destructive("collection", "prod-docs").is_err());
+        assert!(client.check_destructive("collection", "dev-docs").is_ok());
+        assert!(client.force().check_destructive("collection", "prod-docs").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_client() {
+        let scripted = Scripted::default();
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .read_only()
+            .build()
+            .unwrap();
+        assert!(client.is_read_only());
+
+        // Rejected locally, even when forced
+        let insert = InsertRequest { id: 1, vector: vec![1.0], payload: None };
+        assert!(matches!(client.insert_vector("docs", insert).await, Err(CasperError::OperationNotAllowed(_))));
+        assert!(client.force().check_destructive("collection", "dev-docs").is_err());
+        assert!(scripted.requests().is_empty());
+    }
+}
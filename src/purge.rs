@@ -0,0 +1,91 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::models::PurgeResponse;
+use reqwest::Method;
+
+impl CasperClient {
+    /// Physically remove the deleted vectors of a collection, returning how
+    /// many were purged.
+    ///
+    /// Servers with soft deletes only mark deleted vectors, which keep
+    /// taking space (see [`CollectionInfo::deleted`](crate::CollectionInfo::deleted))
+    /// until the server compacts the collection; this triggers it now, e.g.
+    /// after a large cleanup. Servers deleting right away (405 or 501) have
+    /// nothing to purge and fail with [`CasperError::OperationNotAllowed`].
+    ///
+    /// Drops the collection's cached searches, which may include purged
+    /// vectors (see [`SearchRequest::include_deleted`](crate::SearchRequest::include_deleted)).
+    pub async fn purge_deleted(&self, collection_name: &str) -> Result<usize> {
+        self.audited("purge_deleted", collection_name, 0, async {
+            self.check_writable("purge_deleted")?;
+            let url = self.collection_url(collection_name, "/purge")?;
+            let response = self.http(Scope::Admin, Method::POST, url).dispatch(self).await?;
+            if matches!(response.status().as_u16(), 405 | 501) {
+                return Err(CasperError::OperationNotAllowed(format!(
+                    "purging '{}': the server does not support soft deletes",
+                    collection_name
+                )));
+            }
+            let body = self.handle_text_response(response).await?;
+            self.invalidate_search_cache(collection_name);
+            if body.trim().is_empty() {
+                return Ok(0);
+            }
+            let response: PurgeResponse = serde_json::from_str(&body).map_err(|e| {
+                CasperError::InvalidResponse(format!("Failed to parse purge response: {} - {}", e, body))
+            })?;
+            Ok(response.purged)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::SearchRequest;
+    use crate::testing::Scripted;
+    use crate::{CasperClient, CasperError, SearchCachePolicy};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_purge_deleted() {
+        let scripted = Scripted::new([
+            (
+                200,
+                r#"{"name":"docs","dimension":2,"mutable":true,"has_index":false,"max_size":10,"size":4,"deleted":3,
+                    "index":null}"#,
+            ),
+            (200, r#"{"purged":3}"#),
+            (501, "not implemented"),
+        ]);
+        let client = scripted.client();
+
+        assert_eq!(client.get_collection("docs").await.unwrap().deleted, 3);
+        assert_eq!(client.purge_deleted("docs").await.unwrap(), 3);
+        assert!(matches!(client.purge_deleted("docs").await, Err(CasperError::OperationNotAllowed(_))));
+        assert_eq!(scripted.requests()[1], "POST /collection/docs/purge");
+    }
+
+    #[tokio::test]
+    async fn test_purge_drops_cached_deleted_vectors() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":0.9}]"#),
+            (200, r#"{"purged":1}"#),
+            (200, "[]"),
+            (404, "collection not found"),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .search_cache(SearchCachePolicy::new(10, Duration::from_secs(60)))
+            .build()
+            .unwrap();
+        let search = || SearchRequest { vector: vec![0.5, 0.25], include_deleted: true, ..Default::default() };
+
+        assert_eq!(client.query("docs", search()).await.unwrap().len(), 1);
+        assert_eq!(scripted.last_query("include_deleted").as_deref(), Some("true"));
+        assert_eq!(client.purge_deleted("docs").await.unwrap(), 1);
+        assert!(client.query("docs", search()).await.unwrap().is_empty());
+        assert!(matches!(client.purge_deleted("gone").await, Err(CasperError::CollectionNotFound(_))));
+    }
+}
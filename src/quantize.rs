@@ -0,0 +1,137 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{BatchInsertOperation, BatchResult, QuantizedBatchInsertRequest, QuantizedInsertOperation};
+use crate::outbox::WriteOp;
+use crate::parallel;
+use serde::{Deserialize, Serialize};
+
+/// Per-dimension affine i8 quantizer: `code = round((x - offset) / scale) - 128`.
+///
+/// Fitted from sample data so that each dimension's observed `[min, max]`
+/// range maps onto the full i8 range; values outside it are clamped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalarQuantizer {
+    pub offset: Vec<f32>,
+    pub scale: Vec<f32>,
+}
+
+impl ScalarQuantizer {
+    /// Fit per-dimension ranges from `sample`
+    pub fn fit<V: AsRef<[f32]>>(sample: &[V]) -> Result<Self> {
+        let Some(first) = sample.first() else {
+            return Err(CasperError::InvalidArgument(
+                "quantizer needs a non-empty sample".to_string(),
+            ));
+        };
+        let dim = first.as_ref().len();
+
+        let mut min = vec![f32::INFINITY; dim];
+        let mut max = vec![f32::NEG_INFINITY; dim];
+        for v in sample {
+            let v = v.as_ref();
+            if v.len() != dim {
+                return Err(CasperError::InvalidDimension { expected: dim, actual: v.len() });
+            }
+            for (i, &x) in v.iter().enumerate() {
+                min[i] = min[i].min(x);
+                max[i] = max[i].max(x);
+            }
+        }
+
+        let scale = min
+            .iter()
+            .zip(&max)
+            .map(|(lo, hi)| if hi > lo { (hi - lo) / 255.0 } else { 1.0 })
+            .collect();
+
+        Ok(Self { offset: min, scale })
+    }
+
+    /// Vector dimension the quantizer was fitted on
+    pub fn dim(&self) -> usize {
+        self.offset.len()
+    }
+
+    /// Quantize one vector
+    pub fn quantize(&self, vector: &[f32]) -> Result<Vec<i8>> {
+        if vector.len() != self.dim() {
+            return Err(CasperError::InvalidDimension { expected: self.dim(), actual: vector.len() });
+        }
+
+        Ok(vector
+            .iter()
+            .zip(self.offset.iter().zip(&self.scale))
+            .map(|(x, (offset, scale))| {
+                let level = ((x - offset) / scale).round().clamp(0.0, 255.0);
+                (level as i16 - 128) as i8
+            })
+            .collect())
+    }
+
+    /// Approximate inverse of [`ScalarQuantizer::quantize`]
+    pub fn dequantize(&self, codes: &[i8]) -> Vec<f32> {
+        codes
+            .iter()
+            .zip(self.offset.iter().zip(&self.scale))
+            .map(|(&c, (offset, scale))| offset + (c as i16 + 128) as f32 * scale)
+            .collect()
+    }
+}
+
+impl CasperClient {
+    /// Quantize vectors client-side and insert the compact i8 codes.
+    ///
+    /// The collection's index must use `"i8"` quantization; this is checked
+    /// against the collection info before sending.
+    pub async fn insert_quantized(
+        &self,
+        collection_name: &str,
+        quantizer: &ScalarQuantizer,
+        inserts: Vec<BatchInsertOperation>,
+    ) -> Result<BatchResult> {
+        let info = self.get_collection(collection_name).await?;
+        let quantization = info
+            .index
+            .as_ref()
+            .and_then(|index| index.hnsw.as_ref())
+            .map(|hnsw| hnsw.quantization.as_str());
+        if quantization != Some("i8") {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "collection '{}' does not use i8 quantization",
+                collection_name
+            )));
+        }
+
+        let ids: Vec<u32> = inserts.iter().map(|op| op.id).collect();
+        let normalize = self.normalizes(collection_name).await?;
+        let insert = parallel::batch_map(&inserts, |op| {
+            let vector = self.prepare_vector(collection_name, op.vector.clone(), normalize)?;
+            Ok(QuantizedInsertOperation { id: op.id, vector: quantizer.quantize(&vector)? })
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        let body = self
+            .write(collection_name, WriteOp::InsertQuantized(QuantizedBatchInsertRequest { insert }))
+            .await?;
+        self.parse_batch_result(ids, body.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize_round_trip() {
+        let sample = vec![vec![-1.0, 0.0], vec![1.0, 10.0]];
+        let quantizer = ScalarQuantizer::fit(&sample).unwrap();
+
+        let codes = quantizer.quantize(&[-1.0, 10.0]).unwrap();
+        assert_eq!(codes, vec![-128, 127]);
+
+        let restored = quantizer.dequantize(&quantizer.quantize(&[0.5, 2.5]).unwrap());
+        assert!((restored[0] - 0.5).abs() < 0.01);
+        assert!((restored[1] - 2.5).abs() < 0.05);
+    }
+}
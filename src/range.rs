@@ -0,0 +1,193 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::models::{RangeSearchRequest, SearchRequest, SearchResponse, SearchResult};
+use crate::paginate::{Page, Paginated};
+use crate::scores::ScoreScale;
+use crate::version::ApiVersion;
+use reqwest::Method;
+use serde::Deserialize;
+
+/// Results per range search page
+const RANGE_PAGE_SIZE: usize = 1000;
+/// Top-k limit the emulated range search starts from, doubled until the
+/// results leave the range
+const RANGE_FALLBACK_LIMIT: usize = 100;
+
+/// One page of `POST collection/<name>/search/range`
+#[derive(Debug, Deserialize)]
+struct RangePage {
+    results: SearchResponse,
+    /// Cursor of the next page, absent on the last one
+    #[serde(default)]
+    next_cursor: Option<u64>,
+}
+
+impl CasperClient {
+    /// Every vector within `max_distance` of `vector`, closest first, see
+    /// [`CasperClient::stream_search_range`]
+    pub async fn search_range(
+        &self,
+        collection_name: &str,
+        vector: Vec<f32>,
+        max_distance: f32,
+    ) -> Result<SearchResponse> {
+        let request = RangeSearchRequest { vector, max_distance, filter: None };
+        self.stream_search_range(collection_name, request).collect_all().await
+    }
+
+    /// Range search as a [`Paginated`] stream of results, fetched 1000 at
+    /// a time, for ranges too large to hold at once. Scores are on the
+    /// client's [`ScoreScale`], as by [`CasperClient::query`].
+    ///
+    /// Servers without range search (API 1.0, or answering 404, 405 or 501)
+    /// get it emulated with top-k searches of growing limits, all results
+    /// in one page; this needs the collection's index metric to tell
+    /// distances.
+    pub fn stream_search_range<'a>(
+        &'a self,
+        collection_name: &'a str,
+        request: RangeSearchRequest,
+    ) -> Paginated<'a, SearchResult> {
+        Paginated::new(move |cursor| {
+            let request = request.clone();
+            Box::pin(async move {
+                let mut page = self.range_page(collection_name, request, cursor).await?;
+                self.rescale(collection_name, self.score_scale, &mut page.items).await?;
+                Ok(page)
+            })
+        })
+    }
+
+    /// One page of a range search, with raw scores
+    async fn range_page(
+        &self,
+        collection_name: &str,
+        request: RangeSearchRequest,
+        cursor: Option<u64>,
+    ) -> Result<Page<SearchResult>> {
+        if request.max_distance.is_nan() {
+            return Err(CasperError::InvalidArgument("range search distance must not be NaN".to_string()));
+        }
+        let query = SearchRequest { vector: request.vector, filter: request.filter, ..Default::default() };
+        let query = self.prepare_query(collection_name, query).await?;
+        let request = RangeSearchRequest { vector: query.vector, filter: query.filter, ..request };
+        if !self.supports_api(ApiVersion::V1_1) {
+            return self.emulate_range(collection_name, request).await;
+        }
+
+        let url = self.collection_url(collection_name, "/search/range")?;
+        let mut params = vec![("limit", RANGE_PAGE_SIZE.to_string())];
+        if let Some(cursor) = cursor {
+            params.push(("cursor", cursor.to_string()));
+        }
+        let response = self
+            .http(Scope::Read, Method::POST, url)
+            .query(&params)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .body(self.json_body(&request)?)
+            .dispatch(self)
+            .await?;
+        if cursor.is_none() && matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return self.emulate_range(collection_name, request).await;
+        }
+        let page: RangePage = self.handle_response(response).await?;
+        Ok(Page { items: page.results, next: page.next_cursor })
+    }
+
+    /// Range search as top-k searches, doubling the limit until a result
+    /// falls outside the range or the collection is exhausted
+    async fn emulate_range(&self, collection_name: &str, request: RangeSearchRequest) -> Result<Page<SearchResult>> {
+        let info = self.get_collection(collection_name).await?;
+        let Some(metric) = info.index.as_ref().and_then(|index| index.metric()) else {
+            return Err(CasperError::OperationNotAllowed(format!(
+                "range search on collection '{}' needs server support or an index metric",
+                collection_name
+            )));
+        };
+
+        // The vector is prepared already; distances are told from raw scores
+        let client = CasperClient { score_scale: ScoreScale::Raw, ..self.stored_space() };
+        let mut limit = RANGE_FALLBACK_LIMIT;
+        loop {
+            let query = SearchRequest {
+                vector: request.vector.clone(),
+                limit: Some(limit),
+                filter: request.filter.clone(),
+                ..Default::default()
+            };
+            let results = client.query(collection_name, query).await?;
+            let exhausted = results.len() < limit || limit >= info.size;
+            let left_range = match results.last() {
+                Some(last) => ScoreScale::Distance.convert(metric, last.score)? > request.max_distance,
+                None => true,
+            };
+            if exhausted || left_range {
+                let mut within = Vec::with_capacity(results.len());
+                for result in results {
+                    if ScoreScale::Distance.convert(metric, result.score)? <= request.max_distance {
+                        within.push(result);
+                    }
+                }
+                return Ok(Page::last(within));
+            }
+            limit = limit.saturating_mul(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::Scripted;
+    use crate::{CasperClient, ScoreScale};
+
+    #[tokio::test]
+    async fn test_search_range() {
+        let scripted = Scripted::new([
+            (200, r#"{"results":[{"id":1,"score":0.1}],"next_cursor":1}"#),
+            (200, r#"{"results":[{"id":2,"score":0.3}]}"#),
+        ]);
+        let results = scripted.client().search_range("docs", vec![1.0, 0.0], 0.5).await.unwrap();
+        assert_eq!((results[0].id, results[1].id), (1, 2));
+        assert_eq!(scripted.last_query("cursor").as_deref(), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_search_range_emulated() {
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":3,
+            "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+            "normalization":false}}"#;
+        let scripted = Scripted::new([
+            (404, "not found"),
+            (200, docs),
+            (200, r#"[{"id":1,"score":0.2},{"id":2,"score":0.4},{"id":3,"score":0.9}]"#),
+        ]);
+        let results = scripted.client().search_range("docs", vec![1.0, 0.0], 0.5).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(scripted.last_query("limit").as_deref(), Some("100"));
+    }
+
+    #[tokio::test]
+    async fn test_search_range_emulated_on_client_scale() {
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":3,
+            "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+            "normalization":false}}"#;
+        let scripted = Scripted::new([
+            (404, "not found"),
+            (200, docs),
+            (200, r#"[{"id":1,"score":0.25},{"id":2,"score":1.0},{"id":3,"score":3.0}]"#),
+            (200, docs),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        // The range is in distances, whatever scale the scores are reported on
+        let results = client.search_range("docs", vec![1.0, 0.0], 1.0).await.unwrap();
+        let ranked: Vec<(u32, f32)> = results.iter().map(|r| (r.id, r.score)).collect();
+        assert_eq!(ranked, [(1, 0.8), (2, 0.5)]);
+    }
+}
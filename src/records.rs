@@ -0,0 +1,183 @@
+use crate::client::CasperClient;
+use crate::collection::CollectionHandle;
+use crate::error::{CasperError, Result};
+use crate::parallel;
+use crate::models::{
+    BatchInsertOperation, BatchResult, BatchUpdateRequest, GetVectorResponse, InsertRequest, SearchRequest,
+};
+use serde::de::DeserializeOwned;
+
+/// Vectors fetched at once by [`CasperClient::get_records`]
+const FETCH_PARALLELISM: usize = 32;
+
+/// A stored vector with its payload deserialized into `T`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<T> {
+    pub id: u32,
+    pub vector: Vec<f32>,
+    pub payload: T,
+}
+
+/// An application type stored as a vector, usually implemented with
+/// `#[derive(CasperRecord)]`:
+///
+/// ```no_run
+/// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+/// use casper_client::CasperRecord;
+///
+/// #[derive(CasperRecord)]
+/// struct Doc {
+///     #[id]
+///     id: u32,
+///     #[vector]
+///     embedding: Vec<f32>,
+///     title: String,
+/// }
+///
+/// let doc = Doc { id: 1, embedding: vec![0.1, 0.2, 0.3], title: "intro".to_string() };
+/// client.insert_record("docs", &doc).await?;
+/// let similar = client.query("docs", doc.search_request()).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The `#[id]` field may be of any type converting into `u32`, the
+/// `#[vector]` field of any type that is `AsRef<[f32]>`.
+pub trait CasperRecord {
+    fn record_id(&self) -> u32;
+
+    fn record_vector(&self) -> &[f32];
+
+    /// Insert of this record
+    fn insert_request(&self) -> InsertRequest {
+        InsertRequest { id: self.record_id(), vector: self.record_vector().to_vec(), payload: None }
+    }
+
+    /// Search for records similar to this one
+    fn search_request(&self) -> SearchRequest {
+        SearchRequest { vector: self.record_vector().to_vec(), ..Default::default() }
+    }
+}
+
+impl<T: DeserializeOwned> Record<T> {
+    fn hydrate(response: GetVectorResponse) -> Result<Self> {
+        // A missing payload reads as JSON null, so `T = Option<_>` accepts it
+        let payload = serde_json::from_value(response.payload.unwrap_or_default()).map_err(|e| {
+            CasperError::InvalidResponse(format!("payload of vector {} does not match: {}", response.id, e))
+        })?;
+        Ok(Self { id: response.id, vector: response.vector, payload })
+    }
+}
+
+impl CasperClient {
+    /// Fetch vectors `ids` with their payloads deserialized into `T`.
+    ///
+    /// Results are in input order, `None` for IDs that do not exist. A
+    /// payload that does not deserialize into `T` fails the whole call; use
+    /// `Option<_>` for `T` when some vectors have no payload.
+    ///
+    /// ```no_run
+    /// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+    /// #[derive(serde::Deserialize)]
+    /// struct Doc {
+    ///     title: String,
+    /// }
+    ///
+    /// for record in client.get_records::<Doc>("docs", &[1, 2, 3]).await?.into_iter().flatten() {
+    ///     println!("{}: {}", record.id, record.payload.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_records<T>(&self, collection_name: &str, ids: &[u32]) -> Result<Vec<Option<Record<T>>>>
+    where
+        T: DeserializeOwned,
+    {
+        let fetches = parallel::fan_out(ids.iter().copied(), FETCH_PARALLELISM, |id| {
+            let client = self.clone();
+            let collection_name = collection_name.to_string();
+            async move { client.fetch_vector(&collection_name, id).await }
+        });
+        let responses: Vec<Option<GetVectorResponse>> = fetches.collect().await?.into_iter().collect::<Result<_>>()?;
+        responses.into_iter().map(|response| response.map(Record::hydrate).transpose()).collect()
+    }
+}
+
+impl CasperClient {
+    /// Insert `record` as a vector
+    pub async fn insert_record<R: CasperRecord>(&self, collection_name: &str, record: &R) -> Result<()> {
+        self.insert_vector(collection_name, record.insert_request()).await
+    }
+
+    /// Insert `records` in one batch update
+    pub async fn insert_records<R: CasperRecord>(&self, collection_name: &str, records: &[R]) -> Result<BatchResult> {
+        let insert = records
+            .iter()
+            .map(|record| BatchInsertOperation {
+                id: record.record_id(),
+                vector: record.record_vector().to_vec(),
+                payload: None,
+            })
+            .collect();
+        let request = BatchUpdateRequest { insert, delete: Vec::new(), upsert: false };
+        self.batch_update_results(collection_name, request).await
+    }
+}
+
+impl CollectionHandle {
+    /// [`CasperClient::get_records`] on this collection
+    pub async fn get_records<T: DeserializeOwned>(&self, ids: &[u32]) -> Result<Vec<Option<Record<T>>>> {
+        self.client.get_records(&self.name, ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CasperRecord;
+    use crate::testing::Scripted;
+    use serde::Deserialize;
+
+    #[derive(CasperRecord)]
+    struct Tagged {
+        #[id]
+        key: u16,
+        #[vector]
+        embedding: [f32; 2],
+        #[allow(dead_code)]
+        tag: &'static str,
+    }
+
+    #[test]
+    fn test_derive_casper_record() {
+        let record = Tagged { key: 7, embedding: [0.5, 1.0], tag: "a" };
+        let insert = record.insert_request();
+        assert_eq!((insert.id, insert.vector), (7, vec![0.5, 1.0]));
+        assert_eq!(record.search_request().vector, vec![0.5, 1.0]);
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Doc {
+        title: String,
+    }
+
+    #[tokio::test]
+    async fn test_get_records() {
+        let scripted = Scripted::new([
+            (200, r#"{"id":1,"vector":[1.0,0.0],"payload":{"title":"one"}}"#),
+            (404, "not found"),
+            (200, r#"{"id":3,"vector":[1.0]}"#),
+            (200, r#"{"id":3,"vector":[1.0]}"#),
+        ]);
+        let client = scripted.client();
+
+        let records = client.collection("docs").get_records::<Doc>(&[1, 2]).await.unwrap();
+        let first = records[0].as_ref().unwrap();
+        assert_eq!((first.id, first.payload.title.as_str()), (1, "one"));
+        assert!(records[1].is_none());
+
+        // Vector 3 has no payload
+        assert!(client.get_records::<Doc>("docs", &[3]).await.is_err());
+        let untyped = client.get_records::<Option<Doc>>("docs", &[3]).await.unwrap();
+        assert_eq!(untyped[0].as_ref().unwrap().payload, None);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::{CasperError, Result};
+use crate::parallel;
+use crate::rng::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Number of orthogonal iterations used when fitting PCA
+const PCA_ITERATIONS: usize = 30;
+
+/// Linear dimensionality reduction fitted on sample data.
+///
+/// Vectors are mapped as `components · (v - mean)`. Fit once, persist with
+/// [`DimReducer::save`], and register it on the client with
+/// [`crate::CasperClientBuilder::dim_reducer`] so that inserts and searches
+/// against the collection are reduced consistently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DimReducer {
+    /// Per-dimension mean subtracted before projecting (zeros for random projection)
+    pub mean: Vec<f32>,
+    /// Projection rows, `output_dim` vectors of `input_dim` values
+    pub components: Vec<Vec<f32>>,
+    /// Variance captured by each component (PCA only)
+    #[serde(default)]
+    pub explained_variance: Vec<f32>,
+}
+
+impl DimReducer {
+    /// Fit PCA on `sample`, keeping the top `output_dim` principal components
+    pub fn fit_pca<V: AsRef<[f32]> + Sync>(sample: &[V], output_dim: usize, seed: u64) -> Result<Self> {
+        let input_dim = check_sample(sample, output_dim)?;
+        let n = sample.len() as f64;
+
+        let mut mean = vec![0.0f64; input_dim];
+        for v in sample {
+            mean.iter_mut().zip(v.as_ref()).for_each(|(m, &x)| *m += x as f64 / n);
+        }
+
+        // Covariance rows, computed in parallel with the `rayon` feature
+        let dims: Vec<usize> = (0..input_dim).collect();
+        let covariance: Vec<Vec<f64>> = parallel::map(&dims, |&i| {
+            let mut row = vec![0.0f64; input_dim];
+            for v in sample {
+                let v = v.as_ref();
+                let xi = v[i] as f64 - mean[i];
+                row.iter_mut()
+                    .zip(v)
+                    .zip(&mean)
+                    .for_each(|((r, &x), m)| *r += xi * (x as f64 - m));
+            }
+            let denom = (n - 1.0).max(1.0);
+            row.iter_mut().for_each(|r| *r /= denom);
+            row
+        });
+
+        // Orthogonal iteration towards the top eigenvectors
+        let mut rng = Rng::new(seed);
+        let mut basis: Vec<Vec<f64>> = (0..output_dim)
+            .map(|_| (0..input_dim).map(|_| rng.next_f64() - 0.5).collect())
+            .collect();
+        orthonormalize(&mut basis);
+        for _ in 0..PCA_ITERATIONS {
+            basis = parallel::map(&basis, |q| mat_vec(&covariance, q));
+            orthonormalize(&mut basis);
+        }
+
+        let mut components: Vec<(f64, Vec<f64>)> = basis
+            .into_iter()
+            .map(|q| (dot(&q, &mat_vec(&covariance, &q)), q))
+            .collect();
+        components.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(Self {
+            mean: mean.iter().map(|&m| m as f32).collect(),
+            explained_variance: components.iter().map(|(var, _)| *var as f32).collect(),
+            components: components
+                .into_iter()
+                .map(|(_, q)| q.into_iter().map(|x| x as f32).collect())
+                .collect(),
+        })
+    }
+
+    /// Sparse random projection (Achlioptas) from `input_dim` to `output_dim`.
+    ///
+    /// Needs no sample data; distances are approximately preserved.
+    pub fn random_projection(input_dim: usize, output_dim: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let scale = (3.0 / output_dim.max(1) as f64).sqrt() as f32;
+        let components = (0..output_dim)
+            .map(|_| {
+                (0..input_dim)
+                    .map(|_| match rng.below(6) {
+                        0 => scale,
+                        1 => -scale,
+                        _ => 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            mean: vec![0.0; input_dim],
+            components,
+            explained_variance: Vec::new(),
+        }
+    }
+
+    /// Dimension of vectors accepted by [`DimReducer::transform`]
+    pub fn input_dim(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// Dimension of reduced vectors
+    pub fn output_dim(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Reduce one vector
+    pub fn transform(&self, vector: &[f32]) -> Result<Vec<f32>> {
+        if vector.len() != self.input_dim() {
+            return Err(CasperError::InvalidDimension {
+                expected: self.input_dim(),
+                actual: vector.len(),
+            });
+        }
+
+        let centered: Vec<f32> = vector.iter().zip(&self.mean).map(|(x, m)| x - m).collect();
+        Ok(self
+            .components
+            .iter()
+            .map(|c| c.iter().zip(&centered).map(|(a, b)| a * b).sum())
+            .collect())
+    }
+
+    /// Write the reducer to `path` as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Load a reducer written by [`DimReducer::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+fn check_sample<V: AsRef<[f32]>>(sample: &[V], output_dim: usize) -> Result<usize> {
+    let Some(first) = sample.first() else {
+        return Err(CasperError::InvalidArgument("PCA needs a non-empty sample".to_string()));
+    };
+    let input_dim = first.as_ref().len();
+    if output_dim == 0 || output_dim > input_dim {
+        return Err(CasperError::InvalidArgument(format!(
+            "PCA output dimension must be in 1..={}, got {}",
+            input_dim, output_dim
+        )));
+    }
+    if let Some(bad) = sample.iter().find(|v| v.as_ref().len() != input_dim) {
+        return Err(CasperError::InvalidDimension { expected: input_dim, actual: bad.as_ref().len() });
+    }
+    Ok(input_dim)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    matrix.iter().map(|row| dot(row, v)).collect()
+}
+
+/// Modified Gram-Schmidt, in place
+fn orthonormalize(basis: &mut [Vec<f64>]) {
+    for i in 0..basis.len() {
+        let (done, rest) = basis.split_at_mut(i);
+        let v = &mut rest[0];
+        for q in done.iter() {
+            let proj = dot(v, q);
+            v.iter_mut().zip(q).for_each(|(x, y)| *x -= proj * y);
+        }
+        let norm = dot(v, v).sqrt();
+        if norm > 0.0 {
+            v.iter_mut().for_each(|x| *x /= norm);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pca_finds_dominant_axis() {
+        // Points spread along (1, 1, 0) with small noise on the other axes
+        let sample: Vec<Vec<f32>> = (0..100)
+            .map(|i| {
+                let t = i as f32 / 10.0 - 5.0;
+                let noise = ((i * 7) % 3) as f32 * 0.01;
+                vec![t + noise, t - noise, noise]
+            })
+            .collect();
+
+        let pca = DimReducer::fit_pca(&sample, 1, 7).unwrap();
+        let axis = &pca.components[0];
+        let expected = 1.0 / 2.0f32.sqrt();
+        assert!((axis[0].abs() - expected).abs() < 0.01);
+        assert!((axis[1].abs() - expected).abs() < 0.01);
+        assert!(axis[2].abs() < 0.01);
+
+        let reduced = pca.transform(&[1.0, 1.0, 0.0]).unwrap();
+        assert_eq!(reduced.len(), 1);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::error::{CasperError, Result};
+use reqwest::header::HeaderValue;
+use reqwest::{Method, Request, Response};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Header carrying the idempotency key of a retried write
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// How often failed requests are retried, separately for reads and writes.
+///
+/// Nothing is retried by default. [`RetryPolicy::read_attempts`] opts in for
+/// idempotent reads (searches and GETs), [`RetryPolicy::write_attempts`] for
+/// writes; retried writes carry an `Idempotency-Key` header, identical across
+/// attempts, so a server honouring it applies the write at most once.
+///
+/// Only transport failures (connect errors, timeouts) and 429/502/503/504
+/// responses are retried, with exponential backoff. A `Retry-After` header on
+/// a 429 or 503 response replaces the backoff; if it asks for a longer wait
+/// than [`RetryPolicy::max_backoff`], the response is returned instead.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    read_attempts: u32,
+    write_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            read_attempts: 1,
+            write_attempts: 1,
+            backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request exactly once (the default)
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Total attempts for idempotent reads (default 1, i.e. no retries)
+    pub fn read_attempts(mut self, attempts: u32) -> Self {
+        self.read_attempts = attempts.max(1);
+        self
+    }
+
+    /// Total attempts for writes (default 1, i.e. no retries)
+    pub fn write_attempts(mut self, attempts: u32) -> Self {
+        self.write_attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry, doubled for each further one (default 50ms)
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the retry delay (default 2s)
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Attempts allowed for `request`, tagging retryable writes with an
+    /// idempotency key
+    pub(crate) fn prepare(&self, request: &mut Request) -> u32 {
+        if is_idempotent_read(request) {
+            return self.read_attempts;
+        }
+        if self.write_attempts > 1 && !request.headers().contains_key(IDEMPOTENCY_KEY_HEADER) {
+            let key = HeaderValue::from_str(&idempotency_key()).expect("hex is a valid header value");
+            request.headers_mut().insert(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        self.write_attempts
+    }
+
+    /// Whether `result` of an attempt warrants another one
+    pub(crate) fn should_retry(result: &Result<Response>) -> bool {
+        match result {
+            Ok(response) => matches!(response.status().as_u16(), 429 | 502..=504),
+            Err(CasperError::Http(e)) => e.is_connect() || e.is_timeout(),
+            Err(_) => false,
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based) after `result`, `None` if
+    /// the server asked to wait longer than the maximum backoff
+    pub(crate) fn delay(&self, retry: u32, result: &Result<Response>) -> Option<Duration> {
+        match result.as_ref().ok().and_then(retry_after) {
+            Some(wait) if wait > self.max_backoff => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff.saturating_mul(1 << (retry - 1).min(16)).min(self.max_backoff)),
+        }
+    }
+}
+
+/// Wait requested by the `Retry-After` header of a 429 or 503 response,
+/// given in seconds or as an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(response.status().as_u16(), 429 | 503) {
+        return None;
+    }
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(parse_http_date(value)?);
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// Unix seconds of an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_http_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    let mut parts = date.split_whitespace().skip(1);
+    let day: u64 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let year: u64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if parts.next() != Some("GMT") || year < 1970 {
+        return None;
+    }
+
+    // Days since the epoch of a proleptic Gregorian date (Howard Hinnant's algorithm)
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y / 400;
+    let yoe = y % 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era * 146_097 + doe).checked_sub(719_468)?;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// POST endpoints that only read
+const READ_ONLY_POSTS: [&str; 8] = [
+    "/search",
+    "/search/batch",
+    "/search/grouped",
+    "/search/range",
+    "/search/hybrid",
+    "/search/sparse",
+    "/recommend",
+    "/contains",
+];
+
+/// Whether `request` is an idempotent read (GET, search, recommendation or
+/// existence check) that may be sent more than once
+pub(crate) fn is_idempotent_read(request: &Request) -> bool {
+    let path = request.url().path();
+    request.method() == Method::GET
+        || (request.method() == Method::POST && READ_ONLY_POSTS.iter().any(|suffix| path.ends_with(suffix)))
+}
+
+/// Random 128-bit hex key, unique per call
+fn idempotency_key() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    let mut parts = [0u64; 2];
+    for part in &mut parts {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u64(nanos);
+        *part = hasher.finish();
+    }
+    format!("{:016x}{:016x}", parts[0], parts[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_scope() {
+        let policy = RetryPolicy::default().read_attempts(3).write_attempts(3);
+        let client = reqwest::Client::new();
+
+        let mut search = client.post("http://localhost/collection/docs/search").build().unwrap();
+        assert_eq!(policy.prepare(&mut search), 3);
+        assert!(!search.headers().contains_key(IDEMPOTENCY_KEY_HEADER));
+
+        let contains = client.post("http://localhost/collection/docs/contains").build().unwrap();
+        assert!(is_idempotent_read(&contains));
+        let batch = client.post("http://localhost/collection/docs/search/batch").build().unwrap();
+        assert!(is_idempotent_read(&batch));
+
+        let mut insert = client.post("http://localhost/collection/docs/insert").build().unwrap();
+        assert_eq!(policy.prepare(&mut insert), 3);
+        let key = insert.headers()[IDEMPOTENCY_KEY_HEADER].clone();
+        assert_eq!(key.len(), 32);
+        // Re-preparing a retried copy keeps the key
+        policy.prepare(&mut insert);
+        assert_eq!(insert.headers()[IDEMPOTENCY_KEY_HEADER], key);
+
+        let mut delete = client.delete("http://localhost/collection/docs/delete").build().unwrap();
+        assert_eq!(RetryPolicy::default().prepare(&mut delete), 1);
+        assert!(!delete.headers().contains_key(IDEMPOTENCY_KEY_HEADER));
+
+        let failed = Err(CasperError::Unknown("reset".to_string()));
+        assert_eq!(policy.delay(1, &failed), Some(Duration::from_millis(50)));
+        assert_eq!(policy.delay(3, &failed), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(30, &failed), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let policy = RetryPolicy::default().read_attempts(3);
+        let response = |status: u16, retry_after: &str| {
+            let response = http::Response::builder().status(status).header("Retry-After", retry_after);
+            Ok(Response::from(response.body("").unwrap()))
+        };
+
+        assert_eq!(policy.delay(1, &response(429, "1")), Some(Duration::from_secs(1)));
+        // Longer than the maximum backoff: give up instead of waiting
+        assert_eq!(policy.delay(1, &response(503, "120")), None);
+        // Only 429 and 503 carry a meaningful Retry-After
+        assert_eq!(policy.delay(1, &response(502, "1")), Some(Duration::from_millis(50)));
+        // A date in the past means "now"
+        assert_eq!(policy.delay(1, &response(429, "Sun, 06 Nov 1994 08:49:37 GMT")), Some(Duration::ZERO));
+
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("tomorrow"), None);
+    }
+}
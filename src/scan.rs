@@ -0,0 +1,121 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::collection::CollectionHandle;
+use crate::models::GetVectorResponse;
+use crate::paginate::{Page, Paginated};
+use reqwest::Method;
+use serde::Deserialize;
+use std::convert::identity;
+
+/// Vectors fetched per scan request
+const SCAN_PAGE_SIZE: usize = 1000;
+
+/// One page of `GET collection/<name>/scan`
+#[derive(Debug, Deserialize)]
+struct ScanResponse {
+    vectors: Vec<GetVectorResponse>,
+    /// ID the next page starts after, absent on the last page
+    #[serde(default)]
+    next_cursor: Option<u64>,
+}
+
+impl CasperClient {
+    /// Stream every vector of a collection as `(id, vector)`, in ID order.
+    ///
+    /// Pages of 1000 vectors are fetched as the stream is consumed, each
+    /// resuming after the cursor the server returned with the previous one,
+    /// so a collection can be dumped or migrated without knowing its IDs.
+    /// Vectors inserted or deleted during the scan may or may not be seen.
+    ///
+    /// ```no_run
+    /// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+    /// use tokio_stream::StreamExt;
+    ///
+    /// let mut vectors = client.scan_vectors("docs");
+    /// while let Some((id, vector)) = vectors.next().await.transpose()? {
+    ///     println!("{}: {} dimensions", id, vector.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scan_vectors<'a>(&'a self, collection_name: &'a str) -> Paginated<'a, (u32, Vec<f32>)> {
+        self.scan_pages(collection_name, None, |record| (record.id, record.vector))
+    }
+
+    /// [`CasperClient::scan_vectors`] keeping the payload stored with each
+    /// vector
+    pub fn scan_records<'a>(&'a self, collection_name: &'a str) -> Paginated<'a, GetVectorResponse> {
+        self.scan_snapshot(collection_name, None)
+    }
+
+    /// [`CasperClient::scan_records`] as of server snapshot `snapshot`, or
+    /// live without one
+    pub(crate) fn scan_snapshot<'a>(
+        &'a self,
+        collection_name: &'a str,
+        snapshot: Option<u64>,
+    ) -> Paginated<'a, GetVectorResponse> {
+        self.scan_pages(collection_name, snapshot, identity)
+    }
+
+    fn scan_pages<'a, T>(
+        &'a self,
+        collection_name: &'a str,
+        snapshot: Option<u64>,
+        item: fn(GetVectorResponse) -> T,
+    ) -> Paginated<'a, T>
+    where
+        T: Send + 'a,
+    {
+        Paginated::new(move |cursor| {
+            Box::pin(async move {
+                let url = self.collection_url(collection_name, "/scan")?;
+                let mut query = vec![("limit", SCAN_PAGE_SIZE.to_string())];
+                if let Some(cursor) = cursor {
+                    query.push(("cursor", cursor.to_string()));
+                }
+                if let Some(snapshot) = snapshot {
+                    query.push(("snapshot", snapshot.to_string()));
+                }
+                let response = self
+                    .http(Scope::Read, Method::GET, url)
+                    .query(&query)
+                    .header("Accept", "application/json")
+                    .dispatch(self)
+                    .await?;
+                let page: ScanResponse = self.handle_response(response).await?;
+                Ok(Page {
+                    items: page.vectors.into_iter().map(item).collect(),
+                    next: page.next_cursor,
+                })
+            })
+        })
+    }
+}
+
+impl CollectionHandle {
+    /// [`CasperClient::scan_vectors`] on this collection
+    pub fn scan_vectors(&self) -> Paginated<'_, (u32, Vec<f32>)> {
+        self.client.scan_vectors(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_scan_vectors() {
+        let scripted = Scripted::new([
+            (200, r#"{"vectors":[{"id":1,"vector":[1.0,0.0]},{"id":4,"vector":[0.0,1.0]}],"next_cursor":4}"#),
+            (200, r#"{"vectors":[{"id":9,"vector":[0.5,0.5]}]}"#),
+        ]);
+        let client = scripted.client();
+
+        let vectors = client.collection("docs").scan_vectors().collect_all().await.unwrap();
+        let ids: Vec<u32> = vectors.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [1, 4, 9]);
+        assert_eq!(scripted.last_query("cursor").as_deref(), Some("4"));
+        assert_eq!(scripted.requests(), ["GET /collection/docs/scan", "GET /collection/docs/scan"]);
+    }
+}
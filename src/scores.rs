@@ -0,0 +1,199 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::models::{CollectionInfo, IndexInfo, SearchRequest, SearchResponse};
+
+/// How search scores are reported.
+///
+/// Servers score by the index metric: inner products and cosines grow with
+/// similarity, L2 distances shrink with it. Rescaling gives every collection
+/// the same semantics, so one threshold works across metrics:
+///
+/// | metric          | raw score `s` | `Similarity`     | `Distance` |
+/// |-----------------|---------------|------------------|------------|
+/// | `inner-product` | dot product   | `(s + 1) / 2`    | `1 - s`    |
+/// | `cosine`        | cosine        | `(s + 1) / 2`    | `1 - s`    |
+/// | `l2`            | distance      | `1 / (1 + s)`    | `s`        |
+///
+/// Similarities are clamped to `[0, 1]`; inner products only map into that
+/// range for normalized vectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScoreScale {
+    /// Scores as the server returns them
+    #[default]
+    Raw,
+    /// Similarity in `[0, 1]`, higher is closer
+    Similarity,
+    /// Distance, lower is closer
+    Distance,
+}
+
+impl ScoreScale {
+    /// Rescale a raw `score` of an index using `metric`
+    pub fn convert(self, metric: &str, score: f32) -> Result<f32> {
+        Ok(match (self, grows(metric)?) {
+            (ScoreScale::Raw, _) => score,
+            (ScoreScale::Similarity, true) => ((score + 1.0) / 2.0).clamp(0.0, 1.0),
+            (ScoreScale::Similarity, false) => 1.0 / (1.0 + score.max(0.0)),
+            (ScoreScale::Distance, true) => 1.0 - score,
+            (ScoreScale::Distance, false) => score,
+        })
+    }
+
+    /// Rescale the scores of `results` in place
+    pub fn apply(self, metric: &str, results: &mut SearchResponse) -> Result<()> {
+        for result in results.iter_mut() {
+            result.score = self.convert(metric, result.score)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether raw scores of `metric` grow with similarity
+fn grows(metric: &str) -> Result<bool> {
+    match metric {
+        "inner-product" | "ip" | "cosine" => Ok(true),
+        "l2" | "euclidean" => Ok(false),
+        _ => Err(CasperError::InvalidArgument(format!("cannot rescale scores of metric '{}'", metric))),
+    }
+}
+
+/// Client-side cutoffs of a search, [`SearchRequest::score_threshold`] and
+/// [`SearchRequest::max_distance`], compared by the collection's metric
+#[derive(Debug, Default)]
+pub(crate) struct Cutoffs {
+    /// `None` without cutoffs
+    metric: Option<String>,
+    score_threshold: Option<f32>,
+    max_distance: Option<f32>,
+}
+
+impl Cutoffs {
+    /// Whether a result with the raw `score` makes both cutoffs
+    pub(crate) fn keeps(&self, score: f32) -> bool {
+        let Some(metric) = &self.metric else {
+            return true;
+        };
+        // The metric was checked when resolving the cutoffs
+        let closer = |threshold| if grows(metric).unwrap_or(true) { score >= threshold } else { score <= threshold };
+        let within = |max| ScoreScale::Distance.convert(metric, score).is_ok_and(|distance| distance <= max);
+        self.score_threshold.is_none_or(closer) && self.max_distance.is_none_or(within)
+    }
+
+    /// Drop the `results` missing a cutoff
+    pub(crate) fn apply(&self, results: &mut SearchResponse) {
+        results.retain(|result| self.keeps(result.score));
+    }
+}
+
+impl IndexInfo {
+    /// Metric of the HNSW or IVF-PQ index
+    pub fn metric(&self) -> Option<&str> {
+        self.hnsw
+            .as_ref()
+            .map(|hnsw| hnsw.metric.as_str())
+            .or_else(|| self.ivf_pq.as_ref().map(|ivf| ivf.metric.as_str()))
+    }
+}
+
+/// Metric the scores of collection `info` are rescaled by
+pub(crate) fn index_metric(info: &CollectionInfo) -> Result<&str> {
+    info.index.as_ref().and_then(|index| index.metric()).ok_or_else(|| {
+        CasperError::InvalidArgument(format!("collection '{}' has no index metric to rescale scores by", info.name))
+    })
+}
+
+impl CasperClient {
+    /// Cutoffs of `request` on `collection_name`, fetching the collection's
+    /// index metric if it has any
+    pub(crate) async fn cutoffs(&self, collection_name: &str, request: &SearchRequest) -> Result<Cutoffs> {
+        let (score_threshold, max_distance) = (request.score_threshold, request.max_distance);
+        if score_threshold.is_none() && max_distance.is_none() {
+            return Ok(Cutoffs::default());
+        }
+        let metric = self.collection_metric(collection_name).await?;
+        grows(&metric)?;
+        Ok(Cutoffs { metric: Some(metric), score_threshold, max_distance })
+    }
+
+    /// Rescale `results` of a search on `collection_name` to `scale`,
+    /// fetching the collection's index metric unless `scale` is raw
+    pub(crate) async fn rescale(
+        &self,
+        collection_name: &str,
+        scale: ScoreScale,
+        results: &mut SearchResponse,
+    ) -> Result<()> {
+        if scale == ScoreScale::Raw {
+            return Ok(());
+        }
+        let metric = self.collection_metric(collection_name).await?;
+        scale.apply(&metric, results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SearchRequest, SearchResult};
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_score_scale() {
+        let mut results = vec![SearchResult { id: 1, score: 0.5 }, SearchResult { id: 2, score: -1.0 }];
+        ScoreScale::Similarity.apply("cosine", &mut results).unwrap();
+        assert_eq!((results[0].score, results[1].score), (0.75, 0.0));
+
+        assert_eq!(ScoreScale::Similarity.convert("l2", 3.0).unwrap(), 0.25);
+        assert_eq!(ScoreScale::Distance.convert("inner-product", 0.25).unwrap(), 0.75);
+        assert_eq!(ScoreScale::Raw.convert("l2", 3.0).unwrap(), 3.0);
+        assert!(ScoreScale::Similarity.convert("hamming", 3.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_score_scale() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":3.0}]"#),
+            (200, r#"{"name":"docs","dimension":1,"mutable":true,"has_index":true,"max_size":10,"size":1,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+        let results = client.query("docs", SearchRequest { vector: vec![1.0], ..Default::default() }).await.unwrap();
+        assert_eq!(results[0].score, 0.25);
+        assert_eq!(scripted.requests(), ["POST /collection/docs/search", "GET /collection/docs"]);
+    }
+
+    #[tokio::test]
+    async fn test_client_score_scale_on_fallback() {
+        let docs = r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+            "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+            "normalization":false}}"#;
+        let scripted = Scripted::new([
+            (200, r#"{"results":[{"id":1,"score":0.25},{"id":2,"score":1.0}]}"#),
+            (200, docs),
+            (501, "not implemented"),
+            (200, docs),
+            (200, r#"[{"id":1,"score":0.25},{"id":2,"score":1.0}]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        // Native range search and its emulation report the same scores
+        let native = client.search_range("docs", vec![1.0, 0.0], 1.0).await.unwrap();
+        let emulated = client.search_range("docs", vec![1.0, 0.0], 1.0).await.unwrap();
+        let scores = |results: &SearchResponse| results.iter().map(|r| r.score).collect::<Vec<_>>();
+        assert_eq!(scores(&native), [0.8, 0.5]);
+        assert_eq!(scores(&native), scores(&emulated));
+        assert_eq!(
+            scripted.requests()[2..],
+            ["POST /collection/docs/search/range", "GET /collection/docs", "POST /collection/docs/search"]
+        );
+    }
+}
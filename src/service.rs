@@ -0,0 +1,137 @@
+//! The client's HTTP layer as a [`tower::Service`].
+//!
+//! Every HTTP call of [`CasperClient`] ends in one service taking a built
+//! [`reqwest::Request`]: retries, signing, hedging and slow-call tracking
+//! happen inside it. Standard tower middleware (timeouts, concurrency
+//! limits, load shedding, buffering) can be wrapped around it for all calls
+//! with [`CasperClientBuilder::http_layer`](crate::CasperClientBuilder::http_layer),
+//! or around [`CasperClient::service`] to send requests of your own.
+
+use crate::auth::Scope;
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use reqwest::{Method, Request, RequestBuilder, Response};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::util::BoxCloneSyncService;
+use tower::{Layer, Service, ServiceExt};
+
+/// Error type of tower middleware
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The client's HTTP layer, including any middleware added with
+/// [`CasperClientBuilder::http_layer`](crate::CasperClientBuilder::http_layer)
+pub type HttpService = BoxCloneSyncService<Request, Response, CasperError>;
+
+/// Innermost HTTP service: sends a built request through the client's
+/// request pipeline
+#[derive(Debug, Clone)]
+pub struct CasperService {
+    client: CasperClient,
+}
+
+impl Service<Request> for CasperService {
+    type Response = Response;
+    type Error = CasperError;
+    type Future = Pin<Box<dyn Future<Output = Result<Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let client = self.client.clone();
+        Box::pin(async move { client.execute_request(request).await })
+    }
+}
+
+/// Middleware added on the builder, applied in order when the client is built
+pub(crate) struct HttpLayers(Vec<Box<dyn FnOnce(HttpService) -> HttpService + Send>>);
+
+impl HttpLayers {
+    pub(crate) fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub(crate) fn push<L>(&mut self, layer: L)
+    where
+        L: Layer<HttpService> + Send + 'static,
+        L::Service: Service<Request, Response = Response> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Error: Into<BoxError>,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.0.push(Box::new(move |inner| {
+            HttpService::new(layer.layer(inner).map_err(|e| middleware_error(e.into())))
+        }));
+    }
+
+    /// Wrap the client's pipeline in the layers; `None` without any, so
+    /// requests skip the boxing
+    pub(crate) fn apply(self, client: &CasperClient) -> Option<HttpService> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let base = HttpService::new(CasperService { client: client.clone() });
+        Some(self.0.into_iter().fold(base, |service, layer| layer(service)))
+    }
+}
+
+impl fmt::Debug for HttpLayers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HttpLayers({})", self.0.len())
+    }
+}
+
+/// Recover a [`CasperError`] passed through middleware, wrap anything else
+fn middleware_error(error: BoxError) -> CasperError {
+    match error.downcast::<CasperError>() {
+        Ok(error) => *error,
+        Err(error) => CasperError::Middleware(error),
+    }
+}
+
+impl CasperClient {
+    /// The HTTP layer as a [`tower::Service`], with the configured
+    /// middleware, for composing further middleware or sending requests
+    /// built with [`CasperClient::request`]
+    pub fn service(&self) -> HttpService {
+        match &self.middleware {
+            Some(service) => service.clone(),
+            None => HttpService::new(CasperService { client: self.clone() }),
+        }
+    }
+
+    /// Start a request to `path` (relative to the base URL) carrying the
+    /// credentials for `scope`, to be built and sent through
+    /// [`CasperClient::service`]
+    pub fn request(&self, scope: Scope, method: Method, path: &str) -> Result<RequestBuilder> {
+        let url = self.base_url.join(path)?;
+        Ok(self.http(scope, method, url))
+    }
+
+    /// Send a built request through the middleware, if any, then the
+    /// request pipeline
+    pub(crate) async fn dispatch_request(&self, request: Request) -> Result<Response> {
+        match &self.middleware {
+            Some(service) => service.clone().oneshot(request).await,
+            None => self.execute_request(request).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_middleware_error() {
+        let passed: BoxError = Box::new(CasperError::IndexCreationInProgress);
+        assert!(matches!(middleware_error(passed), CasperError::IndexCreationInProgress));
+
+        let foreign: BoxError = Box::new(io::Error::other("overloaded"));
+        assert!(matches!(middleware_error(foreign), CasperError::Middleware(_)));
+    }
+}
@@ -0,0 +1,140 @@
+//! Read-your-writes sessions.
+//!
+//! Servers with read-your-writes support report the sequence number of the
+//! last write they applied in the [`SEQUENCE_HEADER`] of their responses,
+//! and hold back reads sent with [`MIN_SEQUENCE_HEADER`] until they have
+//! applied that write. A [`Session`] remembers the highest sequence number
+//! it was sent and attaches it to all its requests, so searches made right
+//! after an insert see the inserted vector. Servers without the support
+//! send no sequence numbers, and session requests then go out unchanged.
+//!
+//! Session searches skip the client's search cache and coalescing, whose
+//! results may predate the session's writes.
+
+use crate::client::CasperClient;
+use reqwest::header::HeaderValue;
+use reqwest::{Request, Response};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Response header carrying the sequence number of the server's last write
+pub const SEQUENCE_HEADER: &str = "x-casper-sequence";
+/// Request header asking the server to apply writes up to a sequence
+/// number before answering
+pub const MIN_SEQUENCE_HEADER: &str = "x-casper-min-sequence";
+
+/// Highest sequence number seen by a session, 0 while none
+#[derive(Debug, Default)]
+pub(crate) struct SessionToken {
+    sequence: AtomicU64,
+}
+
+impl SessionToken {
+    pub(crate) fn observe(&self, response: &Response) {
+        let sequence = response
+            .headers()
+            .get(SEQUENCE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        if let Some(sequence) = sequence {
+            self.sequence.fetch_max(sequence, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn attach(&self, request: &mut Request) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        if sequence > 0 {
+            request.headers_mut().insert(MIN_SEQUENCE_HEADER, HeaderValue::from(sequence));
+        }
+    }
+}
+
+/// A client whose reads see the writes made through it, see
+/// [`crate::session`]. Clones share the session.
+///
+/// ```no_run
+/// # async fn run(client: casper_client::CasperClient) -> casper_client::Result<()> {
+/// use casper_client::{InsertRequest, SearchRequest};
+///
+/// let session = client.session();
+/// session.insert_vector("docs", InsertRequest { id: 1, vector: vec![0.5, 0.5], payload: None }).await?;
+/// let results = session.query("docs", SearchRequest { vector: vec![0.5, 0.5], ..Default::default() }).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Session {
+    client: CasperClient,
+}
+
+impl Session {
+    /// Sequence number attached to the session's requests, `None` until
+    /// the server reported one
+    pub fn token(&self) -> Option<u64> {
+        let token = self.client.session.as_ref()?;
+        Some(token.sequence.load(Ordering::Relaxed)).filter(|&sequence| sequence > 0)
+    }
+
+    /// Also wait for the writes up to `token`, e.g. the token of a session
+    /// in another process
+    pub fn advance(&self, token: u64) {
+        if let Some(session) = &self.client.session {
+            session.sequence.fetch_max(token, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Deref for Session {
+    type Target = CasperClient;
+
+    fn deref(&self) -> &CasperClient {
+        &self.client
+    }
+}
+
+impl CasperClient {
+    /// Start a read-your-writes session on a copy of this client
+    pub fn session(&self) -> Session {
+        Session { client: CasperClient { session: Some(Arc::new(SessionToken::default())), ..self.clone() } }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InsertRequest, SearchRequest};
+    use crate::testing::Scripted;
+    use crate::SearchCachePolicy;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_session_token() {
+        let scripted = Scripted::new([(200, ""), (200, "[]")]).header(SEQUENCE_HEADER, "42");
+        let session = scripted.client().session();
+        assert_eq!(session.token(), None);
+
+        session.insert_vector("docs", InsertRequest { id: 1, vector: vec![0.5], payload: None }).await.unwrap();
+        assert_eq!(session.token(), Some(42));
+        session.query("docs", SearchRequest { vector: vec![0.5], ..Default::default() }).await.unwrap();
+        assert_eq!(scripted.last_header(MIN_SEQUENCE_HEADER).as_deref(), Some("42"));
+    }
+
+    #[tokio::test]
+    async fn test_session_skips_search_cache() {
+        let scripted = Scripted::new([(200, "[]"), (200, r#"[{"id":1,"score":0.9}]"#), (200, "[]")]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .search_cache(SearchCachePolicy::new(10, Duration::from_secs(60)))
+            .coalesce_searches(true)
+            .build()
+            .unwrap();
+        let search = || SearchRequest { vector: vec![0.5], ..Default::default() };
+
+        assert!(client.query("docs", search()).await.unwrap().is_empty());
+        let session = client.session();
+        assert_eq!(session.query("docs", search()).await.unwrap().len(), 1);
+        assert!(client.query("docs", search()).await.unwrap().is_empty());
+        assert_eq!(scripted.requests().len(), 2);
+    }
+}
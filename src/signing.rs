@@ -0,0 +1,131 @@
+use crate::error::{CasperError, Result};
+use hmac::{Hmac, Mac};
+use reqwest::Request;
+use reqwest::header::HeaderValue;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the unix timestamp (seconds) the signature was made at
+pub const TIMESTAMP_HEADER: &str = "x-casper-timestamp";
+/// Header carrying the hex SHA-256 of the request body
+pub const CONTENT_HASH_HEADER: &str = "x-casper-content-sha256";
+/// Header carrying the hex HMAC-SHA256 signature
+pub const SIGNATURE_HEADER: &str = "x-casper-signature";
+/// Header naming the shared secret used, when a key ID is configured
+pub const KEY_ID_HEADER: &str = "x-casper-key-id";
+
+/// Body hash sent for streamed bodies, which cannot be hashed up front
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// HMAC-SHA256 request signer for deployments behind a signing gateway.
+///
+/// Every HTTP request gets a signature over the canonical string
+///
+/// ```text
+/// METHOD \n PATH?QUERY \n TIMESTAMP \n HEX(SHA256(BODY))
+/// ```
+///
+/// keyed with the shared secret, sent in `x-casper-signature` along with
+/// the timestamp and body hash headers it covers.
+#[derive(Clone)]
+pub struct RequestSigner {
+    secret: Vec<u8>,
+    key_id: Option<String>,
+}
+
+impl RequestSigner {
+    /// Signer using the shared `secret`
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self { secret: secret.into(), key_id: None }
+    }
+
+    /// Send `key_id` in `x-casper-key-id` so the gateway can pick the secret
+    pub fn key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    /// Sign `request` in place at the current time
+    pub(crate) fn sign(&self, request: &mut Request) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let body_hash = match request.body() {
+            Some(body) => match body.as_bytes() {
+                Some(bytes) => hex::encode(Sha256::digest(bytes)),
+                None => UNSIGNED_PAYLOAD.to_string(),
+            },
+            None => hex::encode(Sha256::digest(b"")),
+        };
+
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let signature = self.signature(request.method().as_str(), &path, timestamp, &body_hash);
+
+        let headers = request.headers_mut();
+        headers.insert(TIMESTAMP_HEADER, HeaderValue::from(timestamp));
+        headers.insert(CONTENT_HASH_HEADER, header_value(&body_hash)?);
+        headers.insert(SIGNATURE_HEADER, header_value(&signature)?);
+        if let Some(key_id) = &self.key_id {
+            headers.insert(KEY_ID_HEADER, header_value(key_id)?);
+        }
+        Ok(())
+    }
+
+    /// Hex HMAC-SHA256 of the canonical request string
+    fn signature(&self, method: &str, path: &str, timestamp: u64, body_hash: &str) -> String {
+        let canonical = format!("{}\n{}\n{}\n{}", method, path, timestamp, body_hash);
+        // HMAC accepts keys of any length
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC key of any length");
+        mac.update(canonical.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value)
+        .map_err(|_| CasperError::InvalidArgument(format!("invalid signing header value '{}'", value)))
+}
+
+// The secret must never end up in logs through `{:?}` of the client
+impl fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSigner")
+            .field("secret", &"<redacted>")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let signer = RequestSigner::new("secret").key_id("k1");
+        let empty_hash = hex::encode(Sha256::digest(b""));
+        assert_eq!(
+            signer.signature("GET", "/collections", 1700000000, &empty_hash),
+            "06efb133579c7584f5d32df3ecd63178850172d002131095380dfd9bcf5b1a89"
+        );
+
+        let mut request = reqwest::Client::new()
+            .post("http://localhost:8080/collection/docs/search?limit=5")
+            .body("{}")
+            .build()
+            .unwrap();
+        signer.sign(&mut request).unwrap();
+        let headers = request.headers();
+        assert_eq!(headers[KEY_ID_HEADER], "k1");
+        assert_eq!(headers[CONTENT_HASH_HEADER], hex::encode(Sha256::digest(b"{}")).as_str());
+        assert_eq!(headers[SIGNATURE_HEADER].len(), 64);
+    }
+}
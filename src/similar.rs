@@ -0,0 +1,209 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::models::{RecommendBody, SearchRequest, SearchResponse};
+use crate::scores::ScoreScale;
+use crate::vector;
+use crate::version::ApiVersion;
+use reqwest::Method;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+impl CasperClient {
+    /// "More like this": the `limit` nearest neighbours of the stored vector
+    /// `id`, excluding `id` itself.
+    ///
+    /// The server is asked to search with the stored vector directly; servers
+    /// without that endpoint (API 1.0, or answering 404, 405 or 501) get the
+    /// vector fetched and sent back as a regular search. A missing vector yields
+    /// [`CasperError::VectorNotFound`]. Scores are on the client's
+    /// [`ScoreScale`] either way.
+    pub async fn search_by_id(&self, collection_name: &str, id: u32, limit: usize) -> Result<SearchResponse> {
+        let response = if self.supports_api(ApiVersion::V1_1) {
+            let url = self.collection_url(collection_name, &format!("/vector/{}/search", id))?;
+            let response = self
+                .http(Scope::Read, Method::POST, url)
+                .query(&[
+                    ("limit", (limit + 1).to_string()),
+                    ("output", self.search_encoding.output().to_string()),
+                ])
+                .header("Accept", self.search_encoding.accept())
+                .dispatch(self)
+                .await?;
+            Some(response).filter(|response| !matches!(response.status().as_u16(), 404 | 405 | 501))
+        } else {
+            None
+        };
+
+        let results = if let Some(response) = response {
+            self.handle_search_response(response).await?
+        } else {
+            let vector = self
+                .get_vector(collection_name, id)
+                .await?
+                .ok_or(CasperError::VectorNotFound(id))?;
+            let request = SearchRequest { vector, limit: Some(limit + 1), ..Default::default() };
+            self.raw_stored_space().query(collection_name, request).await?
+        };
+
+        let mut results: SearchResponse = results.into_iter().filter(|result| result.id != id).take(limit).collect();
+        self.rescale(collection_name, self.score_scale, &mut results).await?;
+        Ok(results)
+    }
+
+    /// The `limit` vectors most like the `positives` and least like the
+    /// `negatives` (stored vector IDs), excluding the examples themselves.
+    ///
+    /// The server builds the query when it supports recommendations (API
+    /// 1.1); otherwise the examples are fetched and combined client-side with
+    /// [`vector::recommendation_query`]. Scores are on the client's
+    /// [`ScoreScale`] either way.
+    pub async fn recommend(
+        &self,
+        collection_name: &str,
+        positives: &[u32],
+        negatives: &[u32],
+        limit: usize,
+    ) -> Result<SearchResponse> {
+        if positives.is_empty() {
+            return Err(CasperError::InvalidArgument(
+                "recommend requires at least one positive example".to_string(),
+            ));
+        }
+
+        let examples = positives.len() + negatives.len();
+        let response = if self.supports_api(ApiVersion::V1_1) {
+            let url = self.collection_url(collection_name, "/recommend")?;
+            let body = RecommendBody { positive: positives.to_vec(), negative: negatives.to_vec() };
+            let response = self
+                .http(Scope::Read, Method::POST, url)
+                .query(&[
+                    ("limit", (limit + examples).to_string()),
+                    ("output", self.search_encoding.output().to_string()),
+                ])
+                .header("Accept", self.search_encoding.accept())
+                .json(&body)
+                .dispatch(self)
+                .await?;
+            Some(response).filter(|response| !matches!(response.status().as_u16(), 404 | 405 | 501))
+        } else {
+            None
+        };
+
+        let results = if let Some(response) = response {
+            self.handle_search_response(response).await?
+        } else {
+            let positive = self.fetch_vectors(collection_name, positives).await?;
+            let negative = self.fetch_vectors(collection_name, negatives).await?;
+            let request = SearchRequest {
+                vector: vector::recommendation_query(&positive, &negative)?,
+                limit: Some(limit + examples),
+                ..Default::default()
+            };
+            self.raw_stored_space().query(collection_name, request).await?
+        };
+
+        let mut results: SearchResponse = results
+            .into_iter()
+            .filter(|result| !positives.contains(&result.id) && !negatives.contains(&result.id))
+            .take(limit)
+            .collect();
+        self.rescale(collection_name, self.score_scale, &mut results).await?;
+        Ok(results)
+    }
+
+    /// A copy of this client that does not reduce query vectors, for queries
+    /// built from vectors read back from the server (already reduced)
+    pub(crate) fn stored_space(&self) -> CasperClient {
+        CasperClient { reducers: Arc::new(HashMap::new()), ..self.clone() }
+    }
+
+    /// [`CasperClient::stored_space`] reporting raw scores, for fallbacks
+    /// rescaled once at the end like the native path they stand in for
+    pub(crate) fn raw_stored_space(&self) -> CasperClient {
+        CasperClient { score_scale: ScoreScale::Raw, ..self.stored_space() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::Scripted;
+    use crate::{CasperClient, ScoreScale};
+
+    #[tokio::test]
+    async fn test_search_by_id_fallback() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":7,"score":1.0},{"id":3,"score":0.9}]"#),
+            (404, "not found"),
+            (200, r#"{"id":7,"vector":[0.5,0.5]}"#),
+            (200, r#"[{"id":7,"score":1.0},{"id":4,"score":0.8}]"#),
+        ]);
+        let docs = scripted.client().collection("docs");
+
+        assert_eq!(docs.search_by_id(7, 1).await.unwrap()[0].id, 3);
+        // Without the native endpoint the vector is fetched and searched for
+        assert_eq!(docs.search_by_id(7, 1).await.unwrap()[0].id, 4);
+        assert_eq!(
+            scripted.requests(),
+            [
+                "POST /collection/docs/vector/7/search",
+                "POST /collection/docs/vector/7/search",
+                "GET /collection/docs/vector/7",
+                "POST /collection/docs/search",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_by_id_on_client_scale() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":7,"score":0.0},{"id":3,"score":3.0}]"#),
+            (200, r#"{"name":"docs","dimension":2,"mutable":true,"has_index":true,"max_size":10,"size":2,
+                "index":{"hnsw":{"metric":"l2","quantization":"f32","m":16,"m0":32,"ef_construction":200},
+                "ivf_pq":null,"normalization":false}}"#),
+            (501, "not implemented"),
+            (200, r#"{"id":7,"vector":[0.5,0.5]}"#),
+            (200, r#"[{"id":7,"score":0.0},{"id":3,"score":3.0}]"#),
+        ]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .score_scale(ScoreScale::Similarity)
+            .build()
+            .unwrap();
+
+        let native = client.search_by_id("docs", 7, 1).await.unwrap();
+        let fallback = client.search_by_id("docs", 7, 1).await.unwrap();
+        assert_eq!((native[0].score, fallback[0].score), (0.25, 0.25));
+    }
+
+    #[tokio::test]
+    async fn test_recommend() {
+        let scripted = Scripted::new([
+            (200, r#"[{"id":1,"score":1.0},{"id":5,"score":0.9},{"id":6,"score":0.8}]"#),
+            (404, "not found"),
+            (200, r#"{"id":1,"vector":[1.0,0.0]}"#),
+            (200, r#"{"id":2,"vector":[0.0,1.0]}"#),
+            (200, r#"[{"id":1,"score":1.0},{"id":2,"score":0.9},{"id":6,"score":0.5}]"#),
+        ]);
+        let client = scripted.client();
+
+        let native = client.recommend("docs", &[1], &[2], 2).await.unwrap();
+        assert_eq!(native.iter().map(|r| r.id).collect::<Vec<_>>(), [5, 6]);
+        assert_eq!(scripted.last_json(), serde_json::json!({"positive": [1], "negative": [2]}));
+        assert_eq!(scripted.last_query("limit").as_deref(), Some("4"));
+
+        // Without the native endpoint the examples are fetched and combined
+        let fallback = client.recommend("docs", &[1], &[2], 2).await.unwrap();
+        assert_eq!(fallback.iter().map(|r| r.id).collect::<Vec<_>>(), [6]);
+        assert_eq!(scripted.last_json()["vector"], serde_json::json!([2.0, -1.0]));
+        assert_eq!(
+            scripted.requests()[1..],
+            [
+                "POST /collection/docs/recommend",
+                "GET /collection/docs/vector/1",
+                "GET /collection/docs/vector/2",
+                "POST /collection/docs/search",
+            ]
+        );
+    }
+}
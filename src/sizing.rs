@@ -0,0 +1,207 @@
+//! Index sizing helpers: parameter recommendations and memory estimates.
+//!
+//! Heuristics from the HNSW paper (Malkov & Yashunin) and the hnswlib
+//! parameter guide: `m` of roughly 8 to 48 depending on the recall needed and
+//! the intrinsic dimensionality, `m0 = 2 * m`, and an `ef_construction`
+//! large enough for the build to reach that recall.
+
+use crate::error::{CasperError, Result};
+use crate::models::{CollectionInfo, HNSWIndexConfig};
+
+/// Metric of recommended configurations; set your own on the result
+const DEFAULT_METRIC: &str = "inner-product";
+/// Fewest links per node a recommendation goes down to
+const MIN_M: usize = 4;
+
+/// HNSW parameters suggested by [`recommend_hnsw_params`]
+#[derive(Debug, Clone)]
+pub struct HnswRecommendation {
+    /// Parameters for the target recall, regardless of the budget
+    pub recommended: HNSWIndexConfig,
+    /// Fewer links and possibly `i8` vectors, when `recommended` exceeds the
+    /// memory budget; expect recall below the target
+    pub within_budget: Option<HNSWIndexConfig>,
+}
+
+impl HnswRecommendation {
+    /// Parameters that fit the budget: `within_budget` if set, else
+    /// `recommended`
+    pub fn config(&self) -> &HNSWIndexConfig {
+        self.within_budget.as_ref().unwrap_or(&self.recommended)
+    }
+
+    /// Whether the budget forced parameters below the recommendation
+    pub fn is_degraded(&self) -> bool {
+        self.within_budget.is_some()
+    }
+}
+
+/// Suggest HNSW parameters for a collection.
+///
+/// - `collection_size`: number of vectors the index will hold
+/// - `dimension`: vector dimension
+/// - `target_recall`: desired recall@k, in `(0, 1]`
+/// - `memory_budget`: bytes available for vectors and graph, if limited
+///
+/// Higher recall and higher dimensions call for more links per node. When
+/// the recommendation exceeds the budget, a reduced configuration (fewer
+/// links, then `i8` vectors) that fits is returned alongside it, see
+/// [`HnswRecommendation::within_budget`]; a budget that cannot be met even
+/// then is an error. Configurations use the `inner-product` metric and no PQ.
+pub fn recommend_hnsw_params(
+    collection_size: usize,
+    dimension: usize,
+    target_recall: f32,
+    memory_budget: Option<usize>,
+) -> Result<HnswRecommendation> {
+    if !(target_recall > 0.0 && target_recall <= 1.0) {
+        return Err(CasperError::InvalidArgument(format!(
+            "target recall {} is not in (0, 1]",
+            target_recall
+        )));
+    }
+
+    let mut m: usize = match target_recall {
+        r if r < 0.9 => 8,
+        r if r < 0.95 => 12,
+        r if r < 0.99 => 16,
+        _ => 32,
+    };
+    // High-dimensional data needs more links for the same recall
+    if dimension > 256 {
+        m = m * 3 / 2;
+    }
+    // Larger graphs need more links to stay navigable
+    if collection_size > 10_000_000 {
+        m += 8;
+    }
+    let recommended = HNSWIndexConfig {
+        metric: DEFAULT_METRIC.to_string(),
+        quantization: "f32".to_string(),
+        m,
+        m0: 2 * m,
+        ef_construction: ef_construction(m),
+        pq_name: None,
+    };
+    let budget = match memory_budget {
+        Some(budget) if estimated_bytes(collection_size, dimension, &recommended) > budget => budget,
+        _ => return Ok(HnswRecommendation { recommended, within_budget: None }),
+    };
+
+    let mut config = recommended.clone();
+    for quantization in ["f32", "i8"] {
+        config.quantization = quantization.to_string();
+        for links in (MIN_M..=m).rev() {
+            config.m = links;
+            config.m0 = 2 * links;
+            if estimated_bytes(collection_size, dimension, &config) <= budget {
+                config.ef_construction = ef_construction(links);
+                return Ok(HnswRecommendation { recommended, within_budget: Some(config) });
+            }
+        }
+    }
+    Err(CasperError::InvalidArgument(format!(
+        "{} vectors of dimension {} do not fit in {} bytes",
+        collection_size, dimension, budget
+    )))
+}
+
+/// Build-time candidate list size for `m` links per node
+fn ef_construction(m: usize) -> usize {
+    (m * 8).max(100)
+}
+
+/// Predicted server-side memory of an HNSW-indexed collection, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Number of vectors the estimate is for
+    pub vectors: usize,
+    /// Stored vectors, in the index's quantization
+    pub vector_bytes: usize,
+    /// Graph links (u32 IDs)
+    pub graph_bytes: usize,
+    pub total_bytes: usize,
+}
+
+impl MemoryEstimate {
+    /// Memory of `vectors` vectors of `dimension` indexed with `config`.
+    ///
+    /// A node has `m0` links on layer 0 and, on average, `1 / (m - 1)` upper
+    /// layers of `m` links each. Components take 4 bytes for `f32`, 2 for
+    /// `f16`/`bf16` and 1 for `i8`/`u8`; PQ codes are counted as one byte per
+    /// component, an upper bound since a code covers a whole subvector.
+    pub fn for_vectors(vectors: usize, dimension: usize, config: &HNSWIndexConfig) -> Self {
+        let component = match config.quantization.as_str() {
+            "f16" | "bf16" => 2,
+            "i8" | "u8" => 1,
+            q if q.starts_with("pq") => 1,
+            _ => 4,
+        };
+        let upper_links = config.m as f64 / (config.m.max(2) - 1) as f64;
+        let link_bytes = ((config.m0 as f64 + upper_links) * 4.0).ceil() as usize;
+
+        let vector_bytes = vectors.saturating_mul(dimension.saturating_mul(component));
+        let graph_bytes = vectors.saturating_mul(link_bytes);
+        Self { vectors, vector_bytes, graph_bytes, total_bytes: vector_bytes.saturating_add(graph_bytes) }
+    }
+}
+
+/// Predict the memory a collection needs once full (`max_size` vectors)
+/// with an HNSW index configured as `config`, e.g. to size a machine before
+/// loading data
+pub fn estimate_memory(info: &CollectionInfo, config: &HNSWIndexConfig) -> MemoryEstimate {
+    let vectors = (info.max_size as usize).max(info.size);
+    MemoryEstimate::for_vectors(vectors, info.dimension, config)
+}
+
+fn estimated_bytes(vectors: usize, dimension: usize, config: &HNSWIndexConfig) -> usize {
+    MemoryEstimate::for_vectors(vectors, dimension, config).total_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_hnsw_params() {
+        let fast = recommend_hnsw_params(100_000, 128, 0.85, None).unwrap().recommended;
+        let exact = recommend_hnsw_params(100_000, 768, 0.99, Some(1 << 30)).unwrap();
+        assert!(!exact.is_degraded());
+        let exact = exact.recommended;
+        assert_eq!((fast.m, fast.m0, fast.ef_construction), (8, 16, 100));
+        assert_eq!((exact.m, exact.m0, exact.ef_construction), (48, 96, 384));
+        assert_eq!(exact.quantization, "f32");
+
+        // 1M x 768 f32 vectors alone need ~3 GB; i8 brings them under 1 GB
+        let budgeted = recommend_hnsw_params(1_000_000, 768, 0.99, Some(1 << 30)).unwrap();
+        assert!(budgeted.is_degraded());
+        assert_eq!((budgeted.recommended.m, budgeted.recommended.quantization.as_str()), (48, "f32"));
+        assert_eq!(budgeted.config().quantization, "i8");
+        assert!(estimated_bytes(1_000_000, 768, budgeted.config()) <= 1 << 30);
+
+        assert!(recommend_hnsw_params(1_000_000, 768, 0.99, Some(1 << 20)).is_err());
+        assert!(recommend_hnsw_params(1_000, 8, 1.5, None).is_err());
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        let info = CollectionInfo {
+            name: "docs".to_string(),
+            dimension: 128,
+            mutable: true,
+            has_index: false,
+            max_size: 1_000,
+            size: 10,
+            deleted: 0,
+            index: None,
+            labels: Default::default(),
+        };
+        let config = recommend_hnsw_params(1_000, 128, 0.9, None).unwrap().recommended;
+        let estimate = estimate_memory(&info, &config);
+        assert_eq!(estimate.vectors, 1_000);
+        assert_eq!(estimate.vector_bytes, 1_000 * 128 * 4);
+        // m = 12: 24 layer-0 links plus 12/11 upper links, 4 bytes each
+        assert_eq!(estimate.graph_bytes, 1_000 * 101);
+        assert_eq!(estimate.total_bytes, estimate.vector_bytes + estimate.graph_bytes);
+    }
+}
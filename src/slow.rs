@@ -0,0 +1,158 @@
+use reqwest::{Method, Request};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// An HTTP call that took longer than its configured threshold
+#[derive(Debug, Clone)]
+pub struct SlowCall {
+    pub method: Method,
+    /// Endpoint key the threshold was looked up by, see [`endpoint_key`]
+    pub endpoint: String,
+    pub collection: Option<String>,
+    /// `limit` query parameter, for searches
+    pub limit: Option<usize>,
+    /// Request body size in bytes (0 for streamed bodies)
+    pub payload_bytes: usize,
+    /// Time until the response headers arrived
+    pub duration: Duration,
+    pub threshold: Duration,
+}
+
+type SlowCallHook = Arc<dyn Fn(&SlowCall) + Send + Sync>;
+
+/// Slow-call thresholds and reporting, configured on the builder
+#[derive(Clone, Default)]
+pub(crate) struct SlowCallPolicy {
+    thresholds: HashMap<String, Duration>,
+    default: Option<Duration>,
+    hook: Option<SlowCallHook>,
+}
+
+impl SlowCallPolicy {
+    pub(crate) fn set_threshold(&mut self, endpoint: &str, threshold: Duration) {
+        self.thresholds.insert(endpoint.to_string(), threshold);
+    }
+
+    pub(crate) fn set_default(&mut self, threshold: Duration) {
+        self.default = Some(threshold);
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: SlowCallHook) {
+        self.hook = Some(hook);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.default.is_some() || !self.thresholds.is_empty()
+    }
+
+    /// Summary of a request taken before sending it, if a threshold applies
+    pub(crate) fn start(&self, request: &Request) -> Option<SlowCall> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let url = request.url();
+        let endpoint = endpoint_key(url.path());
+        let threshold = self.thresholds.get(&endpoint).copied().or(self.default)?;
+        let collection = url
+            .path()
+            .strip_prefix("/collection/")
+            .and_then(|rest| rest.split('/').next())
+            .map(str::to_string);
+        let limit = url
+            .query_pairs()
+            .find(|(key, _)| key == "limit")
+            .and_then(|(_, value)| value.parse().ok());
+        let payload_bytes = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map_or(0, <[u8]>::len);
+
+        Some(SlowCall {
+            method: request.method().clone(),
+            endpoint,
+            collection,
+            limit,
+            payload_bytes,
+            duration: Duration::ZERO,
+            threshold,
+        })
+    }
+
+    /// Report `call` if it took `elapsed` and that exceeds its threshold
+    pub(crate) fn finish(&self, mut call: SlowCall, elapsed: Duration) {
+        if elapsed <= call.threshold {
+            return;
+        }
+        call.duration = elapsed;
+
+        tracing::warn!(
+            method = %call.method,
+            endpoint = %call.endpoint,
+            collection = call.collection.as_deref(),
+            limit = call.limit,
+            payload_bytes = call.payload_bytes,
+            duration_ms = call.duration.as_millis() as u64,
+            threshold_ms = call.threshold.as_millis() as u64,
+            "slow Casper call"
+        );
+        if let Some(hook) = &self.hook {
+            hook(&call);
+        }
+    }
+}
+
+impl fmt::Debug for SlowCallPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowCallPolicy")
+            .field("thresholds", &self.thresholds)
+            .field("default", &self.default)
+            .field("hook", &self.hook.is_some())
+            .finish()
+    }
+}
+
+/// Endpoint key of a request path.
+///
+/// Collection endpoints are keyed `collection/<operation>` (e.g.
+/// `collection/search`, `collection/insert`, `collection/vector`), or
+/// `collection` for the collection itself; other endpoints by their first
+/// path segment (`collections`, `matrix`, `pq`, ...).
+pub fn endpoint_key(path: &str) -> String {
+    let mut segments = path.trim_start_matches('/').split('/');
+    let first = segments.next().unwrap_or_default();
+    match (first, segments.nth(1)) {
+        ("collection", Some(operation)) if !operation.is_empty() => format!("collection/{}", operation),
+        _ => first.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_keys_and_thresholds() {
+        assert_eq!(endpoint_key("/collection/docs/search"), "collection/search");
+        assert_eq!(endpoint_key("/collection/docs/vector/7"), "collection/vector");
+        assert_eq!(endpoint_key("/collection/docs"), "collection");
+        assert_eq!(endpoint_key("/matrix/list"), "matrix");
+
+        let mut policy = SlowCallPolicy::default();
+        policy.set_threshold("collection/search", Duration::from_millis(50));
+        let request = reqwest::Client::new()
+            .post("http://localhost:8080/collection/docs/search?limit=5&output=bin")
+            .body("{\"vector\":[]}")
+            .build()
+            .unwrap();
+        let call = policy.start(&request).unwrap();
+        assert_eq!(call.collection.as_deref(), Some("docs"));
+        assert_eq!(call.limit, Some(5));
+        assert_eq!(call.payload_bytes, 13);
+
+        let request = reqwest::Client::new().get("http://localhost:8080/collections").build().unwrap();
+        assert!(policy.start(&request).is_none());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::encoding::{self, FloatFormat};
+use crate::error::{CasperError, Result};
+use crate::memory;
+use crate::models::{BatchInsertOperation, BatchResult, BatchUpdateResponse};
+use crate::parallel;
+use crate::progress::{Phase, ProgressCounter};
+use crate::reduce::DimReducer;
+use crate::vector;
+use reqwest::{Body, Method};
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncRead;
+use tokio_stream::StreamExt;
+use tokio_util::io::ReaderStream;
+
+/// Inserts serialized into one body chunk, fewer under a tight memory budget
+const INSERTS_PER_CHUNK: usize = 256;
+
+impl CasperClient {
+    /// Batch update whose inserts are serialized lazily while the request
+    /// body is streamed, so only a chunk of vectors is held in memory at a
+    /// time instead of the whole JSON document.
+    ///
+    /// Streamed writes bypass the outbox and are never retried, since the
+    /// body cannot be replayed; signed requests carry an unsigned payload.
+    /// The audit event counts the inserts known upfront (the iterator's
+    /// lower size hint) plus the deletes.
+    pub async fn batch_update_streamed<I>(
+        &self,
+        collection_name: &str,
+        inserts: I,
+        delete: Vec<u32>,
+    ) -> Result<BatchResult>
+    where
+        I: IntoIterator<Item = BatchInsertOperation>,
+        I::IntoIter: Send + 'static,
+    {
+        let mut inserts = inserts.into_iter().peekable();
+        let items = inserts.size_hint().0 + delete.len();
+        // A chunk is held both as operations and as JSON text
+        let item_bytes = inserts.peek().map_or(0, |op| op.vector.len() * memory::JSON_FLOAT_BYTES);
+        let inserts_per_chunk = self.fit_budget(INSERTS_PER_CHUNK, item_bytes, 2);
+        let total = match inserts.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower as u64),
+            _ => None,
+        };
+        let progress =
+            ProgressCounter::new(self.progress.as_ref(), "batch_update_streamed", collection_name, Phase::Writing, total);
+        self.audited("batch_update_streamed", collection_name, items, async {
+            self.check_writable("batch_update_streamed")?;
+            let (mut chunks, state) = BatchChunks::new(
+                inserts,
+                inserts_per_chunk,
+                delete,
+                collection_name,
+                self.reducers.clone(),
+                self.normalizes(collection_name).await?,
+                self.float_format,
+            );
+            chunks.report_to(progress);
+
+            let url = self.collection_url(collection_name, "/update")?;
+            let sent = self
+                .http(Scope::Write, Method::POST, url)
+                .header("Content-Type", "application/json")
+                .body(Body::wrap_stream(tokio_stream::iter(chunks)))
+                .dispatch(self)
+                .await;
+            self.invalidate_search_cache(collection_name);
+
+            let (ids, error) = {
+                let mut state = state.lock().expect("stream state poisoned");
+                (std::mem::take(&mut state.ids), state.error.take())
+            };
+            // An encoding failure aborts the upload; report it rather than the
+            // resulting transport error
+            if let Some(error) = error {
+                return Err(error);
+            }
+            let body = self.handle_text_response(sent?).await?;
+            self.parse_batch_result(ids, Some(&body))
+        })
+        .await
+    }
+
+    /// Batch update streaming an already serialized JSON body (in the
+    /// [`BatchUpdateRequest`](crate::models::BatchUpdateRequest) format)
+    /// from `reader`, e.g. a file produced by an export.
+    ///
+    /// The body is sent as is: dimension reducers, normalization and the float
+    /// format are not applied. Since the request IDs are not known to the
+    /// client, the result only lists the IDs the server reported on, and
+    /// progress is reported in bytes only.
+    pub async fn batch_update_from_reader<R>(&self, collection_name: &str, reader: R) -> Result<BatchResult>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        const OPERATION: &str = "batch_update_from_reader";
+        self.audited(OPERATION, collection_name, 0, async {
+            self.check_writable(OPERATION)?;
+            let progress =
+                ProgressCounter::new(self.progress.as_ref(), OPERATION, collection_name, Phase::Writing, None);
+            let body = ReaderStream::new(reader).map(move |chunk| {
+                if let (Some(progress), Ok(bytes)) = (&progress, &chunk) {
+                    progress.advance(0, bytes.len() as u64);
+                }
+                chunk
+            });
+
+            let url = self.collection_url(collection_name, "/update")?;
+            let response = self
+                .http(Scope::Write, Method::POST, url)
+                .header("Content-Type", "application/json")
+                .body(Body::wrap_stream(body))
+                .dispatch(self)
+                .await;
+            self.invalidate_search_cache(collection_name);
+            let response = response?;
+
+            let body = self.handle_text_response(response).await?;
+            if body.trim().is_empty() {
+                return Ok(BatchResult::default());
+            }
+            let response: BatchUpdateResponse = serde_json::from_str(&body).map_err(|e| {
+                CasperError::InvalidResponse(format!("Failed to parse batch update response: {} - {}", e, body))
+            })?;
+            Ok(BatchResult::from_response(response.results.iter().map(|status| status.id), &response))
+        })
+        .await
+    }
+}
+
+/// IDs written so far and the error that aborted the body, if any
+#[derive(Default)]
+struct StreamState {
+    ids: Vec<u32>,
+    error: Option<CasperError>,
+}
+
+/// Body chunks of a streamed batch update.
+///
+/// The writer sits behind a mutex only to make the stream `Sync`, as reqwest
+/// requires; `next` has exclusive access and never locks.
+struct BatchChunks<I> {
+    inner: Mutex<ChunkWriter<I>>,
+}
+
+impl<I> BatchChunks<I> {
+    fn new(
+        inserts: I,
+        inserts_per_chunk: usize,
+        delete: Vec<u32>,
+        collection_name: &str,
+        reducers: Arc<HashMap<String, DimReducer>>,
+        normalize: bool,
+        float_format: FloatFormat,
+    ) -> (Self, Arc<Mutex<StreamState>>) {
+        let state = Arc::new(Mutex::new(StreamState::default()));
+        let writer = ChunkWriter {
+            inserts,
+            inserts_per_chunk,
+            delete: Some(delete),
+            started: false,
+            written: 0,
+            progress: None,
+            collection_name: collection_name.to_string(),
+            reducers,
+            normalize,
+            float_format,
+            state: state.clone(),
+        };
+        (Self { inner: Mutex::new(writer) }, state)
+    }
+
+    /// Count the inserts and bytes of every chunk on `progress`
+    fn report_to(&mut self, progress: Option<Arc<ProgressCounter>>) {
+        self.inner.get_mut().expect("chunk writer poisoned").progress = progress;
+    }
+}
+
+impl<I: Iterator<Item = BatchInsertOperation>> Iterator for BatchChunks<I> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let writer = self.inner.get_mut().expect("chunk writer poisoned");
+        match writer.next_chunk() {
+            Ok(chunk) => chunk.map(Ok),
+            Err(e) => {
+                let message = e.to_string();
+                writer.state.lock().expect("stream state poisoned").error = Some(e);
+                writer.delete = None;
+                Some(Err(io::Error::other(message)))
+            }
+        }
+    }
+}
+
+/// Serializes `{"insert":[...],"delete":[...]}` a chunk at a time
+struct ChunkWriter<I> {
+    inserts: I,
+    inserts_per_chunk: usize,
+    /// Deletes, taken when the closing chunk is written
+    delete: Option<Vec<u32>>,
+    started: bool,
+    /// Inserts written so far
+    written: usize,
+    progress: Option<Arc<ProgressCounter>>,
+    collection_name: String,
+    reducers: Arc<HashMap<String, DimReducer>>,
+    normalize: bool,
+    float_format: FloatFormat,
+    state: Arc<Mutex<StreamState>>,
+}
+
+impl<I: Iterator<Item = BatchInsertOperation>> ChunkWriter<I> {
+    /// Next body chunk, `None` once the document is complete
+    fn next_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(delete) = &self.delete else {
+            return Ok(None);
+        };
+
+        let mut chunk = Vec::new();
+        let mut ids = Vec::with_capacity(self.inserts_per_chunk);
+        if !self.started {
+            chunk.extend_from_slice(b"{\"insert\":[");
+            self.started = true;
+        }
+
+        let mut ops: Vec<BatchInsertOperation> = self.inserts.by_ref().take(self.inserts_per_chunk).collect();
+        let reducer = self.reducers.get(&self.collection_name);
+        let normalize = self.normalize;
+        parallel::batch_try_for_each(&mut ops, |op| {
+            if let Some(reducer) = reducer {
+                op.vector = reducer.transform(&op.vector)?;
+            }
+            if normalize {
+                vector::normalize(&mut op.vector)?;
+            }
+            Ok(())
+        })?;
+
+        for op in ops {
+            if self.written > 0 {
+                chunk.push(b',');
+            }
+            chunk.extend(encoding::to_json_vec(&op, self.float_format)?);
+            ids.push(op.id);
+            self.written += 1;
+        }
+
+        let inserted = ids.len();
+        let done = inserted < self.inserts_per_chunk;
+        if done {
+            chunk.extend_from_slice(b"],\"delete\":");
+            chunk.extend(serde_json::to_vec(delete)?);
+            chunk.push(b'}');
+            ids.extend(delete);
+            self.delete = None;
+        }
+        self.state.lock().expect("stream state poisoned").ids.extend(ids);
+        if let Some(progress) = &self.progress {
+            progress.advance(inserted as u64, chunk.len() as u64);
+        }
+        Ok(Some(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BatchUpdateRequest;
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_chunks_form_batch_document() {
+        let ops: Vec<BatchInsertOperation> = (0..600)
+            .map(|id| BatchInsertOperation { id, vector: vec![id as f32, 0.5], payload: None })
+            .collect();
+        let (chunks, state) = BatchChunks::new(
+            ops.clone().into_iter(),
+            INSERTS_PER_CHUNK,
+            vec![9000, 9001],
+            "docs",
+            Arc::new(HashMap::new()),
+            false,
+            FloatFormat::Shortest,
+        );
+
+        let chunks: Vec<Vec<u8>> = chunks.collect::<io::Result<_>>().unwrap();
+        assert_eq!(chunks.len(), 3);
+        let body: Vec<u8> = chunks.concat();
+        let expected = BatchUpdateRequest { insert: ops, delete: vec![9000, 9001], upsert: false };
+        assert_eq!(body, serde_json::to_vec(&expected).unwrap());
+        assert_eq!(state.lock().unwrap().ids.len(), 602);
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_from_reader() {
+        let scripted = Scripted::new([(200, r#"{"results":[{"id":1},{"id":2,"error":"bad"}]}"#)]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted)
+            .audit_hook(move |event| sink.lock().unwrap().push(event.operation))
+            .build()
+            .unwrap();
+
+        let body: &[u8] = br#"{"insert":[{"id":1,"vector":[1.0]},{"id":2,"vector":[2.0]}],"delete":[]}"#;
+        let result = client.batch_update_from_reader("docs", body).await.unwrap();
+        assert_eq!((result.succeeded.as_slice(), result.failure(2)), (&[1][..], Some("bad")));
+        assert_eq!(*events.lock().unwrap(), ["batch_update_from_reader"]);
+    }
+}
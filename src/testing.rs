@@ -0,0 +1,124 @@
+//! Test support: an in-memory HTTP transport
+
+use crate::transport::{HttpTransport, TransportFuture};
+use crate::CasperClient;
+use reqwest::header::HeaderMap;
+use reqwest::{Request, Response};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+/// Scripted outcome of one request
+#[derive(Debug)]
+enum Reply {
+    Respond(u16, String),
+    /// Fail in the transport with an I/O error of this kind
+    Fail(std::io::ErrorKind),
+}
+
+/// Answers requests with queued `(status, body)` pairs, recording
+/// `"METHOD /path"` and the URL of each
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scripted {
+    responses: Arc<Mutex<VecDeque<Reply>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+    urls: Arc<Mutex<Vec<Url>>>,
+    /// Headers of the last request
+    last_headers: Arc<Mutex<HeaderMap>>,
+    /// Body of the last request
+    last_body: Arc<Mutex<Vec<u8>>>,
+    /// Headers added to every response
+    response_headers: Vec<(&'static str, String)>,
+    /// How long each response takes
+    delay: Duration,
+}
+
+impl Scripted {
+    pub(crate) fn new<'a>(responses: impl IntoIterator<Item = (u16, &'a str)>) -> Self {
+        let scripted = Self::default();
+        scripted
+            .responses
+            .lock()
+            .unwrap()
+            .extend(responses.into_iter().map(|(status, body)| Reply::Respond(status, body.to_string())));
+        scripted
+    }
+
+    /// Fail the next unanswered request in the transport, e.g. with
+    /// `ConnectionRefused` for a server that cannot be reached
+    pub(crate) fn fail(self, kind: std::io::ErrorKind) -> Self {
+        self.responses.lock().unwrap().push_back(Reply::Fail(kind));
+        self
+    }
+
+    /// Answer each request after `delay`, e.g. so concurrent calls overlap
+    pub(crate) fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Add header `name` to every response
+    pub(crate) fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.response_headers.push((name, value.to_string()));
+        self
+    }
+
+    /// Client of `localhost` sending through this transport
+    pub(crate) fn client(&self) -> CasperClient {
+        CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(self.clone())
+            .build()
+            .unwrap()
+    }
+
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Query parameter `key` of the last request
+    pub(crate) fn last_query(&self, key: &str) -> Option<String> {
+        let urls = self.urls.lock().unwrap();
+        let (_, value) = urls.last()?.query_pairs().find(|(k, _)| k == key)?;
+        Some(value.into_owned())
+    }
+
+    /// Header `name` of the last request
+    pub(crate) fn last_header(&self, name: &str) -> Option<String> {
+        let headers = self.last_headers.lock().unwrap();
+        Some(headers.get(name)?.to_str().ok()?.to_string())
+    }
+
+    /// Body of the last request, parsed as JSON
+    pub(crate) fn last_json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.last_body.lock().unwrap()).expect("request body is not JSON")
+    }
+}
+
+impl HttpTransport for Scripted {
+    fn send(&self, request: Request) -> TransportFuture<'_> {
+        let line = format!("{} {}", request.method(), request.url().path());
+        self.requests.lock().unwrap().push(line);
+        self.urls.lock().unwrap().push(request.url().clone());
+        *self.last_headers.lock().unwrap() = request.headers().clone();
+        *self.last_body.lock().unwrap() = request.body().and_then(|body| body.as_bytes()).unwrap_or_default().to_vec();
+        let (status, body) = match self.responses.lock().unwrap().pop_front().expect("no response scripted") {
+            Reply::Respond(status, body) => (status, body),
+            Reply::Fail(kind) => return Box::pin(async move { Err(std::io::Error::from(kind).into()) }),
+        };
+        // JSON bodies are labelled as such, e.g. for search responses
+        let content_type = if body.starts_with(['{', '[']) { "application/json" } else { "text/plain" };
+        let mut response = http::Response::builder().status(status).header("Content-Type", content_type);
+        for (name, value) in &self.response_headers {
+            response = response.header(*name, value);
+        }
+        let response = response.body(body).unwrap();
+        let delay = self.delay;
+        Box::pin(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            Ok(Response::from(response))
+        })
+    }
+}
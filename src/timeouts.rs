@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+/// How long calls may take, at three levels.
+///
+/// - **connect**: establishing a connection, HTTP or gRPC (default 10s)
+/// - **attempt**: one HTTP request, from sending to the end of the
+///   response (default 30s); each retry gets a fresh attempt timeout
+/// - **operation**: a whole call including retries and their backoff, or a
+///   whole matrix upload (default none)
+///
+/// Matrix uploads are long-lived streams and are only bound by the connect
+/// timeout and the operation deadline, which is sent to the server as the
+/// gRPC deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    connect: Duration,
+    attempt: Option<Duration>,
+    operation: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self { connect: Duration::from_secs(10), attempt: Some(Duration::from_secs(30)), operation: None }
+    }
+}
+
+impl Timeouts {
+    /// Connection establishment timeout
+    pub fn connect(mut self, timeout: Duration) -> Self {
+        self.connect = timeout;
+        self
+    }
+
+    /// Timeout of each HTTP attempt
+    pub fn attempt(mut self, timeout: Duration) -> Self {
+        self.attempt = Some(timeout);
+        self
+    }
+
+    /// Deadline of a whole call, retries included
+    pub fn operation(mut self, deadline: Duration) -> Self {
+        self.operation = Some(deadline);
+        self
+    }
+
+    pub(crate) fn connect_timeout(&self) -> Duration {
+        self.connect
+    }
+
+    pub(crate) fn attempt_timeout(&self) -> Option<Duration> {
+        self.attempt
+    }
+
+    pub(crate) fn operation_deadline(&self) -> Option<Duration> {
+        self.operation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, TransportFuture};
+    use crate::{CasperClient, CasperError};
+    use reqwest::Request;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the timeout of each request and never answers
+    #[derive(Debug, Clone, Default)]
+    struct Stalled {
+        timeouts: Arc<Mutex<Vec<Option<Duration>>>>,
+    }
+
+    impl HttpTransport for Stalled {
+        fn send(&self, request: Request) -> TransportFuture<'_> {
+            self.timeouts.lock().unwrap().push(request.timeout().copied());
+            Box::pin(std::future::pending())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeouts() {
+        let stalled = Stalled::default();
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .timeouts(Timeouts::default().attempt(Duration::from_secs(5)).operation(Duration::from_millis(20)))
+            .transport(stalled.clone())
+            .build()
+            .unwrap();
+
+        let result = client.list_collections().await;
+        assert!(matches!(result, Err(CasperError::DeadlineExceeded(d)) if d == Duration::from_millis(20)));
+        assert_eq!(*stalled.timeouts.lock().unwrap(), vec![Some(Duration::from_secs(5))]);
+    }
+}
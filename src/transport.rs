@@ -0,0 +1,69 @@
+use crate::error::Result;
+use reqwest::{Client, Request, Response};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Future returned by [`HttpTransport::send`]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<Response>> + Send + 'a>>;
+
+/// Sends the client's HTTP requests over the wire.
+///
+/// The default transport is a [`reqwest::Client`]; plug in another one
+/// (hyper, ureq, an on-device broker) with
+/// [`CasperClientBuilder::transport`](crate::CasperClientBuilder::transport).
+/// Retries, signing, hedging and middleware all run above the transport, so
+/// it only has to deliver one request and return its response.
+///
+/// Requests arrive fully built: read them through [`Request::method`],
+/// [`Request::url`], [`Request::headers`] and [`Request::body`]. Bodies are
+/// buffered except for streamed batch updates, whose
+/// [`Body::as_bytes`](reqwest::Body::as_bytes) is `None`. Responses are
+/// built from an `http::Response` with `reqwest::Response::from`.
+pub trait HttpTransport: fmt::Debug + Send + Sync + 'static {
+    fn send(&self, request: Request) -> TransportFuture<'_>;
+}
+
+impl HttpTransport for Client {
+    fn send(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(self.execute(request).await?) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CasperClient;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers every request with an empty collection list
+    #[derive(Debug, Clone, Default)]
+    struct Canned {
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpTransport for Canned {
+        fn send(&self, request: Request) -> TransportFuture<'_> {
+            self.urls.lock().unwrap().push(request.url().to_string());
+            let response = http::Response::builder()
+                .status(200)
+                .header("Content-Type", "application/json")
+                .body(r#"{"collections":[]}"#)
+                .unwrap();
+            Box::pin(async move { Ok(Response::from(response)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport() {
+        let canned = Canned::default();
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(canned.clone())
+            .build()
+            .unwrap();
+
+        let list = client.list_collections().await.unwrap();
+        assert!(list.collections.is_empty());
+        assert_eq!(*canned.urls.lock().unwrap(), vec!["http://localhost:8080/collections"]);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::Result;
+use crate::models::BatchUpdateRequest;
+use crate::outbox::WriteOp;
+use reqwest::Method;
+use tokio_stream::StreamExt;
+
+/// IDs deleted per batch when truncating by scan
+const TRUNCATE_BATCH: usize = 1000;
+
+impl CasperClient {
+    /// Remove every vector of a collection, keeping its configuration,
+    /// labels and index settings, e.g. before re-ingesting it.
+    ///
+    /// Delete-protected collections are refused unless the client is
+    /// [`force`](CasperClient::force)d. Servers without the truncate
+    /// endpoint (404, 405 or 501) get the collection scanned and its IDs
+    /// deleted in batches of 1000, which is not atomic: vectors written
+    /// during the truncation may survive it.
+    pub async fn truncate_collection(&self, collection_name: &str) -> Result<()> {
+        self.audited("truncate_collection", collection_name, 0, async {
+            self.check_destructive("collection", collection_name)?;
+            let url = self.collection_url(collection_name, "/truncate")?;
+            let response = self.http(Scope::Admin, Method::POST, url).dispatch(self).await?;
+            self.invalidate_search_cache(collection_name);
+            if !matches!(response.status().as_u16(), 404 | 405 | 501) {
+                self.handle_text_response(response).await?;
+                return Ok(());
+            }
+
+            // The scan resumes after the last ID seen, so deleting what it
+            // returned does not disturb it
+            let mut ids = self.scan_vectors(collection_name).map(|item| item.map(|(id, _)| id));
+            let mut delete = Vec::with_capacity(TRUNCATE_BATCH);
+            loop {
+                let next = ids.next().await.transpose()?;
+                if let Some(id) = next {
+                    delete.push(id);
+                }
+                if delete.len() == TRUNCATE_BATCH || (next.is_none() && !delete.is_empty()) {
+                    let delete = std::mem::take(&mut delete);
+                    let request = BatchUpdateRequest { insert: Vec::new(), delete, upsert: false };
+                    self.send_write(collection_name, WriteOp::BatchUpdate(request)).await?;
+                }
+                if next.is_none() {
+                    return Ok(());
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_truncate_collection() {
+        let scripted = Scripted::new([
+            (200, ""),
+            (404, "not found"),
+            (200, r#"{"vectors":[{"id":3,"vector":[1.0]},{"id":8,"vector":[0.5]}]}"#),
+            (200, ""),
+        ]);
+        let client = scripted.client();
+
+        client.truncate_collection("docs").await.unwrap();
+        client.truncate_collection("docs").await.unwrap();
+        assert_eq!(
+            scripted.requests(),
+            [
+                "POST /collection/docs/truncate",
+                "POST /collection/docs/truncate",
+                "GET /collection/docs/scan",
+                "POST /collection/docs/update",
+            ]
+        );
+
+        let protected = crate::CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(Scripted::default())
+            .protect("docs")
+            .build()
+            .unwrap();
+        assert!(protected.truncate_collection("docs").await.is_err());
+    }
+}
@@ -0,0 +1,977 @@
+use crate::audit::{self, AuditHook};
+use crate::auth::{BearerAuth, Scope};
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::grpc::service::matrix_service::{
+    matrix_service_client::MatrixServiceClient,
+    upload_matrix_request, MatrixAbort, MatrixData, MatrixHeader, UploadMatrixRequest,
+};
+use crate::limits::RequestLimits;
+use crate::models::{MatrixInfo, UploadMatrixResult};
+use crate::progress::{Phase, ProgressCounter, ProgressHook};
+use reqwest::Method;
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::Request;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint};
+
+/// How long an aborted upload waits for the server to acknowledge the abort
+/// message before the RPC is cancelled outright.
+const ABORT_GRACE: Duration = Duration::from_secs(5);
+
+/// Upper bound of the protobuf framing around the floats of a chunk message
+const CHUNK_FRAMING_BYTES: usize = 32;
+
+/// Advice attached to [`CasperError::GrpcUnavailable`]
+const GRPC_REMEDIATION: &str = "check the gRPC port and that HTTP/2 traffic reaches the server, \
+    or enable CasperClientBuilder::http_upload_fallback";
+const NO_HTTP_UPLOAD_REMEDIATION: &str = "check the gRPC port and that HTTP/2 traffic reaches the server; \
+    it has no HTTP upload endpoint to fall back to";
+
+type GrpcClient = MatrixServiceClient<InterceptedService<Channel, BearerAuth>>;
+
+/// Channel to the gRPC matrix service, created on first use and shared by
+/// all clones of a client.
+///
+/// Creating the channel does not connect; the first upload does (or a
+/// [`Preflight`](crate::preflight::Preflight) warm-up), and later uploads
+/// reuse the connection.
+#[derive(Debug)]
+pub(crate) struct GrpcChannel {
+    addr: String,
+    connect_timeout: Duration,
+    channel: OnceLock<Channel>,
+}
+
+impl GrpcChannel {
+    pub(crate) fn new(addr: String, connect_timeout: Duration) -> Self {
+        Self { addr, connect_timeout, channel: OnceLock::new() }
+    }
+
+    pub(crate) fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// The shared channel; must be called within a Tokio runtime
+    fn channel(&self) -> Result<Channel> {
+        if let Some(channel) = self.channel.get() {
+            return Ok(channel.clone());
+        }
+        let channel = Endpoint::from_shared(self.addr.clone())
+            .map_err(|e| CasperError::GrpcConnection(e.to_string()))?
+            .connect_timeout(self.connect_timeout)
+            .connect_lazy();
+        Ok(self.channel.get_or_init(|| channel).clone())
+    }
+
+    /// Connect now rather than on the first upload; a no-op once the
+    /// channel exists
+    pub(crate) async fn warm(&self, timeout: Duration) -> Result<()> {
+        if self.channel.get().is_some() {
+            return Ok(());
+        }
+        let channel = Endpoint::from_shared(self.addr.clone())
+            .map_err(|e| CasperError::GrpcConnection(e.to_string()))?
+            .connect_timeout(timeout)
+            .connect()
+            .await
+            .map_err(|e| CasperError::PreflightFailed {
+                check: "grpc",
+                message: format!("cannot connect to {}: {}", self.addr, e),
+            })?;
+        let _ = self.channel.set(channel);
+        Ok(())
+    }
+}
+
+/// Channel and credentials of the gRPC matrix service
+#[derive(Debug, Clone)]
+pub(crate) struct GrpcTarget {
+    pub(crate) channel: Arc<GrpcChannel>,
+    pub(crate) token: Option<String>,
+    /// gRPC deadline of each upload
+    pub(crate) deadline: Option<Duration>,
+    pub(crate) progress: Option<ProgressHook>,
+    /// Client uploading over HTTP instead when gRPC is unreachable, see
+    /// [`CasperClientBuilder::http_upload_fallback`](crate::CasperClientBuilder::http_upload_fallback)
+    pub(crate) http_fallback: Option<CasperClient>,
+    pub(crate) limits: RequestLimits,
+}
+
+/// Row-wise matrix data of an upload
+#[derive(Clone)]
+enum MatrixRows {
+    Floats(Arc<[f32]>),
+    /// Little-endian f32s, decoded a chunk at a time
+    LeBytes(Arc<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl MatrixRows {
+    /// Number of f32 values
+    fn len(&self) -> usize {
+        match self {
+            MatrixRows::Floats(floats) => floats.len(),
+            MatrixRows::LeBytes(bytes) => (**bytes).as_ref().len() / 4,
+        }
+    }
+
+    fn floats(&self, range: Range<usize>) -> Vec<f32> {
+        match self {
+            MatrixRows::Floats(floats) => floats[range].to_vec(),
+            MatrixRows::LeBytes(bytes) => (**bytes).as_ref()[range.start * 4..range.end * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Debug for MatrixRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixRows::Floats(_) => write!(f, "Floats({})", self.len()),
+            MatrixRows::LeBytes(_) => write!(f, "LeBytes({})", self.len()),
+        }
+    }
+}
+
+/// A validated matrix upload, ready to be streamed.
+///
+/// A sharded upload carries only a range of the matrix chunks; chunk indices
+/// stay global so the server can reassemble the shards.
+#[derive(Debug, Clone)]
+pub(crate) struct MatrixUpload {
+    name: String,
+    dimension: usize,
+    vectors: MatrixRows,
+    chunk_floats: usize,
+    chunks: Range<usize>,
+    shard: Option<(u32, u32)>,
+    append: bool,
+}
+
+impl MatrixUpload {
+    /// Validate upload parameters.
+    ///
+    /// `chunk_floats` is raised to `dimension` when smaller.
+    pub(crate) fn new(
+        name: &str,
+        dimension: usize,
+        vectors: Vec<f32>,
+        chunk_floats: usize,
+    ) -> Result<Self> {
+        Self::with_rows(name, dimension, MatrixRows::Floats(vectors.into()), chunk_floats)
+    }
+
+    /// Validate an upload of little-endian f32 bytes, which are decoded one
+    /// chunk at a time as the upload proceeds
+    pub(crate) fn from_le_bytes(
+        name: &str,
+        dimension: usize,
+        bytes: Arc<dyn AsRef<[u8]> + Send + Sync>,
+        chunk_floats: usize,
+    ) -> Result<Self> {
+        let len = (*bytes).as_ref().len();
+        if !len.is_multiple_of(4) {
+            return Err(CasperError::InvalidArgument(format!(
+                "byte buffer length {} is not a whole number of f32 values",
+                len
+            )));
+        }
+        Self::with_rows(name, dimension, MatrixRows::LeBytes(bytes), chunk_floats)
+    }
+
+    fn with_rows(name: &str, dimension: usize, vectors: MatrixRows, chunk_floats: usize) -> Result<Self> {
+        if dimension == 0 {
+            return Err(CasperError::InvalidArgument(
+                "dimension must be greater than 0".to_string(),
+            ));
+        }
+
+        if !vectors.len().is_multiple_of(dimension) {
+            return Err(CasperError::InvalidArgument(format!(
+                "vector buffer length {} is not divisible by dimension {}",
+                vectors.len(),
+                dimension
+            )));
+        }
+
+        let chunk_floats = chunk_floats.max(dimension);
+        let total_chunks = vectors.len().div_ceil(chunk_floats);
+
+        Ok(Self {
+            name: name.to_string(),
+            dimension,
+            vectors,
+            chunk_floats,
+            chunks: 0..total_chunks,
+            shard: None,
+            append: false,
+        })
+    }
+
+    /// Append the rows to the existing matrix instead of replacing it
+    pub(crate) fn appending(self) -> Self {
+        Self { append: true, ..self }
+    }
+
+    /// Split into at most `count` uploads covering contiguous chunk ranges.
+    pub(crate) fn into_shards(self, count: usize) -> Vec<MatrixUpload> {
+        let total = self.chunks.len();
+        let count = count.clamp(1, total.max(1));
+        if count == 1 {
+            return vec![self];
+        }
+
+        let per_shard = total.div_ceil(count);
+        let count = total.div_ceil(per_shard);
+        (0..count)
+            .map(|idx| {
+                let start = idx * per_shard;
+                let end = (start + per_shard).min(total);
+                MatrixUpload {
+                    chunks: start..end,
+                    shard: Some((idx as u32, count as u32)),
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Number of matrix rows, across all shards
+    fn rows(&self) -> usize {
+        self.vectors.len() / self.dimension
+    }
+
+    /// Progress of uploading this matrix, counted in rows
+    fn progress(&self, hook: Option<&ProgressHook>) -> Option<Arc<ProgressCounter>> {
+        ProgressCounter::new(hook, "upload_matrix", &self.name, Phase::Uploading, Some(self.rows() as u64))
+    }
+
+    /// Upper bound of the encoded size of a chunk message
+    fn max_message_bytes(&self) -> usize {
+        self.chunk_floats * std::mem::size_of::<f32>() + CHUNK_FRAMING_BYTES
+    }
+
+    fn total_chunks(&self) -> usize {
+        self.vectors.len().div_ceil(self.chunk_floats)
+    }
+
+    fn header(&self) -> UploadMatrixRequest {
+        let (shard_index, shard_count) = self.shard.unwrap_or((0, 0));
+        let header = MatrixHeader {
+            name: self.name.clone(),
+            dimension: self.dimension as u32,
+            total_chunks: self.total_chunks() as u32,
+            max_vectors_per_chunk: (self.chunk_floats / self.dimension).max(1) as u32,
+            shard_index,
+            shard_count,
+            append: self.append,
+        };
+        UploadMatrixRequest {
+            payload: Some(upload_matrix_request::Payload::Header(header)),
+        }
+    }
+
+    /// Floats of chunk `chunk_idx`
+    fn chunk_floats(&self, chunk_idx: usize) -> Vec<f32> {
+        let start = chunk_idx * self.chunk_floats;
+        let end = (start + self.chunk_floats).min(self.vectors.len());
+        self.vectors.floats(start..end)
+    }
+
+    fn chunk(&self, chunk_idx: usize) -> UploadMatrixRequest {
+        let data = MatrixData {
+            chunk_index: chunk_idx as u32,
+            vector: self.chunk_floats(chunk_idx),
+        };
+        UploadMatrixRequest {
+            payload: Some(upload_matrix_request::Payload::Data(data)),
+        }
+    }
+
+    /// Feed header + chunks into `tx`, switching to an abort message as soon
+    /// as an abort is requested.
+    ///
+    /// Returns whether the abort message was sent: once every chunk is
+    /// queued there is nothing left to abort.
+    async fn produce(self, tx: mpsc::Sender<UploadMatrixRequest>, mut abort_rx: watch::Receiver<bool>) -> bool {
+        let messages = std::iter::once(self.header())
+            .chain(self.chunks.clone().map(|idx| self.chunk(idx)));
+
+        for msg in messages {
+            tokio::select! {
+                biased;
+                _ = abort_requested(&mut abort_rx) => {
+                    let abort = UploadMatrixRequest {
+                        payload: Some(upload_matrix_request::Payload::Abort(MatrixAbort {
+                            reason: "upload aborted by client".to_string(),
+                        })),
+                    };
+                    return tx.send(abort).await.is_ok();
+                }
+                sent = tx.send(msg) => {
+                    if sent.is_err() {
+                        return false;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// 64-bit FNV-1a hash of `bytes`, the checksum of matrices and exports
+pub(crate) fn fnv1a_64(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    fnv1a_64_extend(FNV_OFFSET, bytes)
+}
+
+/// Continue the FNV-1a hash `hash` over more `bytes`
+pub(crate) fn fnv1a_64_extend(hash: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.into_iter().fold(hash, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Locally computed summary of matrix data, compared against the server's
+/// view after an upload.
+///
+/// The checksum is the FNV-1a 64-bit hash of the little-endian f32 bytes,
+/// rendered as 16 lowercase hex digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixDigest {
+    pub dim: usize,
+    pub rows: usize,
+    pub checksum: String,
+}
+
+impl MatrixDigest {
+    /// Compute the digest of a row-wise flat matrix buffer.
+    pub fn compute(dimension: usize, vectors: &[f32]) -> Self {
+        // FNV-1a is order dependent, so unlike other preprocessing this
+        // cannot be split across threads
+        let hash = fnv1a_64(vectors.iter().flat_map(|v| v.to_le_bytes()));
+
+        Self {
+            dim: dimension,
+            rows: vectors.len().checked_div(dimension).unwrap_or(0),
+            checksum: format!("{:016x}", hash),
+        }
+    }
+
+    /// Compare against server-side matrix info.
+    ///
+    /// The checksum is only compared when the server reports one.
+    pub fn verify(&self, info: &MatrixInfo) -> Result<()> {
+        let mismatch = |field, expected: String, actual: String| CasperError::MatrixMismatch {
+            name: info.name.clone(),
+            field,
+            expected,
+            actual,
+        };
+
+        if info.dim != self.dim {
+            return Err(mismatch("dim", self.dim.to_string(), info.dim.to_string()));
+        }
+        if info.len != self.rows {
+            return Err(mismatch("rows", self.rows.to_string(), info.len.to_string()));
+        }
+        if let Some(checksum) = &info.checksum
+            && !checksum.eq_ignore_ascii_case(&self.checksum)
+        {
+            return Err(mismatch("checksum", self.checksum.clone(), checksum.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Handle to a matrix upload running in the background.
+///
+/// Await the handle to get the upload result. Dropping it detaches the
+/// upload; call [`UploadHandle::abort`] to cancel it instead.
+#[derive(Debug)]
+pub struct UploadHandle {
+    matrix_name: String,
+    abort_tx: watch::Sender<bool>,
+    task: JoinHandle<Result<UploadMatrixResult>>,
+}
+
+impl UploadHandle {
+    /// Name of the matrix being uploaded
+    pub fn matrix_name(&self) -> &str {
+        &self.matrix_name
+    }
+
+    /// Cancel the upload.
+    ///
+    /// The server is told to discard any partially received data, and the
+    /// handle resolves to [`CasperError::UploadAborted`]. An abort that comes
+    /// after the last chunk was sent cannot stop the server from committing
+    /// the matrix; the handle then resolves to the upload's outcome.
+    pub fn abort(&self) {
+        let _ = self.abort_tx.send(true);
+    }
+
+    /// Whether the upload has completed (successfully or not)
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Future for UploadHandle {
+    type Output = Result<UploadMatrixResult>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task).poll(cx).map(|joined| {
+            joined.unwrap_or_else(|e| {
+                Err(CasperError::Unknown(format!("matrix upload task failed: {}", e)))
+            })
+        })
+    }
+}
+
+/// Start streaming `upload` to the gRPC server at `target`.
+///
+/// The outcome is reported to `audit` once the upload finishes.
+pub(crate) fn spawn(target: GrpcTarget, upload: MatrixUpload, audit: Option<AuditHook>) -> UploadHandle {
+    let (abort_tx, abort_rx) = watch::channel(false);
+    let matrix_name = upload.name.clone();
+    let rows = upload.rows();
+    let name = matrix_name.clone();
+    let task = tokio::spawn(async move {
+        audit::record(audit, "upload_matrix", &name, rows, run(target, upload, abort_rx)).await
+    });
+
+    UploadHandle { matrix_name, abort_tx, task }
+}
+
+async fn run(
+    target: GrpcTarget,
+    upload: MatrixUpload,
+    mut abort_rx: watch::Receiver<bool>,
+) -> Result<UploadMatrixResult> {
+    if *abort_rx.borrow_and_update() {
+        return Err(CasperError::UploadAborted(upload.name));
+    }
+    target.limits.check_grpc_message(upload.max_message_bytes())?;
+    let progress = upload.progress(target.progress.as_ref());
+    upload_with_fallback(connect(target)?, upload, progress, abort_rx).await
+}
+
+/// Upload several matrices over a single gRPC connection.
+///
+/// At most `parallelism` uploads are in flight at once (multiplexed over the
+/// same HTTP/2 connection). Results are returned in input order; a failure of
+/// one matrix does not stop the others. Each upload is reported to `audit`.
+pub(crate) async fn upload_many(
+    target: GrpcTarget,
+    uploads: Vec<Result<MatrixUpload>>,
+    parallelism: usize,
+    audit: Option<AuditHook>,
+) -> Result<Vec<Result<UploadMatrixResult>>> {
+    let hook = target.progress.clone();
+    let target_limits = target.limits;
+    let connection = connect(target)?;
+    let semaphore = Arc::new(Semaphore::new(parallelism.max(1)));
+    // Nobody can abort these uploads; the receiver just never fires.
+    let (_abort_tx, abort_rx) = watch::channel(false);
+
+    let mut tasks = JoinSet::new();
+    let mut results: Vec<Option<Result<UploadMatrixResult>>> = Vec::with_capacity(uploads.len());
+
+    for (idx, upload) in uploads.into_iter().enumerate() {
+        results.push(None);
+        let upload = match upload.and_then(|upload| {
+            target_limits.check_grpc_message(upload.max_message_bytes())?;
+            Ok(upload)
+        }) {
+            Ok(upload) => upload,
+            Err(e) => {
+                results[idx] = Some(Err(e));
+                continue;
+            }
+        };
+
+        let connection = connection.clone();
+        let abort_rx = abort_rx.clone();
+        let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
+            CasperError::Unknown(format!("upload semaphore closed: {}", e))
+        })?;
+        let audit = audit.clone();
+        let progress = upload.progress(hook.as_ref());
+        tasks.spawn(async move {
+            let (name, rows) = (upload.name.clone(), upload.rows());
+            let upload = upload_with_fallback(connection, upload, progress, abort_rx);
+            let result = audit::record(audit, "upload_matrix", &name, rows, upload).await;
+            drop(permit);
+            (idx, result)
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        let (idx, result) = joined
+            .map_err(|e| CasperError::Unknown(format!("matrix upload task failed: {}", e)))?;
+        results[idx] = Some(result);
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.ok_or_else(|| CasperError::Unknown("matrix upload produced no result".to_string())))
+        .collect()
+}
+
+/// Upload one matrix as several concurrent shards over a single connection.
+///
+/// Every shard reports its own counts, which are summed into the result. If
+/// any shard fails, the remaining shards are aborted so the server discards
+/// the partial matrix, and the first error is returned. If gRPC is
+/// unreachable, the HTTP fallback uploads the matrix unsharded.
+pub(crate) async fn upload_sharded(
+    target: GrpcTarget,
+    upload: MatrixUpload,
+    streams: usize,
+) -> Result<UploadMatrixResult> {
+    target.limits.check_grpc_message(upload.max_message_bytes())?;
+    // Shards count towards the rows of the whole matrix
+    let progress = upload.progress(target.progress.as_ref());
+    let connection = connect(target)?;
+    let (abort_tx, abort_rx) = watch::channel(false);
+
+    let mut tasks = JoinSet::new();
+    for shard in upload.clone().into_shards(streams) {
+        tasks.spawn(stream_upload(connection.clone(), shard, progress.clone(), abort_rx.clone()));
+    }
+
+    let mut first_error = None;
+    let (mut total_vectors, mut total_chunks) = (0, 0);
+    while let Some(joined) = tasks.join_next().await {
+        let result = joined.unwrap_or_else(|e| {
+            Err(CasperError::Unknown(format!("matrix upload task failed: {}", e)))
+        });
+        match result {
+            Ok(shard) => {
+                total_vectors += shard.total_vectors;
+                total_chunks += shard.total_chunks;
+            }
+            Err(e) => {
+                let _ = abort_tx.send(true);
+                // Shards aborted because of an earlier failure are not the root cause.
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+        }
+    }
+
+    match (first_error, connection.http_fallback) {
+        (Some(e @ CasperError::GrpcUnavailable { .. }), Some(client)) => {
+            // The shards were aborted; nothing can abort the fallback
+            let (_, abort_rx) = watch::channel(false);
+            upload_http(&client, &upload, progress, abort_rx, e).await
+        }
+        (Some(e), _) => Err(e),
+        (None, _) => Ok(upload_result(total_vectors, total_chunks)),
+    }
+}
+
+/// Matrix service client and the settings of the uploads made with it
+#[derive(Clone)]
+struct Connection {
+    client: GrpcClient,
+    addr: String,
+    deadline: Option<Duration>,
+    http_fallback: Option<CasperClient>,
+}
+
+fn connect(target: GrpcTarget) -> Result<Connection> {
+    let auth = BearerAuth::new(target.token.as_deref())?;
+    Ok(Connection {
+        client: MatrixServiceClient::with_interceptor(target.channel.channel()?, auth),
+        addr: target.channel.addr().to_string(),
+        deadline: target.deadline,
+        http_fallback: target.http_fallback,
+    })
+}
+
+/// [`stream_upload`], over HTTP instead if gRPC is unreachable and the
+/// connection has a fallback
+async fn upload_with_fallback(
+    connection: Connection,
+    upload: MatrixUpload,
+    progress: Option<Arc<ProgressCounter>>,
+    abort_rx: watch::Receiver<bool>,
+) -> Result<UploadMatrixResult> {
+    let Some(client) = connection.http_fallback.clone() else {
+        return stream_upload(connection, upload, progress, abort_rx).await;
+    };
+    match stream_upload(connection, upload.clone(), progress.clone(), abort_rx.clone()).await {
+        Err(e @ CasperError::GrpcUnavailable { .. }) => upload_http(&client, &upload, progress, abort_rx, e).await,
+        result => result,
+    }
+}
+
+/// Stream one matrix over an established connection.
+async fn stream_upload(
+    connection: Connection,
+    upload: MatrixUpload,
+    progress: Option<Arc<ProgressCounter>>,
+    mut abort_rx: watch::Receiver<bool>,
+) -> Result<UploadMatrixResult> {
+    let Connection { mut client, addr, deadline, .. } = connection;
+    let name = upload.name.clone();
+    let dimension = upload.dimension;
+    let last_in_stream = upload.chunks.end.checked_sub(1);
+
+    let (tx, rx) = mpsc::channel::<UploadMatrixRequest>(4);
+    let mut producer = tokio::spawn(upload.produce(tx, abort_rx.clone()));
+
+    // Record the last data chunk pulled by the transport so a rejection
+    // can be attributed to it.
+    let tracker = ChunkTracker::default();
+    let stream = tracker.track(ReceiverStream::new(rx)).map(move |msg| {
+        if let (Some(progress), Some(upload_matrix_request::Payload::Data(data))) = (&progress, &msg.payload) {
+            let floats = data.vector.len();
+            progress.advance((floats / dimension) as u64, (floats * std::mem::size_of::<f32>()) as u64);
+        }
+        msg
+    });
+
+    let mut request = Request::new(stream);
+    if let Some(deadline) = deadline {
+        request.set_timeout(deadline);
+    }
+    let call = client.upload_matrix(request);
+    tokio::pin!(call);
+
+    let response = tokio::select! {
+        response = &mut call => response,
+        _ = abort_requested(&mut abort_rx) => {
+            if !(&mut producer).await.unwrap_or(true) {
+                // Every chunk was already queued, so the server commits the
+                // matrix whatever happens now; report its outcome.
+                (&mut call).await
+            } else {
+                // Give the server a moment to observe the abort message;
+                // dropping the call afterwards cancels the RPC outright.
+                let _ = tokio::time::timeout(ABORT_GRACE, &mut call).await;
+                return Err(CasperError::UploadAborted(name));
+            }
+        }
+    };
+
+    let response = response
+        .map_err(|status| upload_error(status, addr, tracker.last_chunk(), last_in_stream))?
+        .into_inner();
+
+    Ok(upload_result(response.total_vectors, response.total_chunks))
+}
+
+/// Error of a failed upload call, given the last data chunk the transport
+/// pulled.
+///
+/// The server only counts as unreachable (and eligible for the HTTP
+/// fallback) if it failed before any chunk went out: a connection lost
+/// mid-stream may have left part of the matrix on the server, which an
+/// upload restarted from chunk 0 would not account for.
+fn upload_error(
+    status: tonic::Status,
+    addr: String,
+    last_chunk: Option<u32>,
+    last_in_stream: Option<usize>,
+) -> CasperError {
+    if status.code() == tonic::Code::Unavailable && last_chunk.is_none() {
+        return CasperError::GrpcUnavailable {
+            addr,
+            message: status.message().to_string(),
+            remediation: GRPC_REMEDIATION,
+        };
+    }
+    let complete = last_chunk.is_some_and(|c| Some(c as usize) == last_in_stream);
+    CasperError::from_upload_status(status, last_chunk, complete)
+}
+
+/// Body of one chunk of an HTTP matrix upload
+#[derive(Debug, Serialize)]
+struct HttpMatrixChunk {
+    dimension: usize,
+    total_chunks: usize,
+    chunk_index: usize,
+    append: bool,
+    vector: Vec<f32>,
+}
+
+/// Upload `upload` unsharded as one `POST matrix/<name>/chunks` request per
+/// chunk, for servers unreachable over gRPC.
+///
+/// Servers without the endpoint (404, 405 or 501 on the first chunk) get
+/// `grpc_error` back, with remediation advice no longer suggesting the
+/// fallback.
+async fn upload_http(
+    client: &CasperClient,
+    upload: &MatrixUpload,
+    progress: Option<Arc<ProgressCounter>>,
+    abort_rx: watch::Receiver<bool>,
+    grpc_error: CasperError,
+) -> Result<UploadMatrixResult> {
+    let url = client.base_url.join(&format!("matrix/{}/chunks", upload.name))?;
+    let total_chunks = upload.total_chunks();
+    for chunk_index in 0..total_chunks {
+        if *abort_rx.borrow() {
+            return Err(CasperError::UploadAborted(upload.name.clone()));
+        }
+        let chunk = HttpMatrixChunk {
+            dimension: upload.dimension,
+            total_chunks,
+            chunk_index,
+            append: upload.append,
+            vector: upload.chunk_floats(chunk_index),
+        };
+        let floats = chunk.vector.len();
+        let response = client
+            .http(Scope::Admin, Method::POST, url.clone())
+            .header("Content-Type", "application/json")
+            .body(client.json_body(&chunk)?)
+            .dispatch(client)
+            .await?;
+        if chunk_index == 0 && matches!(response.status().as_u16(), 404 | 405 | 501) {
+            return Err(match grpc_error {
+                CasperError::GrpcUnavailable { addr, message, .. } => {
+                    CasperError::GrpcUnavailable { addr, message, remediation: NO_HTTP_UPLOAD_REMEDIATION }
+                }
+                other => other,
+            });
+        }
+        client.handle_text_response(response).await?;
+        if let Some(progress) = &progress {
+            progress.advance((floats / upload.dimension) as u64, (floats * std::mem::size_of::<f32>()) as u64);
+        }
+    }
+    Ok(upload_result(upload.rows() as u32, total_chunks as u32))
+}
+
+fn upload_result(total_vectors: u32, total_chunks: u32) -> UploadMatrixResult {
+    UploadMatrixResult {
+        success: true,
+        message: format!(
+            "Successfully uploaded {} vectors in {} chunks",
+            total_vectors, total_chunks
+        ),
+        total_vectors,
+        total_chunks,
+    }
+}
+
+/// Resolve once an abort is requested; never resolves if the handle is
+/// dropped without aborting.
+async fn abort_requested(abort_rx: &mut watch::Receiver<bool>) {
+    if abort_rx.wait_for(|aborted| *aborted).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Shared record of the last matrix data chunk pulled from an upload stream.
+#[derive(Debug, Clone, Default)]
+struct ChunkTracker {
+    // `u32::MAX` marks "no data chunk yet"
+    last: Arc<AtomicU32>,
+}
+
+impl ChunkTracker {
+    fn track<S>(&self, stream: S) -> impl Stream<Item = UploadMatrixRequest> + use<S>
+    where
+        S: Stream<Item = UploadMatrixRequest>,
+    {
+        self.last.store(u32::MAX, Ordering::Relaxed);
+        let last = self.last.clone();
+        stream.map(move |msg| {
+            if let Some(upload_matrix_request::Payload::Data(data)) = &msg.payload {
+                last.store(data.chunk_index, Ordering::Relaxed);
+            }
+            msg
+        })
+    }
+
+    fn last_chunk(&self) -> Option<u32> {
+        match self.last.load(Ordering::Relaxed) {
+            u32::MAX => None,
+            idx => Some(idx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[test]
+    fn test_shards_cover_all_chunks() {
+        let upload = MatrixUpload::new("m", 2, vec![0.0; 2 * 10], 4).unwrap();
+        assert_eq!(upload.total_chunks(), 5);
+
+        let shards = upload.into_shards(3);
+        let ranges: Vec<_> = shards.iter().map(|s| s.chunks.clone()).collect();
+        assert_eq!(ranges, vec![0..2, 2..4, 4..5]);
+        assert!(shards.iter().all(|s| s.shard.unwrap().1 == 3));
+    }
+
+    #[test]
+    fn test_le_bytes_upload_matches_floats() {
+        let floats: Vec<f32> = (0..10).map(|x| x as f32 * 0.5).collect();
+        let bytes: Vec<u8> = floats.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let from_floats = MatrixUpload::new("m", 2, floats, 4).unwrap();
+        let from_bytes = MatrixUpload::from_le_bytes("m", 2, Arc::new(bytes), 4).unwrap();
+
+        assert_eq!(from_bytes.rows(), 5);
+        for chunk in 0..from_bytes.total_chunks() {
+            assert_eq!(from_bytes.chunk(chunk), from_floats.chunk(chunk));
+        }
+        assert!(MatrixUpload::from_le_bytes("m", 2, Arc::new(vec![0u8; 6]), 4).is_err());
+    }
+
+    #[test]
+    fn test_digest_detects_truncation() {
+        let vectors = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let digest = MatrixDigest::compute(3, &vectors);
+        let mut info = MatrixInfo {
+            name: "m".to_string(),
+            dim: 3,
+            len: 2,
+            enabled: true,
+            checksum: Some(digest.checksum.clone()),
+        };
+        assert!(digest.verify(&info).is_ok());
+
+        info.len = 1;
+        assert!(matches!(
+            digest.verify(&info),
+            Err(CasperError::MatrixMismatch { field: "rows", .. })
+        ));
+
+        info.len = 2;
+        info.checksum = Some(MatrixDigest::compute(3, &vectors[..3]).checksum);
+        assert!(digest.verify(&info).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_abort_only_before_last_chunk() {
+        let upload = MatrixUpload::new("m", 2, vec![0.0; 4], 2).unwrap();
+        let is_abort = |msg: &UploadMatrixRequest| matches!(msg.payload, Some(upload_matrix_request::Payload::Abort(_)));
+
+        // Aborted with chunks still to send: the stream ends in an abort
+        let (abort_tx, abort_rx) = watch::channel(false);
+        let (tx, mut rx) = mpsc::channel(1);
+        let producer = tokio::spawn(upload.clone().produce(tx, abort_rx));
+        rx.recv().await.unwrap();
+        abort_tx.send(true).unwrap();
+        let mut messages = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            messages.push(msg);
+        }
+        assert!(producer.await.unwrap());
+        assert!(messages.last().is_some_and(is_abort));
+
+        // Aborted once everything is queued: nothing is left to abort
+        let (abort_tx, abort_rx) = watch::channel(false);
+        let (tx, mut rx) = mpsc::channel(8);
+        let producer = tokio::spawn(upload.produce(tx, abort_rx));
+        while rx.recv().await.is_some() {}
+        abort_tx.send_replace(true);
+        assert!(!producer.await.unwrap());
+    }
+
+    #[test]
+    fn test_only_connect_failures_are_unreachable() {
+        let unavailable = || tonic::Status::unavailable("connection reset");
+        let before = upload_error(unavailable(), "addr".to_string(), None, Some(3));
+        assert!(matches!(before, CasperError::GrpcUnavailable { .. }));
+        let mid_stream = upload_error(unavailable(), "addr".to_string(), Some(1), Some(3));
+        assert!(matches!(mid_stream, CasperError::GrpcConnection(_)));
+    }
+
+    /// Client over `scripted` whose every gRPC connection attempt is refused,
+    /// uploading over HTTP instead
+    fn refused_grpc_client(scripted: &Scripted) -> CasperClient {
+        let mut client = CasperClient::builder("http://127.0.0.1", 8080, 50051)
+            .transport(scripted.clone())
+            .http_upload_fallback(true)
+            .build()
+            .unwrap();
+        let refuse = tower::service_fn(|_: tonic::transport::Uri| async {
+            Err::<hyper_util::rt::TokioIo<tokio::net::TcpStream>, _>(std::io::Error::from(
+                std::io::ErrorKind::ConnectionRefused,
+            ))
+        });
+        let channel = Endpoint::from_static("http://127.0.0.1:50051").connect_with_connector_lazy(refuse);
+        client.grpc = Arc::new(GrpcChannel {
+            addr: client.grpc.addr().to_string(),
+            connect_timeout: Duration::from_secs(1),
+            channel: OnceLock::from(channel),
+        });
+        client
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_grpc_falls_back_to_http() {
+        let scripted = Scripted::new([(200, ""), (200, ""), (404, "not found")]);
+        let client = refused_grpc_client(&scripted);
+
+        let matrix = client.upload_matrix("m", 2, vec![0.0; 6], 4).await.unwrap();
+        assert_eq!((matrix.len, matrix.dim), (3, 2));
+        assert_eq!(scripted.requests(), ["POST /matrix/m/chunks", "POST /matrix/m/chunks"]);
+
+        // A server without the HTTP endpoint leaves the gRPC error standing
+        let error = client.upload_matrix("m", 2, vec![0.0; 2], 4).await.unwrap_err();
+        assert!(matches!(error, CasperError::GrpcUnavailable { remediation: NO_HTTP_UPLOAD_REMEDIATION, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_upload_matrix_grpc_returns_matrix() {
+        let scripted = Scripted::new([(200, ""), (200, "")]);
+        let client = CasperClient::builder("http://127.0.0.1", 8080, 50051)
+            .transport(scripted.clone())
+            .http_upload_fallback(true)
+            .build()
+            .unwrap();
+
+        // Nothing listens on the ingest address, so the upload falls back to HTTP
+        let matrix = client.upload_matrix_grpc("http://127.0.0.1:1", "m", 2, vec![0.0; 6], 4).await.unwrap();
+        assert_eq!((matrix.name(), matrix.len, matrix.dim), ("m", 3, 2));
+        assert_eq!(scripted.requests(), ["POST /matrix/m/chunks", "POST /matrix/m/chunks"]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_matrices() {
+        let scripted = Scripted::new([(200, ""), (200, ""), (200, "")]);
+        let client = refused_grpc_client(&scripted);
+
+        let matrices = vec![
+            ("a", 2, vec![0.0; 4]),
+            ("b", 2, vec![0.0; 3]),
+            ("c", 2, vec![0.0; 2]),
+            ("d", 3, vec![0.0; 6]),
+        ];
+        let results = client.upload_matrices(matrices, 8, 2).await.unwrap();
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c", "d"]);
+
+        // The malformed matrix fails on its own, the others are uploaded
+        let rows: Vec<Option<u32>> =
+            results.iter().map(|(_, result)| result.as_ref().ok().map(|r| r.total_vectors)).collect();
+        assert_eq!(rows, [Some(2), None, Some(1), Some(2)]);
+        assert!(matches!(results[1].1, Err(CasperError::InvalidArgument(_))));
+        assert_eq!(scripted.requests().len(), 3);
+    }
+}
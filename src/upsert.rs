@@ -0,0 +1,121 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use crate::models::{BatchUpdateRequest, BatchUpdateResponse, InsertRequest};
+use crate::outbox::WriteOp;
+use crate::version::ApiVersion;
+use reqwest::Method;
+
+impl CasperClient {
+    /// Insert a vector, overwriting the vector and payload stored under its
+    /// ID if there is one, where [`CasperClient::insert_vector`] would fail.
+    ///
+    /// Servers before API 1.2 cannot upsert; the ID is deleted and then
+    /// inserted instead, so searches may briefly miss it and a failed insert
+    /// leaves it deleted. Batches upsert with [`BatchUpdateRequest::upsert`].
+    pub async fn upsert_vector(&self, collection_name: &str, request: InsertRequest) -> Result<()> {
+        self.write(collection_name, WriteOp::Upsert(request)).await?;
+        Ok(())
+    }
+
+    /// On servers without native upserts, delete the IDs an upsert writes
+    /// and return it as a plain insert.
+    ///
+    /// Older servers ignore the upsert flag rather than rejecting it, so the
+    /// server's version is asked for first unless it is already known.
+    pub(crate) async fn translate_upsert(&self, collection_name: &str, op: WriteOp) -> Result<WriteOp> {
+        let is_upsert = match &op {
+            WriteOp::Upsert(_) => true,
+            WriteOp::BatchUpdate(request) => request.upsert,
+            _ => false,
+        };
+        if !is_upsert {
+            return Ok(op);
+        }
+        match self.ensure_api(ApiVersion::V1_2, "native upserts").await {
+            Ok(()) => return Ok(op),
+            Err(CasperError::ApiVersionUnsupported { .. }) => {}
+            Err(e) => return Err(e),
+        }
+
+        let (ids, op) = match op {
+            WriteOp::Upsert(request) => (vec![request.id], WriteOp::Insert(request)),
+            WriteOp::BatchUpdate(request) => {
+                let ids = request.insert.iter().map(|op| op.id).collect();
+                (ids, WriteOp::BatchUpdate(BatchUpdateRequest { upsert: false, ..request }))
+            }
+            op => return Ok(op),
+        };
+
+        let url = self.collection_url(collection_name, "/update")?;
+        let response = self
+            .http(Scope::Write, Method::POST, url)
+            .header("Content-Type", "application/json")
+            .body(self.json_body(&BatchUpdateRequest { insert: Vec::new(), delete: ids, upsert: false })?)
+            .dispatch(self)
+            .await?;
+        let body = self.handle_text_response(response).await?;
+        if body.trim().is_empty() {
+            return Ok(op);
+        }
+        // IDs that do not exist yet fail as not found, which is what an
+        // upsert expects; any other failure stops it
+        let response: BatchUpdateResponse = serde_json::from_str(&body).map_err(|e| {
+            CasperError::InvalidResponse(format!("Failed to parse batch update response: {} - {}", e, body))
+        })?;
+        let rejected = response.results.iter().find_map(|status| {
+            let error = status.error.as_deref()?;
+            (!error.to_ascii_lowercase().contains("not found")).then_some((status.id, error))
+        });
+        if let Some((id, reason)) = rejected {
+            return Err(CasperError::ItemRejected { id, reason: reason.to_string() });
+        }
+        Ok(op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Scripted;
+
+    #[tokio::test]
+    async fn test_upsert_vector() {
+        let request = InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None };
+        let scripted = Scripted::new([(200, r#"{"api_version":"1.2"}"#), (200, "")]);
+        scripted.client().upsert_vector("docs", request.clone()).await.unwrap();
+        assert_eq!(scripted.last_query("upsert").as_deref(), Some("true"));
+
+        // API 1.1 servers get a delete and an insert
+        let scripted = Scripted::new([(200, r#"{"results":[{"id":1,"error":"not found"}]}"#), (200, "")]);
+        let client = CasperClient::builder("http://localhost", 8080, 50051)
+            .transport(scripted.clone())
+            .api_version(ApiVersion::V1_1)
+            .build()
+            .unwrap();
+        client.upsert_vector("docs", request).await.unwrap();
+        assert_eq!(scripted.requests(), ["POST /collection/docs/update", "POST /collection/docs/insert"]);
+        assert_eq!(scripted.last_query("upsert"), None);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_negotiates_first() {
+        let request = InsertRequest { id: 1, vector: vec![0.5, 1.0], payload: None };
+        // A server that has not advertised its version yet is asked first
+        let scripted = Scripted::new([(200, r#"{"api_version":"1.1"}"#), (200, ""), (200, "")]);
+        scripted.client().upsert_vector("docs", request.clone()).await.unwrap();
+        assert_eq!(
+            scripted.requests(),
+            ["GET /version", "POST /collection/docs/update", "POST /collection/docs/insert"]
+        );
+
+        // Failures other than a missing ID stop the upsert before the insert
+        let scripted = Scripted::new([
+            (200, r#"{"api_version":"1.1"}"#),
+            (200, r#"{"results":[{"id":1,"error":"collection is not mutable"}]}"#),
+        ]);
+        let rejected = scripted.client().upsert_vector("docs", request).await;
+        assert!(matches!(rejected, Err(CasperError::ItemRejected { id: 1, .. })));
+        assert_eq!(scripted.requests().len(), 2);
+    }
+}
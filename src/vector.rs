@@ -0,0 +1,197 @@
+use crate::client::CasperClient;
+use crate::error::{CasperError, Result};
+use crate::kernels;
+use crate::models::{SearchRequest, SearchResponse, SearchResult};
+use crate::parallel::{self, FAN_OUT_LIMIT};
+
+/// Check that two vectors have the same dimension
+fn check_dim(a: &[f32], b: &[f32]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(CasperError::InvalidDimension { expected: a.len(), actual: b.len() });
+    }
+    Ok(())
+}
+
+/// Element-wise `a + b`
+pub fn add(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    check_dim(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x + y).collect())
+}
+
+/// Element-wise `a - b`
+pub fn sub(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    check_dim(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x - y).collect())
+}
+
+/// `v * factor`
+pub fn scale(v: &[f32], factor: f32) -> Vec<f32> {
+    v.iter().map(|x| x * factor).collect()
+}
+
+/// Dot product of two vectors of equal dimension
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    kernels::dot(a, b)
+}
+
+/// Squared Euclidean distance of two vectors of equal dimension
+pub fn squared_l2(a: &[f32], b: &[f32]) -> f32 {
+    kernels::squared_l2(a, b)
+}
+
+/// Euclidean norm
+pub fn norm(v: &[f32]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// Scale `v` to unit length in place.
+///
+/// Fails with [`CasperError::ZeroNormVector`] for the zero vector.
+pub fn normalize(v: &mut [f32]) -> Result<()> {
+    let n = norm(v);
+    if n == 0.0 {
+        return Err(CasperError::ZeroNormVector);
+    }
+    kernels::scale_in_place(v, 1.0 / n);
+    Ok(())
+}
+
+/// Exact re-ranking: the `limit` candidates with the highest inner product
+/// with `query`, best first, e.g. to refine approximate search results with
+/// the full-precision vectors.
+pub fn rerank<V: AsRef<[f32]>>(query: &[f32], candidates: &[(u32, V)], limit: usize) -> Result<SearchResponse> {
+    let mut results = Vec::with_capacity(candidates.len());
+    for (id, vector) in candidates {
+        check_dim(query, vector.as_ref())?;
+        results.push(SearchResult { id: *id, score: dot(query, vector.as_ref()) });
+    }
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Component-wise mean of `vectors`, `None` if there are none
+pub fn mean<V: AsRef<[f32]>>(vectors: &[V]) -> Result<Option<Vec<f32>>> {
+    let Some(first) = vectors.first() else {
+        return Ok(None);
+    };
+
+    let mut sum = vec![0.0f32; first.as_ref().len()];
+    for v in vectors {
+        check_dim(&sum, v.as_ref())?;
+        sum.iter_mut().zip(v.as_ref()).for_each(|(s, x)| *s += x);
+    }
+
+    let count = vectors.len() as f32;
+    sum.iter_mut().for_each(|s| *s /= count);
+    Ok(Some(sum))
+}
+
+/// Centroid of each group of vectors; empty groups yield `None`
+pub fn centroids<V: AsRef<[f32]>>(groups: &[Vec<V>]) -> Result<Vec<Option<Vec<f32>>>> {
+    groups.iter().map(|group| mean(group)).collect()
+}
+
+/// Analogy query `b - a + c` ("a is to b as c is to ?")
+pub fn analogy(a: &[f32], b: &[f32], c: &[f32]) -> Result<Vec<f32>> {
+    add(&sub(b, a)?, c)
+}
+
+/// Recommendation query from example vectors: `p + (p - n)`, where `p` and
+/// `n` are the means of the positive and negative examples, i.e. the positive
+/// centroid moved away from the negative one. Without negatives this is the
+/// positive centroid.
+pub fn recommendation_query<V: AsRef<[f32]>>(positives: &[V], negatives: &[V]) -> Result<Vec<f32>> {
+    let positive = mean(positives)?.ok_or_else(|| {
+        CasperError::InvalidArgument("recommendation requires at least one positive example".to_string())
+    })?;
+    match mean(negatives)? {
+        Some(negative) => add(&positive, &sub(&positive, &negative)?),
+        None => Ok(positive),
+    }
+}
+
+impl CasperClient {
+    /// Fetch stored vectors by ID, failing if any of them is missing
+    pub(crate) async fn fetch_vectors(&self, collection_name: &str, ids: &[u32]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(ids.len());
+        for &id in ids {
+            let vector = self
+                .get_vector(collection_name, id)
+                .await?
+                .ok_or(CasperError::VectorNotFound(id))?;
+            vectors.push(vector);
+        }
+        Ok(vectors)
+    }
+
+    /// Call `f` with every stored vector of a collection, probing each ID
+    /// below its `max_size` (in ID order within concurrent batches of 32)
+    pub(crate) async fn for_each_stored<F>(&self, collection_name: &str, mut f: F) -> Result<()>
+    where
+        F: FnMut(u32, Vec<f32>),
+    {
+        const BATCH: u32 = FAN_OUT_LIMIT as u32;
+        let max_size = self.get_collection(collection_name).await?.max_size;
+
+        for start in (0..max_size).step_by(BATCH as usize) {
+            let ids = start..max_size.min(start.saturating_add(BATCH));
+            let fetches = parallel::fan_out(ids.clone(), FAN_OUT_LIMIT, |id| {
+                let client = self.clone();
+                let collection_name = collection_name.to_string();
+                async move { client.get_vector(&collection_name, id).await }
+            });
+            for (id, vector) in ids.zip(fetches.collect().await?) {
+                if let Some(vector) = vector? {
+                    f(id, vector);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Search with the centroid of stored vectors `ids` as the query
+    pub async fn search_centroid(
+        &self,
+        collection_name: &str,
+        ids: &[u32],
+        limit: usize,
+    ) -> Result<SearchResponse> {
+        let vectors = self.fetch_vectors(collection_name, ids).await?;
+        let centroid = mean(&vectors)?.ok_or_else(|| {
+            CasperError::InvalidArgument("search_centroid requires at least one ID".to_string())
+        })?;
+
+        let request = SearchRequest { vector: centroid, limit: Some(limit), ..Default::default() };
+        self.query(collection_name, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_and_analogy() {
+        let vectors = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+        assert_eq!(mean(&vectors).unwrap(), Some(vec![2.0, 3.0]));
+        assert!(mean::<Vec<f32>>(&[]).unwrap().is_none());
+        assert!(mean(&[vec![1.0], vec![1.0, 2.0]]).is_err());
+
+        let king_queen = analogy(&[1.0, 0.0], &[1.0, 1.0], &[2.0, 0.0]).unwrap();
+        assert_eq!(king_queen, vec![2.0, 1.0]);
+
+        let query = recommendation_query(&vectors, &[vec![0.0, 3.0]]).unwrap();
+        assert_eq!(query, vec![4.0, 3.0]);
+        assert!(recommendation_query::<Vec<f32>>(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_rerank() {
+        let candidates = vec![(1, vec![1.0, 0.0]), (2, vec![0.6, 0.8]), (3, vec![0.0, 1.0])];
+        let ranked = rerank(&[0.0, 1.0], &candidates, 2).unwrap();
+        let ids: Vec<u32> = ranked.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+        assert!(rerank(&[1.0], &candidates, 2).is_err());
+    }
+}
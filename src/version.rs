@@ -0,0 +1,200 @@
+use crate::auth::Scope;
+use crate::client::{CasperClient, Dispatch};
+use crate::error::{CasperError, Result};
+use reqwest::{Method, Response};
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Header carrying the API version, sent on every request and advertised
+/// by the server on its responses
+pub const API_VERSION_HEADER: &str = "x-casper-api-version";
+
+/// Version of the Casper HTTP API (`major.minor`).
+///
+/// Servers accept requests of any earlier minor version of their major
+/// version. The client adapts to the lower of its pinned version and the
+/// version the server advertises: features a server is too old for either
+/// fall back to a client-side equivalent or fail with
+/// [`CasperError::ApiVersionUnsupported`] before anything is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApiVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ApiVersion {
+    /// First release: collections, vectors, binary search, HNSW, matrices and PQ
+    pub const V1_0: ApiVersion = ApiVersion::new(1, 0);
+    /// Adds collection labels, search filters and decay, explain, query
+    /// profiles, search by ID, recommendations and IVF-PQ indexes
+    pub const V1_1: ApiVersion = ApiVersion::new(1, 1);
+    /// Adds native upserts, sharded matrix uploads and matrix appends
+    pub const V1_2: ApiVersion = ApiVersion::new(1, 2);
+    /// Version this client is written against
+    pub const CURRENT: ApiVersion = ApiVersion::V1_2;
+
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    fn pack(self) -> u32 {
+        (self.major as u32) << 16 | self.minor as u32
+    }
+
+    fn unpack(packed: u32) -> Self {
+        Self::new((packed >> 16) as u16, packed as u16)
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = CasperError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().trim_start_matches('v');
+        let (major, minor) = s.split_once('.').unwrap_or((s, "0"));
+        match (major.parse(), minor.parse()) {
+            (Ok(major), Ok(minor)) => Ok(Self::new(major, minor)),
+            _ => Err(CasperError::InvalidResponse(format!("invalid API version '{}'", s))),
+        }
+    }
+}
+
+/// `GET /version` response
+#[derive(Deserialize)]
+struct VersionResponse {
+    api_version: String,
+}
+
+/// Pinned version and the last version the server advertised, shared by
+/// all clones of a client
+#[derive(Debug)]
+pub(crate) struct ApiState {
+    pinned: ApiVersion,
+    /// Packed server version, 0 while unknown
+    server: AtomicU32,
+}
+
+impl ApiState {
+    pub(crate) fn new(pinned: ApiVersion) -> Self {
+        Self { pinned, server: AtomicU32::new(0) }
+    }
+
+    pub(crate) fn pinned(&self) -> ApiVersion {
+        self.pinned
+    }
+
+    pub(crate) fn server(&self) -> Option<ApiVersion> {
+        match self.server.load(Ordering::Relaxed) {
+            0 => None,
+            packed => Some(ApiVersion::unpack(packed)),
+        }
+    }
+
+    /// Remember the version advertised on `response`, if any
+    pub(crate) fn observe(&self, response: &Response) {
+        let advertised = response
+            .headers()
+            .get(API_VERSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<ApiVersion>().ok());
+        if let Some(version) = advertised {
+            self.server.store(version.pack(), Ordering::Relaxed);
+        }
+    }
+
+    /// Version requests are shaped for: the pinned version, lowered to the
+    /// server's once known
+    pub(crate) fn effective(&self) -> ApiVersion {
+        self.server().map_or(self.pinned, |server| server.min(self.pinned))
+    }
+}
+
+impl CasperClient {
+    /// API version requests are currently shaped for, see [`ApiVersion`]
+    pub fn api_version(&self) -> ApiVersion {
+        self.api.effective()
+    }
+
+    /// Version the server last advertised, `None` before any response
+    pub fn server_api_version(&self) -> Option<ApiVersion> {
+        self.api.server()
+    }
+
+    /// Ask the server for its API version and check it is compatible.
+    ///
+    /// Servers predating `GET /version` are taken to speak
+    /// [`ApiVersion::V1_0`]. Fails with
+    /// [`CasperError::IncompatibleApiVersion`] if the major versions differ.
+    /// Returns the version requests will be shaped for.
+    pub async fn negotiate_api_version(&self) -> Result<ApiVersion> {
+        let url = self.base_url.join("version")?;
+        let response = self.http(Scope::Read, Method::GET, url).dispatch(self).await?;
+
+        let server = if response.status() == 404 {
+            ApiVersion::V1_0
+        } else {
+            let version: VersionResponse = self.handle_response(response).await?;
+            version.api_version.parse()?
+        };
+        self.api.server.store(server.pack(), Ordering::Relaxed);
+
+        let pinned = self.api.pinned();
+        if server.major != pinned.major {
+            return Err(CasperError::IncompatibleApiVersion { client: pinned, server });
+        }
+        Ok(self.api.effective())
+    }
+
+    /// Whether the server may be sent requests of API version `version`
+    pub(crate) fn supports_api(&self, version: ApiVersion) -> bool {
+        self.api.effective() >= version
+    }
+
+    /// Fail unless `feature`, introduced in `version`, can be used
+    pub(crate) fn require_api(&self, version: ApiVersion, feature: &'static str) -> Result<()> {
+        if self.supports_api(version) {
+            return Ok(());
+        }
+        Err(CasperError::ApiVersionUnsupported { feature, required: version, available: self.api.effective() })
+    }
+
+    /// [`CasperClient::require_api`] for features an older server would
+    /// silently ignore instead of rejecting: unless the server already
+    /// advertised its version, it is asked for first rather than assumed
+    pub(crate) async fn ensure_api(&self, version: ApiVersion, feature: &'static str) -> Result<()> {
+        if self.api.server().is_none() && self.api.pinned() >= version {
+            self.negotiate_api_version().await?;
+        }
+        self.require_api(version, feature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_effective_version() {
+        assert_eq!("1.1".parse::<ApiVersion>().unwrap(), ApiVersion::V1_1);
+        assert_eq!("v2".parse::<ApiVersion>().unwrap(), ApiVersion::new(2, 0));
+        assert!("one".parse::<ApiVersion>().is_err());
+        assert_eq!(ApiVersion::unpack(ApiVersion::new(3, 7).pack()).to_string(), "3.7");
+
+        let state = ApiState::new(ApiVersion::CURRENT);
+        assert_eq!(state.effective(), ApiVersion::CURRENT);
+        state.server.store(ApiVersion::V1_0.pack(), Ordering::Relaxed);
+        assert_eq!(state.effective(), ApiVersion::V1_0);
+
+        let pinned = ApiState::new(ApiVersion::V1_0);
+        pinned.server.store(ApiVersion::new(1, 4).pack(), Ordering::Relaxed);
+        assert_eq!(pinned.effective(), ApiVersion::V1_0);
+    }
+}